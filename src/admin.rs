@@ -0,0 +1,49 @@
+use std::time::Instant;
+
+use salvo::prelude::*;
+use serde::Serialize;
+
+use crate::handlers::{parse_bearer_token, unauthorized};
+use crate::state::{HistogramBucket, IdentityUsage, RequestSummary, app_state};
+
+const TOP_IDENTITIES_LIMIT: usize = 10;
+/// Sessions above this many requests are listed as potential abuse.
+const HIGH_REQUEST_COUNT_THRESHOLD: u64 = 1000;
+
+#[derive(Debug, Serialize)]
+struct SessionStatsResponse {
+    session_count: usize,
+    total_tokens: u64,
+    top_identities: Vec<IdentityUsage>,
+    token_usage_buckets: Vec<HistogramBucket>,
+    age_buckets_secs: Vec<HistogramBucket>,
+    requests: RequestSummary,
+}
+
+/// Only routed when `admin_api_key` is configured; the key is separate from
+/// the client API key so operators can share one without the other.
+#[handler]
+pub async fn session_stats(req: &mut Request, res: &mut Response) {
+    let state = app_state();
+    let provided = req
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_bearer_token);
+    if provided.is_none() || provided != state.config.admin_api_key.as_deref() {
+        unauthorized(res, "invalid or missing admin bearer token");
+        return;
+    }
+
+    let sessions = &state.sessions;
+    res.render(Json(SessionStatsResponse {
+        session_count: sessions.session_count().await,
+        total_tokens: sessions.total_token_usage().await,
+        top_identities: sessions.top_identities(TOP_IDENTITIES_LIMIT).await,
+        token_usage_buckets: sessions.usage_histogram().await,
+        age_buckets_secs: sessions.age_histogram(Instant::now()).await,
+        requests: sessions
+            .request_summary(HIGH_REQUEST_COUNT_THRESHOLD, TOP_IDENTITIES_LIMIT)
+            .await,
+    }));
+}
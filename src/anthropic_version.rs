@@ -0,0 +1,117 @@
+use crate::config::Config;
+
+/// The `anthropic-version` a request is handled as: `anthropic_version_override`
+/// when configured, otherwise the client's header. Injected into the `Depot`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnthropicVersion(pub String);
+
+impl AnthropicVersion {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Validates a configured version; versions are `YYYY-MM-DD` dates, so
+/// well-formed values order correctly as plain strings.
+pub fn parse_configured_version(
+    name: &str,
+    value: Option<String>,
+) -> Result<Option<String>, String> {
+    let Some(value) = value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(None);
+    };
+    if !is_version_date(&value) {
+        return Err(format!(
+            "Invalid {name} value '{value}'. Expected YYYY-MM-DD."
+        ));
+    }
+    Ok(Some(value))
+}
+
+/// Without `min_anthropic_version` the client's header is accepted as-is;
+/// with it, the effective version must be present, well-formed and not older.
+pub fn resolve_anthropic_version(
+    client_version: Option<&str>,
+    config: &Config,
+) -> Result<Option<AnthropicVersion>, String> {
+    let effective = config
+        .anthropic_version_override
+        .as_deref()
+        .or(client_version
+            .map(str::trim)
+            .filter(|value| !value.is_empty()));
+
+    if let Some(minimum) = config.min_anthropic_version.as_deref() {
+        let Some(version) = effective else {
+            return Err(format!(
+                "anthropic-version header is required (minimum supported version is {minimum})"
+            ));
+        };
+        if !is_version_date(version) {
+            return Err(format!(
+                "invalid anthropic-version '{version}'; expected YYYY-MM-DD"
+            ));
+        }
+        if version < minimum {
+            return Err(format!(
+                "anthropic-version {version} is older than the minimum supported version {minimum}"
+            ));
+        }
+    }
+
+    Ok(effective.map(|version| AnthropicVersion(version.to_string())))
+}
+
+fn is_version_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(index, byte)| match index {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnthropicVersion, parse_configured_version, resolve_anthropic_version};
+
+    #[test]
+    fn rejects_missing_malformed_and_older_versions_when_minimum_is_set() {
+        let mut config = crate::upstream::test_support::test_config();
+        assert_eq!(resolve_anthropic_version(None, &config), Ok(None));
+
+        config.min_anthropic_version = Some("2023-06-01".to_string());
+        assert!(resolve_anthropic_version(None, &config).is_err());
+        assert!(resolve_anthropic_version(Some("latest"), &config).is_err());
+        let error = resolve_anthropic_version(Some("2023-01-01"), &config).expect_err("too old");
+        assert!(error.contains("older than the minimum"));
+        assert_eq!(
+            resolve_anthropic_version(Some(" 2024-01-01 "), &config),
+            Ok(Some(AnthropicVersion("2024-01-01".to_string())))
+        );
+    }
+
+    #[test]
+    fn override_replaces_client_version() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.min_anthropic_version = Some("2023-06-01".to_string());
+        config.anthropic_version_override = Some("2023-06-01".to_string());
+
+        assert_eq!(
+            resolve_anthropic_version(Some("2020-01-01"), &config),
+            Ok(Some(AnthropicVersion("2023-06-01".to_string())))
+        );
+    }
+
+    #[test]
+    fn validates_configured_version_format() {
+        assert_eq!(
+            parse_configured_version("X", Some(" ".to_string())),
+            Ok(None)
+        );
+        assert!(parse_configured_version("X", Some("2023-6-1".to_string())).is_err());
+    }
+}
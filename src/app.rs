@@ -1,31 +1,60 @@
 use dotenvy::dotenv;
+use salvo::conn::Acceptor;
+use salvo::conn::rustls::{Keycert, RustlsConfig};
 use salvo::prelude::*;
+use salvo::server::ServerHandle;
 use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
+use crate::batches::BatchStore;
+use crate::capture::RequestCapture;
 use crate::config::Config;
 use crate::handlers;
+use crate::rate_limit::RateLimiter;
 use crate::state::{AppState, SessionManager, set_app_state};
+use crate::telemetry::Telemetry;
+use crate::token_count::TokenCounter;
 use crate::upstream::UpstreamClient;
 use crate::utils::init_tracing;
 
 pub async fn run() {
     let _ = dotenv();
     let config = load_config_or_exit();
-    init_tracing(&config.log_level);
+    let telemetry = init_telemetry_or_exit(&config);
+    init_tracing(
+        &config.log_level,
+        config.log_format,
+        telemetry.as_ref().map(Telemetry::tracer),
+    );
     warn_if_validation_disabled(&config);
 
     let upstream = build_upstream_or_exit(config.clone());
+    let tokenizer = build_tokenizer_or_exit();
     let sessions = SessionManager::new(
         config.session_ttl_min_secs,
         config.session_ttl_max_secs,
         config.session_cleanup_interval_secs,
     );
-    spawn_session_cleanup_task(sessions.clone(), config.session_cleanup_interval_secs);
+    let rate_limiter = RateLimiter::new(config.rate_limit_rpm, config.rate_limit_burst);
+    let batches = BatchStore::default();
+    spawn_session_cleanup_task(
+        sessions.clone(),
+        rate_limiter.clone(),
+        batches.clone(),
+        &config,
+    );
+    if config.prewarm_upstream && !config.dry_run {
+        spawn_upstream_prewarm(upstream.clone());
+    }
+    let capture = build_capture_or_exit(&config);
     set_app_state(AppState {
         config: config.clone(),
         upstream,
         sessions,
+        batches,
+        tokenizer,
+        rate_limiter,
+        capture,
     });
 
     info!(
@@ -33,10 +62,92 @@ pub async fn run() {
         config.host, config.port
     );
 
-    let acceptor = TcpListener::new((config.host.as_str(), config.port))
-        .bind()
-        .await;
-    Server::new(acceptor).serve(handlers::router()).await;
+    let listener = TcpListener::new((config.host.as_str(), config.port));
+    let shutdown_timeout = Duration::from_secs(config.shutdown_timeout_secs);
+    let service = handlers::service(&config);
+    match load_tls_config_or_exit(&config) {
+        Some(tls_config) => {
+            info!("TLS enabled; serving HTTPS");
+            serve(
+                listener.rustls(tls_config).bind().await,
+                service,
+                shutdown_timeout,
+            )
+            .await;
+        }
+        None => {
+            info!("TLS not configured; serving plain HTTP");
+            serve(listener.bind().await, service, shutdown_timeout).await;
+        }
+    }
+    info!("Server stopped");
+    if let Some(telemetry) = telemetry {
+        telemetry.shutdown();
+    }
+}
+
+async fn serve<A: Acceptor + Send>(acceptor: A, service: Service, shutdown_timeout: Duration) {
+    let server = Server::new(acceptor);
+    spawn_shutdown_listener(server.handle(), shutdown_timeout);
+    server.serve(service).await;
+}
+
+fn load_tls_config_or_exit(config: &Config) -> Option<RustlsConfig> {
+    let (cert_path, key_path) = match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        _ => return None,
+    };
+    let keycert = Keycert::new()
+        .cert_from_path(cert_path)
+        .and_then(|keycert| keycert.key_from_path(key_path));
+    match keycert {
+        Ok(keycert) => Some(RustlsConfig::new(keycert)),
+        Err(error) => {
+            eprintln!("TLS Error: failed to read certificate or key: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn spawn_shutdown_listener(handle: ServerHandle, timeout: Duration) {
+    tokio::spawn(async move {
+        wait_for_shutdown_signal().await;
+        info!(
+            timeout_secs = timeout.as_secs(),
+            "Shutdown signal received; draining in-flight requests"
+        );
+        handle.stop_graceful(timeout);
+    });
+}
+
+async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(error) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {error}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(error) => {
+                warn!("Failed to listen for SIGTERM: {error}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
 }
 
 fn load_config_or_exit() -> Config {
@@ -49,6 +160,16 @@ fn load_config_or_exit() -> Config {
     }
 }
 
+fn init_telemetry_or_exit(config: &Config) -> Option<Telemetry> {
+    match Telemetry::init(config) {
+        Ok(telemetry) => telemetry,
+        Err(error) => {
+            eprintln!("Initialization Error: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn warn_if_validation_disabled(config: &Config) {
     if config.anthropic_api_key.is_none() {
         warn!("ANTHROPIC_API_KEY not set. Client API key validation is disabled.");
@@ -65,12 +186,68 @@ fn build_upstream_or_exit(config: Config) -> UpstreamClient {
     }
 }
 
-fn spawn_session_cleanup_task(sessions: SessionManager, interval_secs: u64) {
+fn build_capture_or_exit(config: &Config) -> Option<RequestCapture> {
+    match RequestCapture::from_config(config) {
+        Ok(capture) => capture,
+        Err(error) => {
+            eprintln!("Initialization Error: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn build_tokenizer_or_exit() -> TokenCounter {
+    match TokenCounter::cl100k() {
+        Ok(tokenizer) => tokenizer,
+        Err(error) => {
+            eprintln!("Initialization Error: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Probes every base URL in the background so the first real request reuses a
+/// pooled connection instead of paying the TCP/TLS handshake. Never blocks
+/// startup; failures are only logged.
+fn spawn_upstream_prewarm(upstream: UpstreamClient) {
+    tokio::spawn(async move {
+        for probe in upstream.probe_base_urls().await {
+            if probe.reachable {
+                info!(
+                    phase = "upstream_prewarm",
+                    url = %probe.url,
+                    status_code = probe.status_code,
+                    latency_ms = probe.latency_ms,
+                    "Pre-warmed upstream connection"
+                );
+            } else {
+                warn!(
+                    phase = "upstream_prewarm",
+                    url = %probe.url,
+                    latency_ms = probe.latency_ms,
+                    error = probe.error.as_deref().unwrap_or_default(),
+                    "Upstream pre-warm failed"
+                );
+            }
+        }
+    });
+}
+
+fn spawn_session_cleanup_task(
+    sessions: SessionManager,
+    rate_limiter: RateLimiter,
+    batches: BatchStore,
+    config: &Config,
+) {
+    let interval = Duration::from_secs(config.session_cleanup_interval_secs.max(1));
+    let batch_retention = Duration::from_secs(config.batch_retention_secs);
     tokio::spawn(async move {
-        let interval = Duration::from_secs(interval_secs.max(1));
         loop {
             tokio::time::sleep(interval).await;
-            let _ = sessions.cleanup_expired(Instant::now()).await;
+            let now = Instant::now();
+            let _ = sessions.cleanup_expired(now).await;
+            let _ = rate_limiter.cleanup_idle(now).await;
+            let _ = batches.evict_finished(now, batch_retention).await;
         }
     });
 }
@@ -0,0 +1,143 @@
+use salvo::http::StatusCode;
+use salvo::prelude::*;
+use tracing::{debug, info};
+
+use crate::batches::store::BatchResults;
+use crate::batches::{BatchJob, process_batch};
+use crate::handlers::{
+    bad_request, build_identity_key, check_batch_item, conflict, negotiate_anthropic_version,
+    not_found, not_supported, unauthorized, validate_client_api_key_header,
+};
+use crate::middleware::access_log::AccessLogHandle;
+use crate::models::ClaudeBatchRequest;
+use crate::state::app_state;
+
+#[handler]
+pub async fn create_batch(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let state = app_state();
+    let client_auth = match validate_client_api_key_header(req) {
+        Ok(value) => value,
+        Err(message) => {
+            unauthorized(res, &message);
+            return;
+        }
+    };
+    if let Err(message) = negotiate_anthropic_version(req, depot) {
+        bad_request(res, &message);
+        return;
+    }
+    let Some(batch_request) = parse_batch_request(req, res).await else {
+        return;
+    };
+
+    let identity_key = build_identity_key(req, &client_auth);
+    let session_id = state.sessions.resolve_session_id(&identity_key).await;
+    let summary = state.batches.create(batch_request.requests.len()).await;
+    info!(
+        phase = "batch_created",
+        batch_id = %summary.id,
+        requests_len = batch_request.requests.len(),
+        "Created message batch"
+    );
+
+    tokio::spawn(process_batch(
+        state.batches.clone(),
+        BatchJob {
+            batch_id: summary.id.clone(),
+            requests: batch_request.requests,
+            identity_key,
+            session_id,
+            request_id: AccessLogHandle::from_depot(depot).request_id(),
+        },
+    ));
+
+    res.status_code(StatusCode::ACCEPTED);
+    res.render(Json(summary));
+}
+
+/// Every item must pass the checks `/v1/messages` applies before the batch
+/// is accepted; rate limits are charged per item once processing starts.
+async fn parse_batch_request(req: &mut Request, res: &mut Response) -> Option<ClaudeBatchRequest> {
+    let config = &app_state().config;
+    let batch_request = match req
+        .parse_json_with_max_size::<ClaudeBatchRequest>(config.request_body_max_size)
+        .await
+    {
+        Ok(value) => value,
+        Err(error) => {
+            bad_request(res, &format!("invalid request body: {error}"));
+            return None;
+        }
+    };
+    if batch_request.requests.is_empty() {
+        bad_request(res, "batch must contain at least one request");
+        return None;
+    }
+    for (index, request) in batch_request.requests.iter().enumerate() {
+        if let Err(message) = check_batch_item(config, request) {
+            bad_request(res, &format!("requests[{index}]: {message}"));
+            return None;
+        }
+    }
+    Some(batch_request)
+}
+
+#[handler]
+pub async fn get_batch(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    if let Err(message) = validate_client_api_key_header(req) {
+        unauthorized(res, &message);
+        return;
+    }
+
+    let batch_id = req.param::<String>("id").unwrap_or_default();
+    debug!(
+        phase = "batch_poll",
+        request_id = %AccessLogHandle::from_depot(depot).request_id(),
+        batch_id = %batch_id,
+        "Polling message batch"
+    );
+    match app_state().batches.summary(&batch_id).await {
+        Some(summary) => res.render(Json(summary)),
+        None => not_found(res, &format!("batch {batch_id} not found")),
+    }
+}
+
+#[handler]
+pub async fn get_batch_results(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    if let Err(message) = validate_client_api_key_header(req) {
+        unauthorized(res, &message);
+        return;
+    }
+
+    let batch_id = req.param::<String>("id").unwrap_or_default();
+    debug!(
+        phase = "batch_results",
+        request_id = %AccessLogHandle::from_depot(depot).request_id(),
+        batch_id = %batch_id,
+        "Fetching message batch results"
+    );
+    match app_state().batches.results_jsonl(&batch_id).await {
+        Some(BatchResults::Ready(jsonl)) => {
+            let _ = res.add_header("Content-Type", "application/x-jsonl", true);
+            res.render(jsonl);
+        }
+        Some(BatchResults::Pending) => {
+            conflict(res, &format!("batch {batch_id} is still processing"))
+        }
+        None => not_found(res, &format!("batch {batch_id} not found")),
+    }
+}
+
+/// Listing, cancelling and deleting batches are not implemented; a 501 tells
+/// clients to fall back rather than retry.
+#[handler]
+pub async fn unsupported_batch_operation(req: &mut Request, res: &mut Response) {
+    if let Err(message) = validate_client_api_key_header(req) {
+        unauthorized(res, &message);
+        return;
+    }
+    not_supported(
+        res,
+        "This batch operation is not supported by this bridge. Create batches with POST /v1/messages/batches and poll them by id, or submit individual requests to /v1/messages.",
+    );
+}
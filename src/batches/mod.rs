@@ -0,0 +1,95 @@
+mod handlers;
+mod store;
+
+pub use handlers::{create_batch, get_batch, get_batch_results, unsupported_batch_operation};
+pub use store::BatchStore;
+
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::completion::complete_message;
+use crate::handlers::{check_rate_limit, check_session_limit, session_limit_message};
+use crate::models::ClaudeMessagesRequest;
+use crate::upstream::RequestIds;
+use store::{BatchItemOutcome, BatchItemResult};
+
+pub struct BatchJob {
+    pub batch_id: String,
+    pub requests: Vec<ClaudeMessagesRequest>,
+    pub identity_key: String,
+    pub session_id: String,
+    pub request_id: String,
+}
+
+pub async fn process_batch(store: BatchStore, mut job: BatchJob) {
+    let batch_id = job.batch_id.as_str();
+    let ids = RequestIds {
+        session_id: &job.session_id,
+        request_id: &job.request_id,
+    };
+    info!(
+        phase = "batch_processing_start",
+        batch_id,
+        requests_len = job.requests.len(),
+        "Processing message batch"
+    );
+
+    let requests = std::mem::take(&mut job.requests);
+    for (index, request) in requests.into_iter().enumerate() {
+        let outcome = process_item(&job, index, request).await;
+        debug!(
+            phase = "batch_request_done",
+            request_id = ids.request_id,
+            batch_id,
+            index,
+            "Batch request finished"
+        );
+        store
+            .record_result(batch_id, BatchItemResult::new(index, outcome))
+            .await;
+    }
+
+    store.finish(batch_id).await;
+    info!(
+        phase = "batch_processing_done",
+        batch_id, "Message batch ended"
+    );
+}
+
+/// Each item is admitted like its own `/v1/messages` call; an empty
+/// rate-limit bucket delays the item rather than failing it.
+async fn process_item(
+    job: &BatchJob,
+    index: usize,
+    mut request: ClaudeMessagesRequest,
+) -> BatchItemOutcome {
+    while let Err(retry_after) = check_rate_limit(&job.identity_key).await {
+        tokio::time::sleep(retry_after).await;
+    }
+    if let Err(max_requests) = check_session_limit(&job.identity_key).await {
+        return BatchItemOutcome::errored(&session_limit_message(max_requests));
+    }
+
+    let ids = RequestIds {
+        session_id: &job.session_id,
+        request_id: &job.request_id,
+    };
+    request.stream = Some(false);
+    match complete_message(&request, &job.identity_key, ids).await {
+        Ok((response, _)) => BatchItemOutcome::Succeeded {
+            message: serde_json::to_value(response).unwrap_or(Value::Null),
+        },
+        Err(error) => {
+            warn!(
+                phase = "batch_request_failed",
+                request_id = ids.request_id,
+                batch_id = job.batch_id,
+                index,
+                status = %error.status(),
+                "Batch request failed: {}",
+                error.message()
+            );
+            BatchItemOutcome::errored(error.message())
+        }
+    }
+}
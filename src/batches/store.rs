@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::utils::now_timestamp_string;
+
+#[derive(Clone, Debug, Default)]
+pub struct BatchStore {
+    inner: Arc<RwLock<HashMap<String, BatchResult>>>,
+}
+
+#[derive(Debug)]
+struct BatchResult {
+    created_at: String,
+    ended_at: Option<String>,
+    finished_at: Option<Instant>,
+    total: usize,
+    results: Vec<BatchItemResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemResult {
+    custom_id: String,
+    result: BatchItemOutcome,
+}
+
+impl BatchItemResult {
+    pub fn new(index: usize, result: BatchItemOutcome) -> Self {
+        Self {
+            custom_id: format!("request-{index}"),
+            result,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum BatchItemOutcome {
+    #[serde(rename = "succeeded")]
+    Succeeded { message: Value },
+    #[serde(rename = "errored")]
+    Errored { error: BatchItemError },
+}
+
+impl BatchItemOutcome {
+    pub fn errored(message: &str) -> Self {
+        Self::Errored {
+            error: BatchItemError {
+                error_type: "api_error".to_string(),
+                message: message.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemError {
+    #[serde(rename = "type")]
+    error_type: String,
+    message: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BatchResults {
+    Pending,
+    Ready(String),
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchSummary {
+    pub id: String,
+    #[serde(rename = "type")]
+    batch_type: &'static str,
+    pub processing_status: &'static str,
+    request_counts: BatchRequestCounts,
+    created_at: String,
+    ended_at: Option<String>,
+    results_url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchRequestCounts {
+    processing: usize,
+    succeeded: usize,
+    errored: usize,
+}
+
+impl BatchStore {
+    pub async fn create(&self, total: usize) -> BatchSummary {
+        let batch_id = format!("msgbatch_{}", Uuid::new_v4().simple());
+        let batch = BatchResult {
+            created_at: now_timestamp_string(),
+            ended_at: None,
+            finished_at: None,
+            total,
+            results: Vec::with_capacity(total),
+        };
+        let summary = summarize(&batch_id, &batch);
+        self.inner.write().await.insert(batch_id, batch);
+        summary
+    }
+
+    pub async fn record_result(&self, batch_id: &str, result: BatchItemResult) {
+        if let Some(batch) = self.inner.write().await.get_mut(batch_id) {
+            batch.results.push(result);
+        }
+    }
+
+    pub async fn finish(&self, batch_id: &str) {
+        if let Some(batch) = self.inner.write().await.get_mut(batch_id) {
+            batch.ended_at = Some(now_timestamp_string());
+            batch.finished_at = Some(Instant::now());
+        }
+    }
+
+    /// Drops batches that ended more than `retention` ago; batches still in
+    /// progress are always kept.
+    pub async fn evict_finished(&self, now: Instant, retention: Duration) -> usize {
+        let mut store = self.inner.write().await;
+        let before = store.len();
+        store.retain(|_, batch| {
+            batch.finished_at.is_none_or(|finished_at| {
+                now.checked_duration_since(finished_at).unwrap_or_default() <= retention
+            })
+        });
+        before - store.len()
+    }
+
+    pub async fn summary(&self, batch_id: &str) -> Option<BatchSummary> {
+        let store = self.inner.read().await;
+        store.get(batch_id).map(|batch| summarize(batch_id, batch))
+    }
+
+    pub async fn results_jsonl(&self, batch_id: &str) -> Option<BatchResults> {
+        let store = self.inner.read().await;
+        let batch = store.get(batch_id)?;
+        if batch.ended_at.is_none() {
+            return Some(BatchResults::Pending);
+        }
+
+        let lines: Vec<String> = batch
+            .results
+            .iter()
+            .filter_map(|result| serde_json::to_string(result).ok())
+            .collect();
+        Some(BatchResults::Ready(lines.join("\n")))
+    }
+}
+
+fn summarize(batch_id: &str, batch: &BatchResult) -> BatchSummary {
+    let succeeded = batch
+        .results
+        .iter()
+        .filter(|item| matches!(item.result, BatchItemOutcome::Succeeded { .. }))
+        .count();
+    let errored = batch.results.len() - succeeded;
+
+    BatchSummary {
+        id: batch_id.to_string(),
+        batch_type: "message_batch",
+        processing_status: if batch.ended_at.is_some() {
+            "ended"
+        } else {
+            "in_progress"
+        },
+        request_counts: BatchRequestCounts {
+            processing: batch.total.saturating_sub(batch.results.len()),
+            succeeded,
+            errored,
+        },
+        created_at: batch.created_at.clone(),
+        ended_at: batch.ended_at.clone(),
+        results_url: format!("/v1/messages/batches/{batch_id}/results"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BatchItemOutcome, BatchItemResult, BatchResults, BatchStore};
+    use serde_json::{Value, json};
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn tracks_create_poll_fetch_lifecycle() {
+        let store = BatchStore::default();
+        let created = store.create(2).await;
+        assert_eq!(created.processing_status, "in_progress");
+        assert!(created.id.starts_with("msgbatch_"));
+        assert_eq!(
+            store.results_jsonl(&created.id).await,
+            Some(BatchResults::Pending)
+        );
+
+        let message = json!({"type": "message", "content": []});
+        store
+            .record_result(
+                &created.id,
+                BatchItemResult::new(0, BatchItemOutcome::Succeeded { message }),
+            )
+            .await;
+        store
+            .record_result(
+                &created.id,
+                BatchItemResult::new(1, BatchItemOutcome::errored("boom")),
+            )
+            .await;
+
+        let polled = serde_json::to_value(store.summary(&created.id).await.expect("summary"))
+            .expect("serialize");
+        assert_eq!(polled["processing_status"], "in_progress");
+        assert_eq!(polled["request_counts"]["processing"], 0);
+
+        store.finish(&created.id).await;
+        let ended = store.summary(&created.id).await.expect("summary");
+        assert_eq!(ended.processing_status, "ended");
+
+        let Some(BatchResults::Ready(jsonl)) = store.results_jsonl(&created.id).await else {
+            panic!("results should be ready");
+        };
+        let lines: Vec<Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json line"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["custom_id"], "request-0");
+        assert_eq!(lines[0]["result"]["type"], "succeeded");
+        assert_eq!(lines[1]["result"]["type"], "errored");
+        assert_eq!(lines[1]["result"]["error"]["message"], "boom");
+    }
+
+    #[tokio::test]
+    async fn evicts_only_batches_finished_past_retention() {
+        let store = BatchStore::default();
+        let finished = store.create(1).await;
+        let running = store.create(1).await;
+        store.finish(&finished.id).await;
+
+        let retention = Duration::from_secs(60);
+        assert_eq!(store.evict_finished(Instant::now(), retention).await, 0);
+        let later = Instant::now() + Duration::from_secs(120);
+        assert_eq!(store.evict_finished(later, retention).await, 1);
+        assert!(store.summary(&finished.id).await.is_none());
+        assert!(store.summary(&running.id).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn unknown_batch_returns_none() {
+        let store = BatchStore::default();
+        assert!(store.summary("msgbatch_missing").await.is_none());
+        assert!(store.results_jsonl("msgbatch_missing").await.is_none());
+    }
+}
@@ -0,0 +1,235 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::models::ClaudeMessagesRequest;
+use crate::state::app_state;
+
+const CAPTURE_QUEUE_CAPACITY: usize = 1024;
+const SECS_PER_DAY: u64 = 86_400;
+
+/// One line of a capture file: the downstream request and what it was
+/// converted to, for replaying conversion issues offline.
+#[derive(Debug, Serialize)]
+struct CaptureEntry {
+    timestamp_ms: u64,
+    session_id: String,
+    request_id: String,
+    claude_request: Value,
+    converted_request: Value,
+}
+
+/// Hands entries to a background writer so request handling never waits on
+/// disk. Entries are dropped (with a warning) when the queue is full.
+#[derive(Clone, Debug)]
+pub struct RequestCapture {
+    sender: mpsc::Sender<CaptureEntry>,
+}
+
+impl RequestCapture {
+    pub fn from_config(config: &Config) -> Result<Option<Self>, String> {
+        if !config.capture_requests {
+            return Ok(None);
+        }
+        let dir = PathBuf::from(&config.capture_dir);
+        std::fs::create_dir_all(&dir).map_err(|error| {
+            format!("failed to create capture_dir '{}': {error}", dir.display())
+        })?;
+
+        let (sender, receiver) = mpsc::channel(CAPTURE_QUEUE_CAPACITY);
+        let max_file_bytes = u64::from(config.max_capture_file_size_mb) * 1024 * 1024;
+        tokio::spawn(run_writer(receiver, dir, max_file_bytes));
+        Ok(Some(Self { sender }))
+    }
+
+    fn record(&self, entry: CaptureEntry) {
+        if self.sender.try_send(entry).is_err() {
+            warn!(
+                phase = "request_capture_dropped",
+                "Capture queue is full; dropping entry"
+            );
+        }
+    }
+}
+
+/// Records a converted request when `capture_requests` is enabled.
+pub fn capture_conversion<T: Serialize>(
+    request: &ClaudeMessagesRequest,
+    converted: &T,
+    session_id: &str,
+    request_id: &str,
+) {
+    let Some(capture) = app_state().capture.as_ref() else {
+        return;
+    };
+    capture.record(CaptureEntry {
+        timestamp_ms: unix_millis(),
+        session_id: session_id.to_string(),
+        request_id: request_id.to_string(),
+        claude_request: serde_json::to_value(request).unwrap_or(Value::Null),
+        converted_request: serde_json::to_value(converted).unwrap_or(Value::Null),
+    });
+}
+
+struct DailyFile {
+    day: u64,
+    writer: BufWriter<File>,
+    size: u64,
+    full: bool,
+}
+
+async fn run_writer(mut receiver: mpsc::Receiver<CaptureEntry>, dir: PathBuf, max_bytes: u64) {
+    let mut current: Option<DailyFile> = None;
+    while let Some(entry) = receiver.recv().await {
+        let day = entry.timestamp_ms / 1000 / SECS_PER_DAY;
+        if current.as_ref().is_none_or(|file| file.day != day) {
+            if let Some(mut previous) = current.take() {
+                let _ = previous.writer.flush().await;
+            }
+            current = open_daily_file(&dir, day).await;
+        }
+        let Some(file) = current.as_mut() else {
+            continue;
+        };
+
+        write_entry(file, &entry, max_bytes).await;
+        if receiver.is_empty() {
+            let _ = file.writer.flush().await;
+        }
+    }
+}
+
+async fn open_daily_file(dir: &Path, day: u64) -> Option<DailyFile> {
+    let path = dir.join(capture_file_name(day));
+    let opened = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await;
+    let file = match opened {
+        Ok(file) => file,
+        Err(error) => {
+            warn!(
+                phase = "request_capture_open_failed",
+                path = %path.display(),
+                "Failed to open capture file: {error}"
+            );
+            return None;
+        }
+    };
+    let size = file.metadata().await.map(|meta| meta.len()).unwrap_or(0);
+    Some(DailyFile {
+        day,
+        writer: BufWriter::new(file),
+        size,
+        full: false,
+    })
+}
+
+async fn write_entry(file: &mut DailyFile, entry: &CaptureEntry, max_bytes: u64) {
+    let Ok(mut line) = serde_json::to_vec(entry) else {
+        return;
+    };
+    line.push(b'\n');
+    if max_bytes > 0 && file.size + line.len() as u64 > max_bytes {
+        if !file.full {
+            file.full = true;
+            warn!(
+                phase = "request_capture_file_full",
+                max_bytes, "Capture file is full; skipping entries until the next day"
+            );
+        }
+        return;
+    }
+    if let Err(error) = file.writer.write_all(&line).await {
+        warn!(
+            phase = "request_capture_write_failed",
+            "Failed to write capture entry: {error}"
+        );
+        return;
+    }
+    file.size += line.len() as u64;
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// `capture-YYYY-MM-DD.jsonl` for a UTC day number.
+fn capture_file_name(day: u64) -> String {
+    let (year, month, day) = civil_from_days(day as i64);
+    format!("capture-{year:04}-{month:02}-{day:02}.jsonl")
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's
+/// `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CaptureEntry, DailyFile, capture_file_name, write_entry};
+    use serde_json::Value;
+    use tokio::io::{AsyncWriteExt, BufWriter};
+
+    #[test]
+    fn names_files_by_utc_date() {
+        assert_eq!(capture_file_name(0), "capture-1970-01-01.jsonl");
+        assert_eq!(capture_file_name(19_782), "capture-2024-02-29.jsonl");
+        assert_eq!(capture_file_name(20_740), "capture-2026-10-14.jsonl");
+    }
+
+    #[tokio::test]
+    async fn stops_writing_once_the_size_cap_is_reached() {
+        let path =
+            std::env::temp_dir().join(format!("capture-test-{}.jsonl", uuid::Uuid::new_v4()));
+        let file = tokio::fs::File::create(&path).await.expect("create file");
+        let mut daily = DailyFile {
+            day: 0,
+            writer: BufWriter::new(file),
+            size: 0,
+            full: false,
+        };
+        let entry = CaptureEntry {
+            timestamp_ms: 0,
+            session_id: "s".to_string(),
+            request_id: "r".to_string(),
+            claude_request: Value::Null,
+            converted_request: Value::Null,
+        };
+
+        write_entry(&mut daily, &entry, 150).await;
+        write_entry(&mut daily, &entry, 150).await;
+        daily.writer.flush().await.expect("flush");
+
+        let written = tokio::fs::read_to_string(&path).await.expect("read file");
+        let _ = tokio::fs::remove_file(&path).await;
+        assert_eq!(written.lines().count(), 1);
+        assert!(daily.full);
+    }
+}
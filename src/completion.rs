@@ -0,0 +1,153 @@
+use salvo::http::StatusCode;
+use tracing::warn;
+
+use crate::capture::capture_conversion;
+use crate::config::WireApi;
+use crate::conversion::request::{
+    convert_claude_to_openai, convert_claude_to_responses, session_user,
+};
+use crate::conversion::response::{
+    ClaudeResponse, build_synthetic_response, convert_openai_responses_to_claude_response,
+    convert_openai_to_claude_response,
+};
+use crate::errors::UpstreamError;
+use crate::models::ClaudeMessagesRequest;
+use crate::state::app_state;
+use crate::stateful_responses::{continue_previous_response, record_response};
+use crate::upstream::RequestIds;
+use crate::upstream_metadata::UpstreamMetadata;
+
+#[derive(Debug)]
+pub enum CompletionError {
+    Upstream(UpstreamError),
+    Conversion(String),
+}
+
+impl CompletionError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Upstream(error) => error.status,
+            Self::Conversion(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Upstream(error) => &error.message,
+            Self::Conversion(message) => message,
+        }
+    }
+}
+
+pub async fn complete_message(
+    request: &ClaudeMessagesRequest,
+    identity_key: &str,
+    ids: RequestIds<'_>,
+) -> Result<(ClaudeResponse, UpstreamMetadata), CompletionError> {
+    let result = match app_state().config.wire_api_for(&request.model) {
+        WireApi::Chat => complete_chat_message(request, identity_key, ids).await,
+        WireApi::Responses => complete_responses_message(request, identity_key, ids).await,
+    };
+    let result = result.map(|(mut response, metadata)| {
+        if let Some(text) = &app_state().config.empty_response_fallback_text {
+            response.fill_empty_content(text);
+        }
+        (response, metadata)
+    });
+    resolve_content_filter(result, &request.model)
+}
+
+/// Applies `content_filter_mode` to both ways an upstream reports filtering:
+/// a `content_filter` finish reason and a `400` content-filter error.
+fn resolve_content_filter(
+    result: Result<(ClaudeResponse, UpstreamMetadata), CompletionError>,
+    model: &str,
+) -> Result<(ClaudeResponse, UpstreamMetadata), CompletionError> {
+    let replacement = app_state().config.content_filter_text();
+    match result {
+        Ok((mut response, metadata)) if response.is_content_filtered() => {
+            warn!(phase = "content_filtered", "Upstream filtered the response");
+            let Some(text) = replacement else {
+                return Err(CompletionError::Upstream(UpstreamError::content_filtered(
+                    metadata,
+                )));
+            };
+            response.replace_filtered_content(text);
+            Ok((response, metadata))
+        }
+        Err(CompletionError::Upstream(error)) if error.is_content_filtered() => match replacement {
+            Some(text) => Ok((build_synthetic_response(model, text), error.metadata)),
+            None => Err(CompletionError::Upstream(error)),
+        },
+        other => other,
+    }
+}
+
+async fn complete_chat_message(
+    request: &ClaudeMessagesRequest,
+    identity_key: &str,
+    ids: RequestIds<'_>,
+) -> Result<(ClaudeResponse, UpstreamMetadata), CompletionError> {
+    let state = app_state();
+    let mut openai_request = convert_claude_to_openai(request, &state.config);
+    openai_request.user = session_user(&state.config, ids.session_id);
+    capture_conversion(request, &openai_request, ids.session_id, ids.request_id);
+    let (openai_response, metadata) = state
+        .upstream
+        .chat_completion(&openai_request, &openai_request.model, ids)
+        .await
+        .map_err(CompletionError::Upstream)?;
+
+    let (input_tokens, output_tokens) = openai_response.token_counts();
+    state
+        .sessions
+        .add_usage(identity_key, input_tokens, output_tokens)
+        .await;
+
+    convert_openai_to_claude_response(
+        &openai_response,
+        request,
+        state.config.infer_stop_sequence,
+        state.config.thinking_as_text,
+    )
+    .map(|response| (response, metadata))
+    .map_err(CompletionError::Conversion)
+}
+
+async fn complete_responses_message(
+    request: &ClaudeMessagesRequest,
+    identity_key: &str,
+    ids: RequestIds<'_>,
+) -> Result<(ClaudeResponse, UpstreamMetadata), CompletionError> {
+    let state = app_state();
+    let mut responses_request = convert_claude_to_responses(request, &state.config);
+    responses_request.user = session_user(&state.config, ids.session_id);
+    let prefix = continue_previous_response(&mut responses_request, identity_key).await;
+    capture_conversion(request, &responses_request, ids.session_id, ids.request_id);
+    let upstream_result = state
+        .upstream
+        .responses(&responses_request, &responses_request.model, ids)
+        .await;
+    let (upstream_response, metadata) = match upstream_result {
+        Ok(value) => value,
+        Err(error) => {
+            record_response(identity_key, prefix, None).await;
+            return Err(CompletionError::Upstream(error));
+        }
+    };
+
+    let (input_tokens, output_tokens) = upstream_response.token_counts();
+    state
+        .sessions
+        .add_usage(identity_key, input_tokens, output_tokens)
+        .await;
+    record_response(identity_key, prefix, upstream_response.id()).await;
+
+    convert_openai_responses_to_claude_response(
+        &upstream_response,
+        request,
+        state.config.thinking_as_text,
+    )
+    .map(|response| (response, metadata))
+    .map_err(CompletionError::Conversion)
+}
@@ -1,343 +1,276 @@
+mod content_filter;
+mod conversion;
+mod env;
+mod models;
+mod observability;
+mod server;
+mod sessions;
+mod stream;
+mod thinking;
+mod upstream;
+
 use std::collections::HashMap;
-use std::env;
-use std::fs;
-use std::path::Path;
 
 use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config_file::read_raw_config;
+use crate::model_routes::ModelRoute;
+
+use models::{lookup_model_entry, lookup_model_timeout};
+pub(crate) use upstream::parse_wire_api;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Added to the `max_tokens`-scaled body limit so requests with a tiny
+/// `max_tokens` can still carry a normal prompt.
+pub const REQUEST_BODY_BASE_OVERHEAD: usize = 1024 * 1024;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum WireApi {
+    #[default]
     Chat,
     Responses,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// How streamed tool arguments reach the client: `Streaming` forwards each
+/// fragment as its own `input_json_delta`, as Anthropic does; `Buffered`
+/// holds them until the arguments parse as JSON.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ToolJsonMode {
+    #[default]
+    Streaming,
+    Buffered,
+}
+
+/// What a content-filtered upstream response becomes: a `400` to the
+/// client, an empty `end_turn` response, or one carrying
+/// `content_filter_message`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ContentFilterMode {
+    Error,
+    #[default]
+    Empty,
+    Message,
+}
+
+/// One `[[model_routing_rules]]` entry. Every condition that is set must
+/// hold; a rule without conditions matches every request.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ModelRoutingRule {
+    pub max_tokens_lte: Option<u32>,
+    pub min_tokens_gte: Option<u32>,
+    pub has_tools: Option<bool>,
+    pub has_thinking: Option<bool>,
+    pub target_model: String,
+}
+
+/// Built by [`Config::load`]; each area of settings is resolved by its own
+/// submodule, env over config file over default.
+#[derive(Clone, Debug, Default)]
 pub struct Config {
     pub openai_api_key: String,
     pub anthropic_api_key: Option<String>,
     pub openai_base_url: String,
+    pub openai_base_urls: Vec<String>,
     pub azure_api_version: Option<String>,
     pub host: String,
     pub port: u16,
     pub log_level: String,
+    pub log_format: LogFormat,
     pub request_timeout: u64,
     pub stream_request_timeout: Option<u64>,
+    pub model_timeouts: HashMap<String, u64>,
+    pub model_aliases: HashMap<String, String>,
+    pub model_routing_rules: Vec<ModelRoutingRule>,
+    pub model_routes: Vec<ModelRoute>,
+    pub system_prompt_prefix: HashMap<String, String>,
+    pub model_temperature_overrides: HashMap<String, f64>,
+    pub model_no_temperature: Vec<String>,
+    pub model_stream_timeouts: HashMap<String, u64>,
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    pub retry_jitter_factor: f64,
+    pub circuit_breaker_failure_threshold: u32,
+    pub circuit_breaker_success_threshold: u32,
+    pub circuit_breaker_open_duration_secs: u64,
+    pub rate_limit_rpm: u32,
+    pub rate_limit_burst: u32,
+    pub max_requests_per_session: Option<u64>,
     pub request_body_max_size: usize,
+    pub request_body_max_size_per_token: f64,
+    pub model_body_max_sizes: HashMap<String, usize>,
     pub session_ttl_min_secs: u64,
     pub session_ttl_max_secs: u64,
     pub session_cleanup_interval_secs: u64,
+    pub batch_retention_secs: u64,
+    pub shutdown_timeout_secs: u64,
+    pub upstream_proxy: Option<String>,
+    pub upstream_proxy_basic_auth: Option<String>,
+    pub no_proxy: Vec<String>,
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
     pub debug_tool_id_matching: bool,
     pub wire_api: WireApi,
     pub big_model: String,
     pub middle_model: String,
     pub small_model: String,
     pub min_thinking_level: Option<String>,
+    pub enforce_min_tokens_for_thinking: bool,
+    pub min_tokens_thinking_fraction: f64,
+    pub reasoning_models: Vec<String>,
+    pub infer_stop_sequence: bool,
+    pub default_frequency_penalty: Option<f64>,
+    pub default_presence_penalty: Option<f64>,
+    pub default_response_format: Option<Value>,
+    pub system_block_separator: String,
+    pub cache_boundary_separator: String,
+    pub propagate_thinking_blocks: bool,
+    pub preserve_thinking_signatures: bool,
+    pub thinking_continuation_mode: bool,
+    pub normalize_message_order: bool,
+    pub thinking_as_text: bool,
+    pub document_passthrough: bool,
+    pub video_to_text_placeholder: bool,
+    pub accept_legacy_function_role: bool,
+    pub responses_base64_image_source: bool,
+    pub use_stateful_responses: bool,
+    pub responses_include: Vec<String>,
+    pub propagate_session_id_as_user: bool,
+    pub allow_client_session_id: bool,
+    pub drop_unsupported_tools: bool,
+    pub validate_tool_schemas: bool,
+    pub repair_tool_schemas: bool,
+    pub tool_schema_cache_hints: bool,
+    pub strict_json_validation: bool,
+    pub streaming_tool_json_mode: ToolJsonMode,
+    pub max_consecutive_send_errors: u32,
+    pub content_filter_mode: ContentFilterMode,
+    pub content_filter_message: String,
+    pub empty_response_fallback_text: Option<String>,
+    pub capture_requests: bool,
+    pub capture_dir: String,
+    pub max_capture_file_size_mb: u32,
+    pub dry_run: bool,
+    pub prewarm_upstream: bool,
+    pub metrics_enabled: bool,
+    pub metrics_token: Option<String>,
+    pub admin_api_key: Option<String>,
+    pub min_anthropic_version: Option<String>,
+    pub anthropic_version_override: Option<String>,
+    pub otel_exporter_otlp_endpoint: Option<String>,
+    pub otel_service_name: Option<String>,
+    pub cors_allowed_origins: Vec<String>,
+    pub echo_upstream_headers: Vec<String>,
+    pub compress_responses: bool,
+    pub min_compress_size_bytes: usize,
     pub custom_headers: HashMap<String, String>,
 }
 
-#[derive(Debug, Default, Deserialize)]
-struct TomlConfigRaw {
-    openai_api_key: Option<String>,
-    anthropic_api_key: Option<String>,
-    openai_base_url: Option<String>,
-    azure_api_version: Option<String>,
-    host: Option<String>,
-    port: Option<u16>,
-    log_level: Option<String>,
-    request_timeout: Option<u64>,
-    stream_request_timeout: Option<u64>,
-    request_body_max_size: Option<usize>,
-    session_ttl_min_secs: Option<u64>,
-    session_ttl_max_secs: Option<u64>,
-    session_cleanup_interval_secs: Option<u64>,
-    debug_tool_id_matching: Option<bool>,
-    wire_api: Option<String>,
-    big_model: Option<String>,
-    middle_model: Option<String>,
-    small_model: Option<String>,
-    min_thinking_level: Option<String>,
-    custom_headers: Option<HashMap<String, String>>,
-}
-
 impl Config {
     pub fn load() -> Result<Self, String> {
-        let toml_config = read_toml_config("config.toml")?.unwrap_or_default();
-
-        let openai_api_key = env::var("OPENAI_API_KEY")
-            .ok()
-            .or(toml_config.openai_api_key)
-            .ok_or_else(|| {
-                "OPENAI_API_KEY not found in environment variables and config.toml".to_string()
-            })?;
-
-        let anthropic_api_key = env::var("ANTHROPIC_API_KEY")
-            .ok()
-            .or(toml_config.anthropic_api_key);
-
-        let openai_base_url = env::var("OPENAI_BASE_URL")
-            .ok()
-            .or(toml_config.openai_base_url)
-            .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
-
-        let azure_api_version = env::var("AZURE_API_VERSION")
-            .ok()
-            .or(toml_config.azure_api_version);
-
-        let host = env::var("HOST")
-            .ok()
-            .or(toml_config.host)
-            .unwrap_or_else(|| "0.0.0.0".to_string());
-
-        let port = env_u16_with_fallback("PORT", toml_config.port.unwrap_or(8082));
-        let log_level = env::var("LOG_LEVEL")
-            .ok()
-            .or(toml_config.log_level)
-            .unwrap_or_else(|| "INFO".to_string());
-
-        let request_timeout =
-            env_u64_with_fallback("REQUEST_TIMEOUT", toml_config.request_timeout.unwrap_or(90));
-
-        let stream_request_timeout = env_optional_u64("STREAM_REQUEST_TIMEOUT")
-            .or(toml_config.stream_request_timeout)
-            .filter(|value| *value > 0);
-
-        let request_body_max_size = env_usize_with_fallback(
-            "REQUEST_BODY_MAX_SIZE",
-            toml_config
-                .request_body_max_size
-                .unwrap_or(16 * 1024 * 1024),
-        );
-
-        let session_ttl_min_secs = env_u64_with_fallback(
-            "SESSION_TTL_MIN_SECS",
-            toml_config.session_ttl_min_secs.unwrap_or(1800),
-        );
-        let session_ttl_max_secs = env_u64_with_fallback(
-            "SESSION_TTL_MAX_SECS",
-            toml_config.session_ttl_max_secs.unwrap_or(86400),
-        );
-        let session_cleanup_interval_secs = env_u64_with_fallback(
-            "SESSION_CLEANUP_INTERVAL_SECS",
-            toml_config.session_cleanup_interval_secs.unwrap_or(60),
-        );
-
-        validate_session_config(
-            session_ttl_min_secs,
-            session_ttl_max_secs,
-            session_cleanup_interval_secs,
-        )?;
-
-        let debug_tool_id_matching = env_bool_with_fallback(
-            "DEBUG_TOOL_ID_MATCHING",
-            toml_config.debug_tool_id_matching.unwrap_or(false),
-        );
-
-        let wire_api_raw = env::var("WIRE_API").ok().or(toml_config.wire_api);
-        let wire_api = parse_wire_api(wire_api_raw.as_deref())?;
-
-        let big_model = env::var("BIG_MODEL")
-            .ok()
-            .or(toml_config.big_model)
-            .unwrap_or_else(|| "gpt-4o".to_string());
-
-        let middle_model = env::var("MIDDLE_MODEL")
-            .ok()
-            .or(toml_config.middle_model)
-            .unwrap_or_else(|| big_model.clone());
-
-        let small_model = env::var("SMALL_MODEL")
-            .ok()
-            .or(toml_config.small_model)
-            .unwrap_or_else(|| "gpt-4o-mini".to_string());
-
-        let min_thinking_level_raw = env::var("MIN_THINKING_LEVEL")
-            .ok()
-            .or(toml_config.min_thinking_level);
-        let min_thinking_level = parse_min_thinking_level(min_thinking_level_raw.as_deref())?;
-
-        let mut custom_headers = toml_config.custom_headers.unwrap_or_default();
-        custom_headers.extend(collect_custom_headers());
+        let mut file_config = read_raw_config()?;
+        let mut config = Self::default();
+        upstream::load(&mut config, &mut file_config)?;
+        server::load(&mut config, &mut file_config)?;
+        server::load_http(&mut config, &mut file_config)?;
+        upstream::load_proxy(&mut config, &mut file_config)?;
+        upstream::load_resilience(&mut config, &file_config)?;
+        sessions::load(&mut config, &file_config)?;
+        sessions::load_body_limits(&mut config, &mut file_config)?;
+        models::load(&mut config, &mut file_config)?;
+        thinking::load(&mut config, &mut file_config)?;
+        conversion::load_request_defaults(&mut config, &mut file_config)?;
+        conversion::load(&mut config, &mut file_config);
+        conversion::load_tools(&mut config, &file_config);
+        stream::load(&mut config, &mut file_config)?;
+        content_filter::load(&mut config, &mut file_config)?;
+        observability::load(&mut config, &mut file_config);
+        observability::load_operations(&mut config, &mut file_config);
+        Ok(config)
+    }
 
-        Ok(Self {
-            openai_api_key,
-            anthropic_api_key,
-            openai_base_url,
-            azure_api_version,
-            host,
-            port,
-            log_level,
-            request_timeout,
-            stream_request_timeout,
-            request_body_max_size,
-            session_ttl_min_secs,
-            session_ttl_max_secs,
-            session_cleanup_interval_secs,
-            debug_tool_id_matching,
-            wire_api,
-            big_model,
-            middle_model,
-            small_model,
-            min_thinking_level,
-            custom_headers,
-        })
+    pub fn request_timeout_for(&self, model: &str) -> u64 {
+        lookup_model_timeout(&self.model_timeouts, model).unwrap_or(self.request_timeout)
     }
 
-    pub fn validate_openai_api_key_format(&self) -> bool {
-        self.openai_api_key.starts_with("sk-")
+    pub fn stream_request_timeout_for(&self, model: &str) -> Option<u64> {
+        lookup_model_timeout(&self.model_stream_timeouts, model).or(self.stream_request_timeout)
     }
 
-    pub fn validate_client_api_key(&self, provided_key: Option<&str>) -> bool {
-        match self.anthropic_api_key.as_deref() {
-            Some(expected) => provided_key.map(|key| key == expected).unwrap_or(false),
-            None => true,
+    /// Keyed by the model name the client sent, before alias mapping. With
+    /// `request_body_max_size_per_token` set, small `max_tokens` requests get
+    /// a proportionally smaller limit.
+    pub fn body_max_size_for(&self, claude_model: &str, max_tokens: u32) -> usize {
+        let limit = lookup_model_entry(&self.model_body_max_sizes, claude_model)
+            .copied()
+            .unwrap_or(self.request_body_max_size);
+        if self.request_body_max_size_per_token <= 0.0 {
+            return limit;
         }
+        let scaled = f64::from(max_tokens) * self.request_body_max_size_per_token;
+        limit.min((scaled as usize).saturating_add(REQUEST_BODY_BASE_OVERHEAD))
     }
-}
 
-fn validate_session_config(min_secs: u64, max_secs: u64, cleanup_secs: u64) -> Result<(), String> {
-    if min_secs == 0 {
-        return Err("SESSION_TTL_MIN_SECS must be > 0".to_string());
-    }
-    if max_secs < min_secs {
-        return Err("SESSION_TTL_MAX_SECS must be >= SESSION_TTL_MIN_SECS".to_string());
+    /// The read limit for `/v1/messages`, before the model is known.
+    pub fn largest_body_max_size(&self) -> usize {
+        self.model_body_max_sizes
+            .values()
+            .copied()
+            .fold(self.request_body_max_size, usize::max)
     }
-    if cleanup_secs == 0 {
-        return Err("SESSION_CLEANUP_INTERVAL_SECS must be > 0".to_string());
-    }
-
-    Ok(())
-}
-
-fn read_toml_config(path: &str) -> Result<Option<TomlConfigRaw>, String> {
-    let config_path = Path::new(path);
 
-    if !config_path.exists() {
-        return Ok(None);
+    /// `strict_json_validation` predates `streaming_tool_json_mode` and still
+    /// forces buffering on its own.
+    pub fn buffers_tool_json(&self) -> bool {
+        self.strict_json_validation || self.streaming_tool_json_mode == ToolJsonMode::Buffered
     }
 
-    let content = fs::read_to_string(config_path)
-        .map_err(|error| format!("Failed to read {}: {}", config_path.display(), error))?;
-
-    let parsed = toml::from_str::<TomlConfigRaw>(&content)
-        .map_err(|error| format!("Failed to parse {}: {}", config_path.display(), error))?;
-
-    Ok(Some(parsed))
-}
-
-fn collect_custom_headers() -> HashMap<String, String> {
-    let mut custom_headers = HashMap::new();
-    for (env_key, env_value) in env::vars() {
-        let Some(header_raw) = env_key.strip_prefix("CUSTOM_HEADER_") else {
-            continue;
-        };
-        if header_raw.is_empty() {
-            continue;
+    /// Replacement text for a content-filtered response, or `None` when
+    /// `content_filter_mode = "error"` rejects it instead.
+    pub fn content_filter_text(&self) -> Option<&str> {
+        match self.content_filter_mode {
+            ContentFilterMode::Error => None,
+            ContentFilterMode::Empty => Some(""),
+            ContentFilterMode::Message => Some(&self.content_filter_message),
         }
-        custom_headers.insert(header_raw.replace('_', "-"), env_value);
     }
-    custom_headers
-}
-
-fn parse_wire_api(value: Option<&str>) -> Result<WireApi, String> {
-    let Some(raw_value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
-        return Ok(WireApi::Chat);
-    };
 
-    match raw_value.to_ascii_lowercase().as_str() {
-        "chat" => Ok(WireApi::Chat),
-        "responses" => Ok(WireApi::Responses),
-        _ => Err(format!(
-            "Invalid WIRE_API value '{raw_value}'. Supported values: chat, responses."
-        )),
+    pub fn system_prompt_prefix_for(&self, upstream_model: &str) -> Option<&str> {
+        lookup_model_entry(&self.system_prompt_prefix, upstream_model).map(String::as_str)
     }
-}
-
-fn parse_min_thinking_level(value: Option<&str>) -> Result<Option<String>, String> {
-    let Some(raw_value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
-        return Ok(None);
-    };
 
-    let normalized = raw_value.to_ascii_lowercase();
-    match normalized.as_str() {
-        "low" | "medium" | "high" => Ok(Some(normalized)),
-        _ => Err(format!(
-            "Invalid MIN_THINKING_LEVEL value '{raw_value}'. Supported values: low, medium, high."
-        )),
-    }
-}
-
-fn env_u16_with_fallback(key: &str, fallback: u16) -> u16 {
-    env::var(key)
-        .ok()
-        .and_then(|value| value.parse::<u16>().ok())
-        .unwrap_or(fallback)
-}
-
-fn env_u64_with_fallback(key: &str, fallback: u64) -> u64 {
-    env::var(key)
-        .ok()
-        .and_then(|value| value.parse::<u64>().ok())
-        .unwrap_or(fallback)
-}
-
-fn env_optional_u64(key: &str) -> Option<u64> {
-    env::var(key)
-        .ok()
-        .and_then(|value| value.parse::<u64>().ok())
-        .filter(|value| *value > 0)
-}
-
-fn env_bool_with_fallback(key: &str, fallback: bool) -> bool {
-    env::var(key)
-        .ok()
-        .map(|value| {
-            matches!(
-                value.trim().to_ascii_lowercase().as_str(),
-                "1" | "true" | "yes" | "on"
-            )
-        })
-        .unwrap_or(fallback)
-}
-
-fn env_usize_with_fallback(key: &str, fallback: usize) -> usize {
-    env::var(key)
-        .ok()
-        .and_then(|value| value.parse::<usize>().ok())
-        .unwrap_or(fallback)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::parse_min_thinking_level;
-
-    #[test]
-    fn parse_min_thinking_level_accepts_valid_values_case_insensitive() {
-        assert_eq!(
-            parse_min_thinking_level(Some("  LOW ")).expect("should parse"),
-            Some("low".to_string())
-        );
-        assert_eq!(
-            parse_min_thinking_level(Some("Medium")).expect("should parse"),
-            Some("medium".to_string())
-        );
-        assert_eq!(
-            parse_min_thinking_level(Some("HIGH")).expect("should parse"),
-            Some("high".to_string())
-        );
+    /// `None` means the upstream model should not receive `temperature`;
+    /// otherwise the request's value wins over a per-model override.
+    pub fn temperature_for(&self, upstream_model: &str, requested: Option<f64>) -> Option<f64> {
+        let model = upstream_model.to_ascii_lowercase();
+        if self
+            .model_no_temperature
+            .iter()
+            .any(|prefix| model.starts_with(prefix.as_str()))
+        {
+            return None;
+        }
+        requested
+            .or_else(|| {
+                lookup_model_entry(&self.model_temperature_overrides, upstream_model).copied()
+            })
+            .or(Some(1.0))
     }
 
-    #[test]
-    fn parse_min_thinking_level_treats_empty_as_none() {
-        assert_eq!(parse_min_thinking_level(None).expect("should parse"), None);
-        assert_eq!(
-            parse_min_thinking_level(Some("   ")).expect("should parse"),
-            None
-        );
+    pub fn validate_openai_api_key_format(&self) -> bool {
+        self.openai_api_key.starts_with("sk-")
     }
 
-    #[test]
-    fn parse_min_thinking_level_rejects_invalid_values() {
-        let error = parse_min_thinking_level(Some("max")).expect_err("should fail");
-        assert!(error.contains("Invalid MIN_THINKING_LEVEL value 'max'"));
+    pub fn validate_client_api_key(&self, provided_key: Option<&str>) -> bool {
+        match self.anthropic_api_key.as_deref() {
+            Some(expected) => provided_key.map(|key| key == expected).unwrap_or(false),
+            None => true,
+        }
     }
 }
@@ -0,0 +1,64 @@
+use std::env;
+
+use crate::config_file::RawConfig;
+
+use super::{Config, ContentFilterMode};
+
+const DEFAULT_CONTENT_FILTER_MESSAGE: &str =
+    "[The response was blocked by the upstream content filter.]";
+
+/// What the client sees when upstream filters or returns nothing.
+pub(super) fn load(config: &mut Config, file_config: &mut RawConfig) -> Result<(), String> {
+    config.content_filter_mode = parse_content_filter_mode(
+        env::var("CONTENT_FILTER_MODE")
+            .ok()
+            .or(file_config.content_filter_mode.take())
+            .as_deref(),
+    )?;
+    config.content_filter_message = env::var("CONTENT_FILTER_MESSAGE")
+        .ok()
+        .or(file_config.content_filter_message.take())
+        .unwrap_or_else(|| DEFAULT_CONTENT_FILTER_MESSAGE.to_string());
+    config.empty_response_fallback_text = env::var("EMPTY_RESPONSE_FALLBACK_TEXT")
+        .ok()
+        .or(file_config.empty_response_fallback_text.take())
+        .filter(|value| !value.trim().is_empty());
+    Ok(())
+}
+
+fn parse_content_filter_mode(value: Option<&str>) -> Result<ContentFilterMode, String> {
+    let Some(raw_value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(ContentFilterMode::Empty);
+    };
+
+    match raw_value.to_ascii_lowercase().as_str() {
+        "error" => Ok(ContentFilterMode::Error),
+        "empty" => Ok(ContentFilterMode::Empty),
+        "message" => Ok(ContentFilterMode::Message),
+        _ => Err(format!(
+            "Invalid CONTENT_FILTER_MODE value '{raw_value}'. Supported values: error, empty, message."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_content_filter_mode;
+
+    #[test]
+    fn content_filter_mode_selects_replacement_text() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.content_filter_mode = parse_content_filter_mode(None).expect("should parse");
+        assert_eq!(config.content_filter_text(), Some(""));
+
+        config.content_filter_mode = parse_content_filter_mode(Some(" Error ")).expect("parse");
+        assert_eq!(config.content_filter_text(), None);
+
+        config.content_filter_mode = parse_content_filter_mode(Some("message")).expect("parse");
+        config.content_filter_message = "blocked".to_string();
+        assert_eq!(config.content_filter_text(), Some("blocked"));
+
+        let error = parse_content_filter_mode(Some("drop")).expect_err("should fail");
+        assert!(error.contains("Invalid CONTENT_FILTER_MODE value 'drop'"));
+    }
+}
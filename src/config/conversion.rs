@@ -0,0 +1,152 @@
+use std::env;
+
+use serde_json::Value;
+
+use crate::config_file::RawConfig;
+
+use super::Config;
+use super::env::{env_bool_with_fallback, env_optional_f64, resolve_list};
+
+/// Defaults and separators applied while building the upstream request.
+pub(super) fn load_request_defaults(
+    config: &mut Config,
+    file_config: &mut RawConfig,
+) -> Result<(), String> {
+    config.default_frequency_penalty =
+        env_optional_f64("DEFAULT_FREQUENCY_PENALTY").or(file_config.default_frequency_penalty);
+    config.default_presence_penalty =
+        env_optional_f64("DEFAULT_PRESENCE_PENALTY").or(file_config.default_presence_penalty);
+    config.default_response_format = parse_response_format(
+        env::var("DEFAULT_RESPONSE_FORMAT").ok().as_deref(),
+        file_config.default_response_format.take(),
+    )?;
+
+    config.system_block_separator = env::var("SYSTEM_BLOCK_SEPARATOR")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(|value| unescape_separator(&value))
+        .or(file_config.system_block_separator.take())
+        .unwrap_or_else(|| "\n\n".to_string());
+    config.cache_boundary_separator = env::var("CACHE_BOUNDARY_SEPARATOR")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(|value| unescape_separator(&value))
+        .or(file_config.cache_boundary_separator.take())
+        .unwrap_or_else(|| "\n\n---\n\n".to_string());
+    Ok(())
+}
+
+/// Switches for message and content-block translation.
+pub(super) fn load(config: &mut Config, file_config: &mut RawConfig) {
+    config.normalize_message_order = env_bool_with_fallback(
+        "NORMALIZE_MESSAGE_ORDER",
+        file_config.normalize_message_order.unwrap_or(false),
+    );
+    config.document_passthrough = env_bool_with_fallback(
+        "DOCUMENT_PASSTHROUGH",
+        file_config.document_passthrough.unwrap_or(false),
+    );
+    config.video_to_text_placeholder = env_bool_with_fallback(
+        "VIDEO_TO_TEXT_PLACEHOLDER",
+        file_config.video_to_text_placeholder.unwrap_or(false),
+    );
+    config.accept_legacy_function_role = env_bool_with_fallback(
+        "ACCEPT_LEGACY_FUNCTION_ROLE",
+        file_config.accept_legacy_function_role.unwrap_or(false),
+    );
+    config.responses_base64_image_source = env_bool_with_fallback(
+        "RESPONSES_BASE64_IMAGE_SOURCE",
+        file_config.responses_base64_image_source.unwrap_or(false),
+    );
+    config.use_stateful_responses = env_bool_with_fallback(
+        "USE_STATEFUL_RESPONSES",
+        file_config.use_stateful_responses.unwrap_or(false),
+    );
+    config.responses_include = resolve_list(
+        env::var("RESPONSES_INCLUDE").ok(),
+        file_config.responses_include.take(),
+    );
+}
+
+/// How tool definitions and tool ids are checked and rewritten.
+pub(super) fn load_tools(config: &mut Config, file_config: &RawConfig) {
+    config.debug_tool_id_matching = env_bool_with_fallback(
+        "DEBUG_TOOL_ID_MATCHING",
+        file_config.debug_tool_id_matching.unwrap_or(false),
+    );
+    config.drop_unsupported_tools = env_bool_with_fallback(
+        "DROP_UNSUPPORTED_TOOLS",
+        file_config.drop_unsupported_tools.unwrap_or(false),
+    );
+    config.validate_tool_schemas = env_bool_with_fallback(
+        "VALIDATE_TOOL_SCHEMAS",
+        file_config.validate_tool_schemas.unwrap_or(false),
+    );
+    config.repair_tool_schemas = env_bool_with_fallback(
+        "REPAIR_TOOL_SCHEMAS",
+        file_config.repair_tool_schemas.unwrap_or(false),
+    );
+    config.tool_schema_cache_hints = env_bool_with_fallback(
+        "TOOL_SCHEMA_CACHE_HINTS",
+        file_config.tool_schema_cache_hints.unwrap_or(false),
+    );
+}
+
+/// Env values cannot easily hold newlines, so `\n` and `\t` escapes are
+/// expanded there; TOML/YAML strings already support escapes natively.
+fn unescape_separator(value: &str) -> String {
+    value.replace("\\n", "\n").replace("\\t", "\t")
+}
+
+/// The env value is a JSON string; the file value is a table. Either must be
+/// an object with a `type`, e.g. `{"type": "json_object"}`.
+fn parse_response_format(
+    env_value: Option<&str>,
+    file_value: Option<Value>,
+) -> Result<Option<Value>, String> {
+    let value = match env_value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(raw_value) => serde_json::from_str(raw_value)
+            .map_err(|error| format!("Invalid DEFAULT_RESPONSE_FORMAT JSON: {error}"))?,
+        None => match file_value {
+            Some(value) => value,
+            None => return Ok(None),
+        },
+    };
+
+    if value.get("type").and_then(Value::as_str).is_none() {
+        return Err("DEFAULT_RESPONSE_FORMAT must be an object with a string `type`".to_string());
+    }
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_response_format, unescape_separator};
+    use serde_json::json;
+
+    #[test]
+    fn unescape_separator_expands_newline_and_tab_escapes() {
+        assert_eq!(unescape_separator("\\n---\\n"), "\n---\n");
+        assert_eq!(unescape_separator("\\t|"), "\t|");
+    }
+
+    #[test]
+    fn parse_response_format_prefers_env_json_and_requires_type() {
+        let file_value = Some(json!({"type": "text"}));
+        assert_eq!(
+            parse_response_format(Some(r#"{"type":"json_object"}"#), file_value.clone())
+                .expect("should parse"),
+            Some(json!({"type": "json_object"}))
+        );
+        assert_eq!(
+            parse_response_format(None, file_value).expect("should parse"),
+            Some(json!({"type": "text"}))
+        );
+        assert_eq!(
+            parse_response_format(None, None).expect("should parse"),
+            None
+        );
+        assert!(parse_response_format(Some("[1]"), None).is_err());
+        assert!(parse_response_format(Some("{not json"), None).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use std::env;
+
+pub(super) fn env_u16_with_fallback(key: &str, fallback: u16) -> u16 {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<u16>().ok())
+        .unwrap_or(fallback)
+}
+
+pub(super) fn env_u32_with_fallback(key: &str, fallback: u32) -> u32 {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(fallback)
+}
+
+pub(super) fn env_u64_with_fallback(key: &str, fallback: u64) -> u64 {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(fallback)
+}
+
+pub(super) fn env_optional_u64(key: &str) -> Option<u64> {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|value| *value > 0)
+}
+
+pub(super) fn env_optional_f64(key: &str) -> Option<f64> {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.trim().parse::<f64>().ok())
+}
+
+pub(super) fn env_bool_with_fallback(key: &str, fallback: bool) -> bool {
+    env::var(key)
+        .ok()
+        .map(|value| {
+            matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        })
+        .unwrap_or(fallback)
+}
+
+pub(super) fn env_usize_with_fallback(key: &str, fallback: usize) -> usize {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(fallback)
+}
+
+/// A comma-separated env value or a config-file list, trimmed, with empty
+/// entries dropped.
+pub(super) fn resolve_list(
+    env_value: Option<String>,
+    file_value: Option<Vec<String>>,
+) -> Vec<String> {
+    let raw = match env_value {
+        Some(value) => value.split(',').map(str::to_string).collect(),
+        None => file_value.unwrap_or_default(),
+    };
+    raw.iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_list;
+
+    #[test]
+    fn resolve_no_proxy_prefers_env_and_drops_blanks() {
+        assert_eq!(
+            resolve_list(
+                Some(" localhost, ,.internal ".to_string()),
+                Some(vec!["file.example".to_string()])
+            ),
+            vec!["localhost".to_string(), ".internal".to_string()]
+        );
+        assert_eq!(
+            resolve_list(None, Some(vec!["10.0.0.0/8".to_string()])),
+            vec!["10.0.0.0/8".to_string()]
+        );
+        assert!(resolve_list(None, None).is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::env;
+
+use crate::config_file::RawConfig;
+use crate::model_routes::{ModelTarget, compile_model_routes, env_model_routes, find_model_route};
+
+use super::{Config, ModelRoutingRule, WireApi};
+
+/// Model tiers, per-model overrides and routing.
+pub(super) fn load(config: &mut Config, file_config: &mut RawConfig) -> Result<(), String> {
+    config.big_model = env::var("BIG_MODEL")
+        .ok()
+        .or(file_config.big_model.take())
+        .unwrap_or_else(|| "gpt-4o".to_string());
+    config.middle_model = env::var("MIDDLE_MODEL")
+        .ok()
+        .or(file_config.middle_model.take())
+        .unwrap_or_else(|| config.big_model.clone());
+    config.small_model = env::var("SMALL_MODEL")
+        .ok()
+        .or(file_config.small_model.take())
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    config.reasoning_models = parse_model_prefixes(
+        env::var("REASONING_MODELS")
+            .ok()
+            .or(file_config.reasoning_models.take())
+            .as_deref(),
+    );
+
+    config.model_timeouts = normalize_model_limits(file_config.model_timeouts.take());
+    config.model_stream_timeouts = normalize_model_limits(file_config.model_stream_timeouts.take());
+    config.model_aliases = normalize_model_strings(file_config.model_aliases.take());
+    config.system_prompt_prefix = normalize_model_strings(file_config.system_prompt_prefix.take());
+    config.model_temperature_overrides =
+        normalize_model_temperatures(file_config.model_temperature_overrides.take())?;
+    config.model_no_temperature = file_config
+        .model_no_temperature
+        .take()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|model| model.trim().to_ascii_lowercase())
+        .filter(|model| !model.is_empty())
+        .collect();
+
+    config.model_routing_rules =
+        validate_model_routing_rules(file_config.model_routing_rules.take().unwrap_or_default())?;
+    let env_routes = env_model_routes(|name| env::var(name).ok());
+    let raw_routes = if env_routes.is_empty() {
+        file_config.model_routing.take().unwrap_or_default()
+    } else {
+        env_routes
+    };
+    config.model_routes = compile_model_routes(raw_routes)?;
+    Ok(())
+}
+
+impl Config {
+    /// `model_aliases` win over `[[model_routing]]`; a route's
+    /// `wire_api_override` only applies when that route chose the model.
+    pub fn model_target_for(&self, claude_model: &str) -> ModelTarget<'_> {
+        if let Some(alias) = lookup_model_entry(&self.model_aliases, claude_model) {
+            return ModelTarget {
+                model: Some(alias),
+                wire_api: &self.wire_api,
+            };
+        }
+        match find_model_route(&self.model_routes, claude_model) {
+            Some(route) => ModelTarget {
+                model: Some(&route.target),
+                wire_api: route.wire_api_override.as_ref().unwrap_or(&self.wire_api),
+            },
+            None => ModelTarget {
+                model: None,
+                wire_api: &self.wire_api,
+            },
+        }
+    }
+
+    /// The wire API a request is sent over; see [`Config::model_target_for`].
+    pub fn wire_api_for(&self, claude_model: &str) -> &WireApi {
+        self.model_target_for(claude_model).wire_api
+    }
+}
+
+pub(super) fn normalize_model_limits<T>(raw: Option<HashMap<String, T>>) -> HashMap<String, T>
+where
+    T: Default + PartialOrd,
+{
+    raw.unwrap_or_default()
+        .into_iter()
+        .filter(|(_, limit)| *limit > T::default())
+        .map(|(model, limit)| (model.trim().to_ascii_lowercase(), limit))
+        .filter(|(model, _)| !model.is_empty())
+        .collect()
+}
+
+fn normalize_model_strings(raw: Option<HashMap<String, String>>) -> HashMap<String, String> {
+    raw.unwrap_or_default()
+        .into_iter()
+        .map(|(alias, target)| (alias.trim().to_ascii_lowercase(), target.trim().to_string()))
+        .filter(|(alias, target)| !alias.is_empty() && !target.is_empty())
+        .collect()
+}
+
+fn normalize_model_temperatures(
+    raw: Option<HashMap<String, f64>>,
+) -> Result<HashMap<String, f64>, String> {
+    let mut temperatures = HashMap::new();
+    for (model, temperature) in raw.unwrap_or_default() {
+        let model = model.trim().to_ascii_lowercase();
+        if model.is_empty() {
+            continue;
+        }
+        if !(0.0..=2.0).contains(&temperature) {
+            return Err(format!(
+                "model_temperature_overrides.{model} must be between 0.0 and 2.0"
+            ));
+        }
+        temperatures.insert(model, temperature);
+    }
+    Ok(temperatures)
+}
+
+pub(super) fn lookup_model_timeout(timeouts: &HashMap<String, u64>, model: &str) -> Option<u64> {
+    lookup_model_entry(timeouts, model).copied()
+}
+
+/// Exact model names win; otherwise the longest configured prefix applies,
+/// so `o1` covers `o1-preview` unless `o1-preview` has its own entry.
+pub(super) fn lookup_model_entry<'a, T>(
+    entries: &'a HashMap<String, T>,
+    model: &str,
+) -> Option<&'a T> {
+    let model = model.to_ascii_lowercase();
+    if let Some(value) = entries.get(&model) {
+        return Some(value);
+    }
+
+    entries
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, value)| value)
+}
+
+fn validate_model_routing_rules(
+    rules: Vec<ModelRoutingRule>,
+) -> Result<Vec<ModelRoutingRule>, String> {
+    rules
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut rule)| {
+            rule.target_model = rule.target_model.trim().to_string();
+            if rule.target_model.is_empty() {
+                return Err(format!(
+                    "model_routing_rules[{index}].target_model must not be empty"
+                ));
+            }
+            Ok(rule)
+        })
+        .collect()
+}
+
+fn parse_model_prefixes(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(|prefix| prefix.trim().to_ascii_lowercase())
+        .filter(|prefix| !prefix.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        lookup_model_entry, lookup_model_timeout, normalize_model_limits, normalize_model_strings,
+        normalize_model_temperatures, parse_model_prefixes,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn model_timeouts_prefer_exact_then_longest_prefix() {
+        let timeouts = normalize_model_limits(Some(HashMap::from([
+            ("o1".to_string(), 300),
+            ("O1-Mini".to_string(), 120),
+            ("gpt-4o-mini".to_string(), 0),
+        ])));
+
+        assert_eq!(lookup_model_timeout(&timeouts, "o1"), Some(300));
+        assert_eq!(lookup_model_timeout(&timeouts, "o1-preview"), Some(300));
+        assert_eq!(lookup_model_timeout(&timeouts, "o1-mini-2024"), Some(120));
+        assert_eq!(lookup_model_timeout(&timeouts, "gpt-4o-mini"), None);
+    }
+
+    #[test]
+    fn temperature_overrides_apply_only_without_request_value() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.model_temperature_overrides =
+            normalize_model_temperatures(Some(HashMap::from([("GPT-4o".to_string(), 0.7)])))
+                .expect("valid overrides");
+        config.model_no_temperature = vec!["o1".to_string()];
+
+        assert_eq!(config.temperature_for("gpt-4o-2024-08-06", None), Some(0.7));
+        assert_eq!(config.temperature_for("gpt-4o", Some(0.2)), Some(0.2));
+        assert_eq!(config.temperature_for("gpt-4.1", None), Some(1.0));
+        assert_eq!(config.temperature_for("o1-preview", Some(0.2)), None);
+        assert!(
+            normalize_model_temperatures(Some(HashMap::from([("o3".to_string(), 3.0)]))).is_err()
+        );
+    }
+
+    #[test]
+    fn model_aliases_match_case_insensitively_by_exact_then_prefix() {
+        let aliases = normalize_model_strings(Some(HashMap::from([
+            ("Claude-Code".to_string(), "gpt-4-turbo".to_string()),
+            ("claude-code-latest".to_string(), " gpt-4o ".to_string()),
+            ("empty".to_string(), " ".to_string()),
+        ])));
+
+        let lookup = |model| lookup_model_entry(&aliases, model).map(String::as_str);
+        assert_eq!(lookup("claude-code-latest"), Some("gpt-4o"));
+        assert_eq!(lookup("CLAUDE-CODE-beta"), Some("gpt-4-turbo"));
+        assert_eq!(lookup("empty"), None);
+        assert_eq!(lookup("claude-3-5-sonnet"), None);
+    }
+
+    #[test]
+    fn parse_model_prefixes_trims_and_lowercases_entries() {
+        assert_eq!(
+            parse_model_prefixes(Some(" My-R1 ,, custom-think ")),
+            vec!["my-r1".to_string(), "custom-think".to_string()]
+        );
+        assert!(parse_model_prefixes(None).is_empty());
+    }
+}
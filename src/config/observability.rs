@@ -0,0 +1,57 @@
+use std::env;
+
+use crate::config_file::RawConfig;
+
+use super::Config;
+use super::env::{env_bool_with_fallback, env_u32_with_fallback};
+
+/// Metrics, tracing export and request capture.
+pub(super) fn load(config: &mut Config, file_config: &mut RawConfig) {
+    config.metrics_enabled = env_bool_with_fallback(
+        "METRICS_ENABLED",
+        file_config.metrics_enabled.unwrap_or(true),
+    );
+    config.metrics_token = env::var("METRICS_TOKEN")
+        .ok()
+        .or(file_config.metrics_token.take())
+        .filter(|value| !value.trim().is_empty());
+    config.otel_exporter_otlp_endpoint = env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .or(file_config.otel_exporter_otlp_endpoint.take())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    config.otel_service_name = env::var("OTEL_SERVICE_NAME")
+        .ok()
+        .or(file_config.otel_service_name.take())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    config.capture_requests = env_bool_with_fallback(
+        "CAPTURE_REQUESTS",
+        file_config.capture_requests.unwrap_or(false),
+    );
+    config.capture_dir = env::var("CAPTURE_DIR")
+        .ok()
+        .or(file_config.capture_dir.take())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "captures".to_string());
+    config.max_capture_file_size_mb = env_u32_with_fallback(
+        "MAX_CAPTURE_FILE_SIZE_MB",
+        file_config.max_capture_file_size_mb.unwrap_or(100),
+    );
+}
+
+/// Admin access and start-up behaviour for operators.
+pub(super) fn load_operations(config: &mut Config, file_config: &mut RawConfig) {
+    config.admin_api_key = env::var("ADMIN_API_KEY")
+        .ok()
+        .or(file_config.admin_api_key.take())
+        .filter(|value| !value.trim().is_empty());
+    config.prewarm_upstream = env_bool_with_fallback(
+        "PREWARM_UPSTREAM",
+        file_config.prewarm_upstream.unwrap_or(false),
+    );
+    config.dry_run = env_bool_with_fallback("DRY_RUN", file_config.dry_run.unwrap_or(false))
+        || env::args().any(|arg| arg == "--dry-run");
+}
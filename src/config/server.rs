@@ -0,0 +1,139 @@
+use std::env;
+
+use crate::anthropic_version::parse_configured_version;
+use crate::config_file::RawConfig;
+
+use super::env::{
+    env_bool_with_fallback, env_u16_with_fallback, env_u64_with_fallback, env_usize_with_fallback,
+};
+use super::{Config, LogFormat};
+
+/// Listener, logging and TLS settings.
+pub(super) fn load(config: &mut Config, file_config: &mut RawConfig) -> Result<(), String> {
+    config.host = env::var("HOST")
+        .ok()
+        .or(file_config.host.take())
+        .unwrap_or_else(|| "0.0.0.0".to_string());
+    config.port = env_u16_with_fallback("PORT", file_config.port.unwrap_or(8082));
+    config.log_level = env::var("LOG_LEVEL")
+        .ok()
+        .or(file_config.log_level.take())
+        .unwrap_or_else(|| "INFO".to_string());
+    let log_format_raw = env::var("LOG_FORMAT")
+        .ok()
+        .or(file_config.log_format.take());
+    config.log_format = parse_log_format(log_format_raw.as_deref())?;
+
+    config.tls_cert_path = env::var("TLS_CERT_PATH")
+        .ok()
+        .or(file_config.tls_cert_path.take())
+        .filter(|value| !value.trim().is_empty());
+    config.tls_key_path = env::var("TLS_KEY_PATH")
+        .ok()
+        .or(file_config.tls_key_path.take())
+        .filter(|value| !value.trim().is_empty());
+    if config.tls_cert_path.is_some() != config.tls_key_path.is_some() {
+        return Err("TLS_CERT_PATH and TLS_KEY_PATH must be set together".to_string());
+    }
+
+    config.shutdown_timeout_secs = env_u64_with_fallback(
+        "SHUTDOWN_TIMEOUT_SECS",
+        file_config.shutdown_timeout_secs.unwrap_or(30),
+    );
+    Ok(())
+}
+
+/// Header checks and response shaping applied by the HTTP middleware stack.
+pub(super) fn load_http(config: &mut Config, file_config: &mut RawConfig) -> Result<(), String> {
+    config.min_anthropic_version = parse_configured_version(
+        "MIN_ANTHROPIC_VERSION",
+        env::var("MIN_ANTHROPIC_VERSION")
+            .ok()
+            .or(file_config.min_anthropic_version.take()),
+    )?;
+    config.anthropic_version_override = parse_configured_version(
+        "ANTHROPIC_VERSION_OVERRIDE",
+        env::var("ANTHROPIC_VERSION_OVERRIDE")
+            .ok()
+            .or(file_config.anthropic_version_override.take()),
+    )?;
+    config.cors_allowed_origins = resolve_cors_origins(
+        env::var("CORS_ALLOWED_ORIGINS").ok(),
+        file_config.cors_allowed_origins.take(),
+    );
+    config.compress_responses = env_bool_with_fallback(
+        "COMPRESS_RESPONSES",
+        file_config.compress_responses.unwrap_or(false),
+    );
+    config.min_compress_size_bytes = env_usize_with_fallback(
+        "MIN_COMPRESS_SIZE_BYTES",
+        file_config.min_compress_size_bytes.unwrap_or(1024),
+    );
+    Ok(())
+}
+
+fn parse_log_format(value: Option<&str>) -> Result<LogFormat, String> {
+    let Some(raw_value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(LogFormat::Text);
+    };
+
+    match raw_value.to_ascii_lowercase().as_str() {
+        "text" => Ok(LogFormat::Text),
+        "json" => Ok(LogFormat::Json),
+        _ => Err(format!(
+            "Invalid LOG_FORMAT value '{raw_value}'. Supported values: text, json."
+        )),
+    }
+}
+
+/// Same list sources as `resolve_base_urls`; an empty result allows any origin.
+fn resolve_cors_origins(env_value: Option<String>, file_value: Option<Vec<String>>) -> Vec<String> {
+    let raw = match env_value {
+        Some(value) => value.split(',').map(str::to_string).collect(),
+        None => file_value.unwrap_or_default(),
+    };
+    let origins: Vec<String> = raw
+        .iter()
+        .map(|origin| origin.trim().trim_end_matches('/').to_string())
+        .filter(|origin| !origin.is_empty())
+        .collect();
+    if origins.is_empty() {
+        vec!["*".to_string()]
+    } else {
+        origins
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogFormat, parse_log_format, resolve_cors_origins};
+
+    #[test]
+    fn resolve_cors_origins_defaults_to_wildcard() {
+        assert_eq!(resolve_cors_origins(None, None), vec!["*".to_string()]);
+        assert_eq!(
+            resolve_cors_origins(
+                Some("https://a.example/, https://b.example".to_string()),
+                None
+            ),
+            vec![
+                "https://a.example".to_string(),
+                "https://b.example".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_log_format_defaults_to_text_and_rejects_unknown() {
+        assert_eq!(
+            parse_log_format(None).expect("should parse"),
+            LogFormat::Text
+        );
+        assert_eq!(
+            parse_log_format(Some(" JSON ")).expect("should parse"),
+            LogFormat::Json
+        );
+        let error = parse_log_format(Some("xml")).expect_err("should fail");
+        assert!(error.contains("Invalid LOG_FORMAT value 'xml'"));
+    }
+}
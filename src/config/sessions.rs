@@ -0,0 +1,89 @@
+use crate::config_file::RawConfig;
+
+use super::Config;
+use super::env::{
+    env_bool_with_fallback, env_optional_f64, env_optional_u64, env_u32_with_fallback,
+    env_u64_with_fallback, env_usize_with_fallback,
+};
+use super::models::normalize_model_limits;
+
+/// Session lifetimes, per-client limits and how session ids are exposed.
+pub(super) fn load(config: &mut Config, file_config: &RawConfig) -> Result<(), String> {
+    config.session_ttl_min_secs = env_u64_with_fallback(
+        "SESSION_TTL_MIN_SECS",
+        file_config.session_ttl_min_secs.unwrap_or(1800),
+    );
+    config.session_ttl_max_secs = env_u64_with_fallback(
+        "SESSION_TTL_MAX_SECS",
+        file_config.session_ttl_max_secs.unwrap_or(86400),
+    );
+    config.session_cleanup_interval_secs = env_u64_with_fallback(
+        "SESSION_CLEANUP_INTERVAL_SECS",
+        file_config.session_cleanup_interval_secs.unwrap_or(60),
+    );
+    validate_session_config(
+        config.session_ttl_min_secs,
+        config.session_ttl_max_secs,
+        config.session_cleanup_interval_secs,
+    )?;
+    config.batch_retention_secs = env_u64_with_fallback(
+        "BATCH_RETENTION_SECS",
+        file_config.batch_retention_secs.unwrap_or(86400),
+    );
+
+    config.rate_limit_rpm =
+        env_u32_with_fallback("RATE_LIMIT_RPM", file_config.rate_limit_rpm.unwrap_or(0));
+    config.rate_limit_burst = env_u32_with_fallback(
+        "RATE_LIMIT_BURST",
+        file_config.rate_limit_burst.unwrap_or(10),
+    );
+    config.max_requests_per_session = env_optional_u64("MAX_REQUESTS_PER_SESSION")
+        .or(file_config.max_requests_per_session)
+        .filter(|value| *value > 0);
+
+    config.propagate_session_id_as_user = env_bool_with_fallback(
+        "PROPAGATE_SESSION_ID_AS_USER",
+        file_config.propagate_session_id_as_user.unwrap_or(false),
+    );
+    config.allow_client_session_id = env_bool_with_fallback(
+        "ALLOW_CLIENT_SESSION_ID",
+        file_config.allow_client_session_id.unwrap_or(false),
+    );
+    Ok(())
+}
+
+/// Request body size limits, global and per model.
+pub(super) fn load_body_limits(
+    config: &mut Config,
+    file_config: &mut RawConfig,
+) -> Result<(), String> {
+    config.request_body_max_size = env_usize_with_fallback(
+        "REQUEST_BODY_MAX_SIZE",
+        file_config
+            .request_body_max_size
+            .unwrap_or(16 * 1024 * 1024),
+    );
+    let per_token = env_optional_f64("REQUEST_BODY_MAX_SIZE_PER_TOKEN")
+        .or(file_config.request_body_max_size_per_token)
+        .unwrap_or(0.0);
+    if !(per_token.is_finite() && per_token >= 0.0) {
+        return Err("REQUEST_BODY_MAX_SIZE_PER_TOKEN must be a non-negative number".to_string());
+    }
+    config.request_body_max_size_per_token = per_token;
+    config.model_body_max_sizes = normalize_model_limits(file_config.model_body_max_sizes.take());
+    Ok(())
+}
+
+fn validate_session_config(min_secs: u64, max_secs: u64, cleanup_secs: u64) -> Result<(), String> {
+    if min_secs == 0 {
+        return Err("SESSION_TTL_MIN_SECS must be > 0".to_string());
+    }
+    if max_secs < min_secs {
+        return Err("SESSION_TTL_MAX_SECS must be >= SESSION_TTL_MIN_SECS".to_string());
+    }
+    if cleanup_secs == 0 {
+        return Err("SESSION_CLEANUP_INTERVAL_SECS must be > 0".to_string());
+    }
+
+    Ok(())
+}
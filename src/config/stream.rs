@@ -0,0 +1,62 @@
+use std::env;
+
+use crate::config_file::RawConfig;
+
+use super::env::{env_bool_with_fallback, env_u32_with_fallback};
+use super::{Config, ToolJsonMode};
+
+/// How streamed responses are relayed to the client.
+pub(super) fn load(config: &mut Config, file_config: &mut RawConfig) -> Result<(), String> {
+    config.strict_json_validation = env_bool_with_fallback(
+        "STRICT_JSON_VALIDATION",
+        file_config.strict_json_validation.unwrap_or(false),
+    );
+    config.streaming_tool_json_mode = parse_tool_json_mode(
+        env::var("STREAMING_TOOL_JSON_MODE")
+            .ok()
+            .or(file_config.streaming_tool_json_mode.take())
+            .as_deref(),
+    )?;
+    config.max_consecutive_send_errors = env_u32_with_fallback(
+        "MAX_CONSECUTIVE_SEND_ERRORS",
+        file_config.max_consecutive_send_errors.unwrap_or(3),
+    );
+    config.infer_stop_sequence = env_bool_with_fallback(
+        "INFER_STOP_SEQUENCE",
+        file_config.infer_stop_sequence.unwrap_or(false),
+    );
+    Ok(())
+}
+
+fn parse_tool_json_mode(value: Option<&str>) -> Result<ToolJsonMode, String> {
+    let Some(raw_value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(ToolJsonMode::Streaming);
+    };
+
+    match raw_value.to_ascii_lowercase().as_str() {
+        "streaming" => Ok(ToolJsonMode::Streaming),
+        "buffered" => Ok(ToolJsonMode::Buffered),
+        _ => Err(format!(
+            "Invalid STREAMING_TOOL_JSON_MODE value '{raw_value}'. Supported values: streaming, buffered."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ToolJsonMode, parse_tool_json_mode};
+
+    #[test]
+    fn parse_tool_json_mode_defaults_to_streaming_and_rejects_unknown() {
+        assert_eq!(
+            parse_tool_json_mode(None).expect("should parse"),
+            ToolJsonMode::Streaming
+        );
+        assert_eq!(
+            parse_tool_json_mode(Some(" Buffered ")).expect("should parse"),
+            ToolJsonMode::Buffered
+        );
+        let error = parse_tool_json_mode(Some("patch")).expect_err("should fail");
+        assert!(error.contains("Invalid STREAMING_TOOL_JSON_MODE value 'patch'"));
+    }
+}
@@ -0,0 +1,122 @@
+use std::env;
+
+use crate::config_file::RawConfig;
+
+use super::Config;
+use super::env::{env_bool_with_fallback, env_optional_f64};
+
+/// How extended-thinking requests and reasoning output are translated.
+pub(super) fn load(config: &mut Config, file_config: &mut RawConfig) -> Result<(), String> {
+    let min_thinking_level_raw = env::var("MIN_THINKING_LEVEL")
+        .ok()
+        .or(file_config.min_thinking_level.take());
+    config.min_thinking_level = parse_min_thinking_level(min_thinking_level_raw.as_deref())?;
+    config.enforce_min_tokens_for_thinking = env_bool_with_fallback(
+        "ENFORCE_MIN_TOKENS_FOR_THINKING",
+        file_config.enforce_min_tokens_for_thinking.unwrap_or(false),
+    );
+    config.min_tokens_thinking_fraction = env_optional_f64("MIN_TOKENS_THINKING_FRACTION")
+        .or(file_config.min_tokens_thinking_fraction)
+        .unwrap_or(0.5);
+    let fraction = config.min_tokens_thinking_fraction;
+    if !(fraction > 0.0 && fraction <= 1.0) {
+        return Err(
+            "MIN_TOKENS_THINKING_FRACTION must be greater than 0.0 and at most 1.0".to_string(),
+        );
+    }
+
+    // `include_thinking_in_history` is an alias; the canonical name wins
+    // when both are set in the same source.
+    let propagate_from_file = file_config
+        .propagate_thinking_blocks
+        .or(file_config.include_thinking_in_history)
+        .unwrap_or(false);
+    config.propagate_thinking_blocks = env_bool_with_fallback(
+        "PROPAGATE_THINKING_BLOCKS",
+        env_bool_with_fallback("INCLUDE_THINKING_IN_HISTORY", propagate_from_file),
+    );
+    config.preserve_thinking_signatures = env_bool_with_fallback(
+        "PRESERVE_THINKING_SIGNATURES",
+        file_config.preserve_thinking_signatures.unwrap_or(true),
+    );
+    config.thinking_continuation_mode = env_bool_with_fallback(
+        "THINKING_CONTINUATION_MODE",
+        file_config.thinking_continuation_mode.unwrap_or(false),
+    );
+    config.thinking_as_text = env_bool_with_fallback(
+        "THINKING_AS_TEXT",
+        file_config.thinking_as_text.unwrap_or(false),
+    );
+    Ok(())
+}
+
+fn parse_min_thinking_level(value: Option<&str>) -> Result<Option<String>, String> {
+    let Some(raw_value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+
+    let normalized = raw_value.to_ascii_lowercase();
+    match normalized.as_str() {
+        "low" | "medium" | "high" => Ok(Some(normalized)),
+        _ => Err(format!(
+            "Invalid MIN_THINKING_LEVEL value '{raw_value}'. Supported values: low, medium, high."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, parse_min_thinking_level};
+    use crate::config::Config;
+    use crate::config_file::RawConfig;
+
+    #[test]
+    fn include_thinking_in_history_is_an_alias_for_propagate_thinking_blocks() {
+        let mut config = Config::default();
+        let mut file_config = RawConfig {
+            include_thinking_in_history: Some(true),
+            ..RawConfig::default()
+        };
+        load(&mut config, &mut file_config).expect("should load");
+        assert!(config.propagate_thinking_blocks);
+
+        let mut file_config = RawConfig {
+            propagate_thinking_blocks: Some(false),
+            include_thinking_in_history: Some(true),
+            ..RawConfig::default()
+        };
+        load(&mut config, &mut file_config).expect("should load");
+        assert!(!config.propagate_thinking_blocks);
+    }
+
+    #[test]
+    fn parse_min_thinking_level_accepts_valid_values_case_insensitive() {
+        assert_eq!(
+            parse_min_thinking_level(Some("  LOW ")).expect("should parse"),
+            Some("low".to_string())
+        );
+        assert_eq!(
+            parse_min_thinking_level(Some("Medium")).expect("should parse"),
+            Some("medium".to_string())
+        );
+        assert_eq!(
+            parse_min_thinking_level(Some("HIGH")).expect("should parse"),
+            Some("high".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_min_thinking_level_treats_empty_as_none() {
+        assert_eq!(parse_min_thinking_level(None).expect("should parse"), None);
+        assert_eq!(
+            parse_min_thinking_level(Some("   ")).expect("should parse"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_min_thinking_level_rejects_invalid_values() {
+        let error = parse_min_thinking_level(Some("max")).expect_err("should fail");
+        assert!(error.contains("Invalid MIN_THINKING_LEVEL value 'max'"));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::env;
+
+use crate::config_file::RawConfig;
+
+use super::env::{
+    env_optional_f64, env_optional_u64, env_u32_with_fallback, env_u64_with_fallback, resolve_list,
+};
+use super::{Config, WireApi};
+
+/// Credentials, endpoints and headers for the upstream OpenAI-compatible API.
+pub(super) fn load(config: &mut Config, file_config: &mut RawConfig) -> Result<(), String> {
+    config.openai_api_key = env::var("OPENAI_API_KEY")
+        .ok()
+        .or(file_config.openai_api_key.take())
+        .ok_or_else(|| {
+            "OPENAI_API_KEY not found in environment variables and config file".to_string()
+        })?;
+    config.anthropic_api_key = env::var("ANTHROPIC_API_KEY")
+        .ok()
+        .or(file_config.anthropic_api_key.take());
+
+    config.openai_base_url = env::var("OPENAI_BASE_URL")
+        .ok()
+        .or(file_config.openai_base_url.take())
+        .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+    config.openai_base_urls = resolve_base_urls(
+        env::var("OPENAI_BASE_URLS").ok(),
+        file_config.openai_base_urls.take(),
+        &config.openai_base_url,
+    );
+    config.azure_api_version = env::var("AZURE_API_VERSION")
+        .ok()
+        .or(file_config.azure_api_version.take());
+
+    let wire_api_raw = env::var("WIRE_API").ok().or(file_config.wire_api.take());
+    config.wire_api = parse_wire_api(wire_api_raw.as_deref())?;
+
+    config.echo_upstream_headers = resolve_echo_headers(
+        env::var("ECHO_UPSTREAM_HEADERS").ok(),
+        file_config.echo_upstream_headers.take(),
+    )?;
+    config.custom_headers = file_config.custom_headers.take().unwrap_or_default();
+    config.custom_headers.extend(collect_custom_headers());
+    Ok(())
+}
+
+pub(super) fn load_proxy(config: &mut Config, file_config: &mut RawConfig) -> Result<(), String> {
+    config.upstream_proxy = env::var("UPSTREAM_PROXY")
+        .ok()
+        .or(file_config.upstream_proxy.take())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    config.upstream_proxy_basic_auth = env::var("UPSTREAM_PROXY_BASIC_AUTH")
+        .ok()
+        .or(file_config.upstream_proxy_basic_auth.take())
+        .filter(|value| !value.is_empty());
+    if config
+        .upstream_proxy_basic_auth
+        .as_deref()
+        .is_some_and(|value| !value.contains(':'))
+    {
+        return Err("UPSTREAM_PROXY_BASIC_AUTH must be in user:pass form".to_string());
+    }
+    config.no_proxy = resolve_list(env::var("NO_PROXY").ok(), file_config.no_proxy.take());
+    Ok(())
+}
+
+/// Timeouts, retries and the circuit breaker guarding upstream calls.
+pub(super) fn load_resilience(config: &mut Config, file_config: &RawConfig) -> Result<(), String> {
+    config.request_timeout =
+        env_u64_with_fallback("REQUEST_TIMEOUT", file_config.request_timeout.unwrap_or(90));
+    config.stream_request_timeout = env_optional_u64("STREAM_REQUEST_TIMEOUT")
+        .or(file_config.stream_request_timeout)
+        .filter(|value| *value > 0);
+
+    config.max_retries = env_u32_with_fallback("MAX_RETRIES", file_config.max_retries.unwrap_or(2));
+    config.retry_base_delay_ms = env_u64_with_fallback(
+        "RETRY_BASE_DELAY_MS",
+        file_config.retry_base_delay_ms.unwrap_or(500),
+    );
+    config.retry_jitter_factor = env_optional_f64("RETRY_JITTER_FACTOR")
+        .or(file_config.retry_jitter_factor)
+        .unwrap_or(0.25);
+    if !(0.0..=1.0).contains(&config.retry_jitter_factor) {
+        return Err("RETRY_JITTER_FACTOR must be between 0.0 and 1.0".to_string());
+    }
+
+    config.circuit_breaker_failure_threshold = env_u32_with_fallback(
+        "CIRCUIT_BREAKER_FAILURE_THRESHOLD",
+        file_config.circuit_breaker_failure_threshold.unwrap_or(5),
+    );
+    config.circuit_breaker_success_threshold = env_u32_with_fallback(
+        "CIRCUIT_BREAKER_SUCCESS_THRESHOLD",
+        file_config.circuit_breaker_success_threshold.unwrap_or(1),
+    );
+    config.circuit_breaker_open_duration_secs = env_u64_with_fallback(
+        "CIRCUIT_BREAKER_OPEN_DURATION_SECS",
+        file_config.circuit_breaker_open_duration_secs.unwrap_or(30),
+    );
+    Ok(())
+}
+
+fn collect_custom_headers() -> HashMap<String, String> {
+    let mut custom_headers = HashMap::new();
+    for (env_key, env_value) in env::vars() {
+        let Some(header_raw) = env_key.strip_prefix("CUSTOM_HEADER_") else {
+            continue;
+        };
+        if header_raw.is_empty() {
+            continue;
+        }
+        custom_headers.insert(header_raw.replace('_', "-"), env_value);
+    }
+    custom_headers
+}
+
+pub(crate) fn parse_wire_api(value: Option<&str>) -> Result<WireApi, String> {
+    let Some(raw_value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(WireApi::Chat);
+    };
+
+    match raw_value.to_ascii_lowercase().as_str() {
+        "chat" => Ok(WireApi::Chat),
+        "responses" => Ok(WireApi::Responses),
+        _ => Err(format!(
+            "Invalid WIRE_API value '{raw_value}'. Supported values: chat, responses."
+        )),
+    }
+}
+
+/// `OPENAI_BASE_URLS` is comma-separated; the config file takes a list. An
+/// empty result falls back to the single `openai_base_url`.
+fn resolve_base_urls(
+    env_value: Option<String>,
+    file_value: Option<Vec<String>>,
+    fallback: &str,
+) -> Vec<String> {
+    let raw = match env_value {
+        Some(value) => value.split(',').map(str::to_string).collect(),
+        None => file_value.unwrap_or_default(),
+    };
+    let urls: Vec<String> = raw
+        .iter()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .collect();
+    if urls.is_empty() {
+        vec![fallback.trim_end_matches('/').to_string()]
+    } else {
+        urls
+    }
+}
+
+/// Lowercased upstream response header names; each must be a valid header
+/// name so the `x-upstream-` copy can always be built.
+fn resolve_echo_headers(
+    env_value: Option<String>,
+    file_value: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let raw = match env_value {
+        Some(value) => value.split(',').map(str::to_string).collect(),
+        None => file_value.unwrap_or_default(),
+    };
+    raw.iter()
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .map(
+            |name| match reqwest::header::HeaderName::from_bytes(name.as_bytes()) {
+                Ok(_) => Ok(name),
+                Err(_) => Err(format!("Invalid ECHO_UPSTREAM_HEADERS entry '{name}'")),
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_base_urls, resolve_echo_headers};
+
+    #[test]
+    fn resolve_base_urls_prefers_env_and_falls_back_to_single_url() {
+        assert_eq!(
+            resolve_base_urls(
+                Some(" http://a:8000/v1/ ,,http://b:8000/v1".to_string()),
+                Some(vec!["http://file/v1".to_string()]),
+                "https://api.openai.com/v1",
+            ),
+            vec![
+                "http://a:8000/v1".to_string(),
+                "http://b:8000/v1".to_string()
+            ]
+        );
+        assert_eq!(
+            resolve_base_urls(None, Some(vec!["http://file/v1/".to_string()]), "x"),
+            vec!["http://file/v1".to_string()]
+        );
+        assert_eq!(
+            resolve_base_urls(Some(" ".to_string()), None, "https://api.openai.com/v1/"),
+            vec!["https://api.openai.com/v1".to_string()]
+        );
+    }
+
+    #[test]
+    fn resolve_echo_headers_lowercases_and_rejects_invalid_names() {
+        assert_eq!(
+            resolve_echo_headers(Some(" X-Request-Id ,,apim-request-id".to_string()), None)
+                .expect("valid names"),
+            vec!["x-request-id".to_string(), "apim-request-id".to_string()]
+        );
+        assert!(resolve_echo_headers(None, Some(vec!["bad header".to_string()])).is_err());
+    }
+}
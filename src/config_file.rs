@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::ModelRoutingRule;
+use crate::model_routes::RawModelRoute;
+
+const DEFAULT_CONFIG_FILES: [&str; 3] = ["config.toml", "config.yaml", "config.yml"];
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RawConfig {
+    pub openai_api_key: Option<String>,
+    pub anthropic_api_key: Option<String>,
+    pub openai_base_url: Option<String>,
+    pub openai_base_urls: Option<Vec<String>>,
+    pub azure_api_version: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub log_level: Option<String>,
+    pub log_format: Option<String>,
+    pub request_timeout: Option<u64>,
+    pub stream_request_timeout: Option<u64>,
+    pub model_timeouts: Option<HashMap<String, u64>>,
+    pub model_aliases: Option<HashMap<String, String>>,
+    pub model_routing_rules: Option<Vec<ModelRoutingRule>>,
+    #[serde(alias = "model_routes")]
+    pub model_routing: Option<Vec<RawModelRoute>>,
+    pub system_prompt_prefix: Option<HashMap<String, String>>,
+    pub model_temperature_overrides: Option<HashMap<String, f64>>,
+    pub model_no_temperature: Option<Vec<String>>,
+    pub model_stream_timeouts: Option<HashMap<String, u64>>,
+    pub max_retries: Option<u32>,
+    pub retry_base_delay_ms: Option<u64>,
+    pub retry_jitter_factor: Option<f64>,
+    pub circuit_breaker_failure_threshold: Option<u32>,
+    pub circuit_breaker_success_threshold: Option<u32>,
+    pub circuit_breaker_open_duration_secs: Option<u64>,
+    pub rate_limit_rpm: Option<u32>,
+    pub rate_limit_burst: Option<u32>,
+    pub max_requests_per_session: Option<u64>,
+    pub request_body_max_size: Option<usize>,
+    pub request_body_max_size_per_token: Option<f64>,
+    pub model_body_max_sizes: Option<HashMap<String, usize>>,
+    pub session_ttl_min_secs: Option<u64>,
+    pub session_ttl_max_secs: Option<u64>,
+    pub session_cleanup_interval_secs: Option<u64>,
+    pub batch_retention_secs: Option<u64>,
+    pub shutdown_timeout_secs: Option<u64>,
+    pub upstream_proxy: Option<String>,
+    pub upstream_proxy_basic_auth: Option<String>,
+    pub no_proxy: Option<Vec<String>>,
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    pub debug_tool_id_matching: Option<bool>,
+    pub wire_api: Option<String>,
+    pub big_model: Option<String>,
+    pub middle_model: Option<String>,
+    pub small_model: Option<String>,
+    pub min_thinking_level: Option<String>,
+    pub enforce_min_tokens_for_thinking: Option<bool>,
+    pub min_tokens_thinking_fraction: Option<f64>,
+    pub reasoning_models: Option<String>,
+    pub infer_stop_sequence: Option<bool>,
+    pub dry_run: Option<bool>,
+    pub prewarm_upstream: Option<bool>,
+    pub system_block_separator: Option<String>,
+    pub cache_boundary_separator: Option<String>,
+    pub propagate_thinking_blocks: Option<bool>,
+    pub include_thinking_in_history: Option<bool>,
+    pub preserve_thinking_signatures: Option<bool>,
+    pub thinking_continuation_mode: Option<bool>,
+    pub normalize_message_order: Option<bool>,
+    pub thinking_as_text: Option<bool>,
+    pub document_passthrough: Option<bool>,
+    pub video_to_text_placeholder: Option<bool>,
+    pub accept_legacy_function_role: Option<bool>,
+    pub responses_base64_image_source: Option<bool>,
+    pub use_stateful_responses: Option<bool>,
+    pub responses_include: Option<Vec<String>>,
+    pub propagate_session_id_as_user: Option<bool>,
+    pub allow_client_session_id: Option<bool>,
+    pub drop_unsupported_tools: Option<bool>,
+    pub validate_tool_schemas: Option<bool>,
+    pub repair_tool_schemas: Option<bool>,
+    pub tool_schema_cache_hints: Option<bool>,
+    pub strict_json_validation: Option<bool>,
+    pub streaming_tool_json_mode: Option<String>,
+    pub max_consecutive_send_errors: Option<u32>,
+    pub content_filter_mode: Option<String>,
+    pub content_filter_message: Option<String>,
+    pub empty_response_fallback_text: Option<String>,
+    pub capture_requests: Option<bool>,
+    pub capture_dir: Option<String>,
+    pub max_capture_file_size_mb: Option<u32>,
+    pub default_frequency_penalty: Option<f64>,
+    pub default_presence_penalty: Option<f64>,
+    pub default_response_format: Option<serde_json::Value>,
+    pub metrics_enabled: Option<bool>,
+    pub metrics_token: Option<String>,
+    pub admin_api_key: Option<String>,
+    pub min_anthropic_version: Option<String>,
+    pub anthropic_version_override: Option<String>,
+    pub otel_exporter_otlp_endpoint: Option<String>,
+    pub otel_service_name: Option<String>,
+    pub cors_allowed_origins: Option<Vec<String>>,
+    pub echo_upstream_headers: Option<Vec<String>>,
+    pub compress_responses: Option<bool>,
+    pub min_compress_size_bytes: Option<usize>,
+    pub custom_headers: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ConfigFormat {
+    Toml,
+    Yaml,
+}
+
+pub fn read_raw_config() -> Result<RawConfig, String> {
+    if let Some(explicit_path) = env::var("CONFIG_FILE")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        return read_config_file(Path::new(explicit_path.trim()))?
+            .ok_or_else(|| format!("CONFIG_FILE not found: {explicit_path}"));
+    }
+
+    for candidate in DEFAULT_CONFIG_FILES {
+        if let Some(parsed) = read_config_file(Path::new(candidate))? {
+            return Ok(parsed);
+        }
+    }
+
+    Ok(RawConfig::default())
+}
+
+fn read_config_file(config_path: &Path) -> Result<Option<RawConfig>, String> {
+    if !config_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(config_path)
+        .map_err(|error| format!("Failed to read {}: {}", config_path.display(), error))?;
+
+    parse_config(&content, config_format(config_path))
+        .map(Some)
+        .map_err(|error| format!("Failed to parse {}: {}", config_path.display(), error))
+}
+
+fn config_format(config_path: &Path) -> ConfigFormat {
+    match config_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+        _ => ConfigFormat::Toml,
+    }
+}
+
+fn parse_config(content: &str, format: ConfigFormat) -> Result<RawConfig, String> {
+    match format {
+        ConfigFormat::Toml => toml::from_str(content).map_err(|error| error.to_string()),
+        ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|error| error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigFormat, config_format, parse_config};
+    use std::path::Path;
+
+    #[test]
+    fn detects_format_from_extension() {
+        assert_eq!(config_format(Path::new("config.toml")), ConfigFormat::Toml);
+        assert_eq!(config_format(Path::new("config.YAML")), ConfigFormat::Yaml);
+        assert_eq!(
+            config_format(Path::new("/etc/bridge.yml")),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(config_format(Path::new("bridge")), ConfigFormat::Toml);
+    }
+
+    #[test]
+    fn toml_and_yaml_parse_into_same_raw_config() {
+        let toml_raw = parse_config(
+            "big_model = \"gpt-4o\"\nport = 9000\n[custom_headers]\nX-Team = \"platform\"\n",
+            ConfigFormat::Toml,
+        )
+        .expect("toml should parse");
+        let yaml_raw = parse_config(
+            "big_model: gpt-4o\nport: 9000\ncustom_headers:\n  X-Team: platform\n",
+            ConfigFormat::Yaml,
+        )
+        .expect("yaml should parse");
+
+        for raw in [toml_raw, yaml_raw] {
+            assert_eq!(raw.big_model.as_deref(), Some("gpt-4o"));
+            assert_eq!(raw.port, Some(9000));
+            assert_eq!(
+                raw.custom_headers
+                    .as_ref()
+                    .and_then(|headers| headers.get("X-Team"))
+                    .map(String::as_str),
+                Some("platform")
+            );
+        }
+    }
+
+    #[test]
+    fn yaml_supports_multiline_header_values() {
+        let raw = parse_config(
+            "custom_headers:\n  X-Note: |\n    line one\n    line two\n",
+            ConfigFormat::Yaml,
+        )
+        .expect("yaml should parse");
+        let note = raw
+            .custom_headers
+            .and_then(|headers| headers.get("X-Note").cloned());
+        assert_eq!(note.as_deref(), Some("line one\nline two\n"));
+    }
+}
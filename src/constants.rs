@@ -2,6 +2,7 @@ pub const ROLE_USER: &str = "user";
 pub const ROLE_ASSISTANT: &str = "assistant";
 pub const ROLE_SYSTEM: &str = "system";
 pub const ROLE_TOOL: &str = "tool";
+pub const ROLE_FUNCTION: &str = "function";
 
 pub const CONTENT_TEXT: &str = "text";
 #[allow(dead_code)]
@@ -14,9 +15,17 @@ pub const CONTENT_THINKING: &str = "thinking";
 
 pub const TOOL_FUNCTION: &str = "function";
 
+pub const ANTHROPIC_BETA_HEADER: &str = "anthropic-beta";
+pub const ANTHROPIC_VERSION_HEADER: &str = "anthropic-version";
+pub const BETA_INTERLEAVED_THINKING_PREFIX: &str = "interleaved-thinking-";
+
 pub const STOP_END_TURN: &str = "end_turn";
 pub const STOP_MAX_TOKENS: &str = "max_tokens";
 pub const STOP_TOOL_USE: &str = "tool_use";
+pub const STOP_SEQUENCE: &str = "stop_sequence";
+/// Internal only: Claude has no such stop reason, so it is resolved per
+/// `content_filter_mode` before reaching the client.
+pub const STOP_CONTENT_FILTERED: &str = "content_filtered";
 
 pub const EVENT_MESSAGE_START: &str = "message_start";
 pub const EVENT_MESSAGE_STOP: &str = "message_stop";
@@ -1,10 +1,37 @@
 use serde_json::Value;
 use tracing::warn;
 
+use crate::constants::ROLE_ASSISTANT;
 use crate::conversion::request::models::{OpenAiAssistantMessage, OpenAiMessage, OpenAiToolCall};
 use crate::models::{ClaudeContent, ClaudeContentBlock, ClaudeMessage};
 
-pub fn convert_claude_assistant_message(message: &ClaudeMessage) -> OpenAiMessage {
+/// Reasoning a client seeded in a trailing assistant prefill: the text of
+/// an unsigned thinking block, which the reply should continue rather than
+/// restart.
+pub fn seeded_thinking(messages: &[ClaudeMessage]) -> Option<String> {
+    let last = messages
+        .last()
+        .filter(|message| message.role == ROLE_ASSISTANT)?;
+    let Some(ClaudeContent::Blocks(blocks)) = &last.content else {
+        return None;
+    };
+    blocks.iter().find_map(|block| match block {
+        ClaudeContentBlock::Thinking {
+            thinking,
+            signature,
+            ..
+        } if signature.as_deref().is_none_or(str::is_empty) && !thinking.is_empty() => {
+            Some(thinking.clone())
+        }
+        _ => None,
+    })
+}
+
+pub fn convert_claude_assistant_message(
+    message: &ClaudeMessage,
+    propagate_thinking_blocks: bool,
+    preserve_thinking_signatures: bool,
+) -> OpenAiMessage {
     let Some(content) = &message.content else {
         return OpenAiMessage::Assistant(OpenAiAssistantMessage::from_text_and_tools(None, vec![]));
     };
@@ -14,7 +41,11 @@ pub fn convert_claude_assistant_message(message: &ClaudeMessage) -> OpenAiMessag
             OpenAiAssistantMessage::from_text_and_tools(Some(text_content.to_string()), vec![]),
         ),
         ClaudeContent::Blocks(blocks) => {
-            let (text_parts, tool_calls) = extract_assistant_parts(blocks);
+            let (text_parts, tool_calls) = extract_assistant_parts(
+                blocks,
+                propagate_thinking_blocks,
+                preserve_thinking_signatures,
+            );
             let content_text = if text_parts.is_empty() {
                 None
             } else {
@@ -32,13 +63,33 @@ pub fn convert_claude_assistant_message(message: &ClaudeMessage) -> OpenAiMessag
     }
 }
 
-fn extract_assistant_parts(blocks: &[ClaudeContentBlock]) -> (Vec<String>, Vec<OpenAiToolCall>) {
+/// Chat completions has no field for earlier reasoning, so when propagation
+/// is enabled thinking blocks are replayed ahead of the visible text as
+/// `<thinking>…</thinking>`, carrying the block's signature as an attribute
+/// when `preserve_thinking_signatures` is on; otherwise they are dropped.
+fn extract_assistant_parts(
+    blocks: &[ClaudeContentBlock],
+    propagate_thinking_blocks: bool,
+    preserve_thinking_signatures: bool,
+) -> (Vec<String>, Vec<OpenAiToolCall>) {
+    let mut thinking_parts = Vec::new();
     let mut text_parts = Vec::new();
     let mut tool_calls = Vec::new();
 
     for block in blocks {
         match block {
             ClaudeContentBlock::Text { text, .. } => text_parts.push(text.clone()),
+            ClaudeContentBlock::Thinking {
+                thinking,
+                signature,
+                ..
+            } if propagate_thinking_blocks && !thinking.trim().is_empty() => {
+                let signature = signature
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|signature| preserve_thinking_signatures && !signature.is_empty());
+                thinking_parts.push(replayed_thinking(thinking.trim(), signature));
+            }
             ClaudeContentBlock::ToolUse {
                 id, name, input, ..
             } => {
@@ -50,7 +101,17 @@ fn extract_assistant_parts(blocks: &[ClaudeContentBlock]) -> (Vec<String>, Vec<O
         }
     }
 
-    (text_parts, tool_calls)
+    thinking_parts.append(&mut text_parts);
+    (thinking_parts, tool_calls)
+}
+
+fn replayed_thinking(thinking: &str, signature: Option<&str>) -> String {
+    match signature {
+        Some(signature) => {
+            format!("<thinking signature=\"{signature}\">\n{thinking}\n</thinking>\n")
+        }
+        None => format!("<thinking>\n{thinking}\n</thinking>\n"),
+    }
 }
 
 fn build_tool_call(
@@ -106,3 +167,45 @@ fn build_tool_call(
         arguments,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::seeded_thinking;
+    use crate::models::ClaudeMessage;
+
+    fn messages(value: serde_json::Value) -> Vec<ClaudeMessage> {
+        serde_json::from_value(value).expect("valid messages")
+    }
+
+    #[test]
+    fn seeds_only_from_unsigned_thinking_in_a_trailing_prefill() {
+        let prefill = messages(json!([
+            {"role": "user", "content": "plan it"},
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "First, list the files.", "signature": ""}
+            ]}
+        ]));
+        assert_eq!(
+            seeded_thinking(&prefill).as_deref(),
+            Some("First, list the files.")
+        );
+
+        let signed = messages(json!([
+            {"role": "user", "content": "plan it"},
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "Done before.", "signature": "sig"}
+            ]}
+        ]));
+        assert_eq!(seeded_thinking(&signed), None);
+
+        let answered = messages(json!([
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "Earlier turn."}
+            ]},
+            {"role": "user", "content": "next"}
+        ]));
+        assert_eq!(seeded_thinking(&answered), None);
+    }
+}
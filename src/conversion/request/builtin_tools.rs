@@ -0,0 +1,148 @@
+use serde_json::{Value, json};
+use tracing::warn;
+
+use crate::models::ClaudeToolDefinition;
+
+/// Anthropic-defined tools carry a versioned `type` (`computer_20250124`,
+/// `text_editor_20250429`, ...) instead of an `input_schema`; client tools
+/// omit `type` or send `custom`.
+pub fn is_builtin_tool(tool: &ClaudeToolDefinition) -> bool {
+    tool.tool_type
+        .as_deref()
+        .map(str::trim)
+        .is_some_and(|tool_type| !tool_type.is_empty() && tool_type != "custom")
+}
+
+/// Description and parameters for a built-in tool sent as an OpenAI
+/// function. Types without a known schema keep the request's own fields.
+pub fn builtin_tool_function(tool: &ClaudeToolDefinition) -> Option<(String, Value)> {
+    let tool_type = tool.tool_type.as_deref().unwrap_or_default();
+    if tool_type.starts_with("computer_") {
+        return Some((computer_description(tool), computer_parameters()));
+    }
+    if tool_type.starts_with("text_editor_") {
+        return Some((
+            "View, create and edit files. `str_replace` replaces exactly one occurrence of `old_str`.".to_string(),
+            text_editor_parameters(),
+        ));
+    }
+    if tool_type.starts_with("bash_") {
+        return Some((
+            "Run a command in a persistent bash shell; set `restart` to start a fresh one."
+                .to_string(),
+            bash_parameters(),
+        ));
+    }
+    None
+}
+
+pub fn log_builtin_tool(tool: &ClaudeToolDefinition, dropped: bool) {
+    warn!(
+        phase = "builtin_tool",
+        tool_type = tool.tool_type.as_deref().unwrap_or_default(),
+        tool_name = tool.name.as_deref().unwrap_or_default(),
+        dropped,
+        "Request declares an Anthropic built-in tool"
+    );
+}
+
+fn computer_description(tool: &ClaudeToolDefinition) -> String {
+    let dimension = |key: &str| tool.extra.get(key).and_then(Value::as_u64);
+    let mut description =
+        "Control the computer's mouse and keyboard and take screenshots.".to_string();
+    if let (Some(width), Some(height)) = (
+        dimension("display_width_px"),
+        dimension("display_height_px"),
+    ) {
+        description.push_str(&format!(" The display is {width}x{height} pixels."));
+    }
+    description
+}
+
+fn computer_parameters() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "action": {
+                "type": "string",
+                "enum": [
+                    "key", "type", "mouse_move", "left_click", "left_click_drag",
+                    "right_click", "middle_click", "double_click", "triple_click",
+                    "scroll", "wait", "screenshot", "cursor_position"
+                ]
+            },
+            "coordinate": {
+                "type": "array",
+                "items": {"type": "integer"},
+                "description": "[x, y] in pixels"
+            },
+            "text": {"type": "string"},
+            "scroll_direction": {"type": "string", "enum": ["up", "down", "left", "right"]},
+            "scroll_amount": {"type": "integer"},
+            "duration": {"type": "number"}
+        },
+        "required": ["action"]
+    })
+}
+
+fn text_editor_parameters() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "command": {
+                "type": "string",
+                "enum": ["view", "create", "str_replace", "insert", "undo_edit"]
+            },
+            "path": {"type": "string"},
+            "file_text": {"type": "string"},
+            "old_str": {"type": "string"},
+            "new_str": {"type": "string"},
+            "insert_line": {"type": "integer"},
+            "view_range": {"type": "array", "items": {"type": "integer"}}
+        },
+        "required": ["command", "path"]
+    })
+}
+
+fn bash_parameters() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "command": {"type": "string"},
+            "restart": {"type": "boolean"}
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builtin_tool_function, is_builtin_tool};
+    use crate::models::ClaudeToolDefinition;
+
+    fn tool(value: serde_json::Value) -> ClaudeToolDefinition {
+        serde_json::from_value(value).expect("valid tool")
+    }
+
+    #[test]
+    fn recognizes_builtin_types_and_synthesizes_computer_schema() {
+        let computer = tool(serde_json::json!({
+            "type": "computer_20250124",
+            "name": "computer",
+            "display_width_px": 1024,
+            "display_height_px": 768
+        }));
+        assert!(is_builtin_tool(&computer));
+        assert!(!is_builtin_tool(&tool(
+            serde_json::json!({"type": "custom", "name": "Bash"})
+        )));
+        assert!(!is_builtin_tool(&tool(serde_json::json!({"name": "Bash"}))));
+
+        let (description, parameters) = builtin_tool_function(&computer).expect("computer schema");
+        assert!(description.contains("1024x768"));
+        assert_eq!(parameters["required"], serde_json::json!(["action"]));
+        assert!(
+            builtin_tool_function(&tool(serde_json::json!({"type": "web_search_20250305"})))
+                .is_none()
+        );
+    }
+}
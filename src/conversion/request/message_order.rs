@@ -0,0 +1,145 @@
+use tracing::warn;
+
+use super::models::{
+    OpenAiAssistantMessage, OpenAiMessage, OpenAiUserContent, OpenAiUserContentPart,
+    OpenAiUserMessage,
+};
+
+const MERGED_TEXT_SEPARATOR: &str = "\n\n";
+
+/// Collapses runs of same-role user or assistant messages into one, since
+/// many OpenAI-compatible servers reject histories that do not alternate.
+/// Assistant text is joined and tool calls are kept in order.
+pub fn merge_consecutive_roles(messages: &mut Vec<OpenAiMessage>) {
+    let mut merged: Vec<OpenAiMessage> = Vec::with_capacity(messages.len());
+    for message in messages.drain(..) {
+        match (merged.last_mut(), message) {
+            (Some(OpenAiMessage::Assistant(previous)), OpenAiMessage::Assistant(next)) => {
+                merge_assistant(previous, next);
+            }
+            (Some(OpenAiMessage::User(previous)), OpenAiMessage::User(next)) => {
+                warn!(
+                    phase = "normalize_message_order",
+                    "Merging consecutive user messages"
+                );
+                merge_user(previous, next);
+            }
+            (_, message) => merged.push(message),
+        }
+    }
+    *messages = merged;
+}
+
+fn merge_assistant(previous: &mut OpenAiAssistantMessage, next: OpenAiAssistantMessage) {
+    previous.content = match (previous.content.take(), next.content) {
+        (Some(first), Some(second)) => Some(format!("{first}{MERGED_TEXT_SEPARATOR}{second}")),
+        (first, second) => first.or(second),
+    };
+    if let Some(next_calls) = next.tool_calls {
+        previous
+            .tool_calls
+            .get_or_insert_with(Vec::new)
+            .extend(next_calls);
+    }
+}
+
+fn merge_user(previous: &mut OpenAiUserMessage, next: OpenAiUserMessage) {
+    let first = std::mem::replace(
+        &mut previous.content,
+        OpenAiUserContent::Text(String::new()),
+    );
+    previous.content = match (first, next.content) {
+        (OpenAiUserContent::Text(first), OpenAiUserContent::Text(second)) => {
+            OpenAiUserContent::Text(format!("{first}{MERGED_TEXT_SEPARATOR}{second}"))
+        }
+        (first, second) => {
+            let mut parts = into_parts(first);
+            parts.extend(into_parts(second));
+            OpenAiUserContent::Parts(parts)
+        }
+    };
+}
+
+fn into_parts(content: OpenAiUserContent) -> Vec<OpenAiUserContentPart> {
+    match content {
+        OpenAiUserContent::Text(text) => vec![OpenAiUserContentPart::Text { text }],
+        OpenAiUserContent::Parts(parts) => parts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::conversion::request::convert_claude_to_openai;
+    use crate::models::ClaudeMessagesRequest;
+
+    fn request(messages: serde_json::Value) -> ClaudeMessagesRequest {
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": messages
+        }))
+        .expect("valid request")
+    }
+
+    #[test]
+    fn merges_consecutive_assistant_turns_and_keeps_tool_calls() {
+        let request = request(json!([
+            {"role": "user", "content": "run it"},
+            {"role": "assistant", "content": "planner says hi"},
+            {"role": "assistant", "content": [
+                {"type": "text", "text": "executor runs"},
+                {"type": "tool_use", "id": "call_1", "name": "Bash", "input": {"cmd": "ls"}}
+            ]},
+            {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "call_1", "content": "a.txt"}
+            ]}
+        ]));
+        let mut config = crate::upstream::test_support::test_config();
+        config.normalize_message_order = true;
+
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config).messages)
+            .expect("serialize messages");
+        let roles: Vec<&str> = payload
+            .as_array()
+            .expect("messages array")
+            .iter()
+            .filter_map(|message| message["role"].as_str())
+            .collect();
+        assert_eq!(roles, ["user", "assistant", "tool"]);
+        assert_eq!(
+            payload[1]["content"],
+            json!("planner says hi\n\nexecutor runs")
+        );
+        assert_eq!(payload[1]["tool_calls"][0]["id"], json!("call_1"));
+        assert_eq!(payload[2]["tool_call_id"], json!("call_1"));
+    }
+
+    #[test]
+    fn merges_consecutive_user_turns_only_when_enabled() {
+        let request = request(json!([
+            {"role": "user", "content": "first"},
+            {"role": "user", "content": [
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/a.png"}}
+            ]}
+        ]));
+        let mut config = crate::upstream::test_support::test_config();
+        assert_eq!(
+            convert_claude_to_openai(&request, &config).messages.len(),
+            2
+        );
+
+        config.normalize_message_order = true;
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config).messages)
+            .expect("serialize messages");
+        assert_eq!(payload.as_array().map(Vec::len), Some(1));
+        assert_eq!(
+            payload[0]["content"],
+            json!([
+                {"type": "text", "text": "first"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/a.png"}}
+            ])
+        );
+    }
+}
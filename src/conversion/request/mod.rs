@@ -1,38 +1,60 @@
 mod assistant;
+mod builtin_tools;
+mod message_order;
 mod models;
+mod previous_response;
 mod responses_convert;
 mod responses_models;
+mod routing;
 mod system;
+mod tool_call_ids;
 mod tool_result;
+mod tool_schema;
 mod tools;
 mod user;
 
-pub use models::{OpenAiChatRequest, OpenAiMessage, OpenAiUserMessage};
+pub use assistant::seeded_thinking;
+pub use models::{
+    OpenAiChatRequest, OpenAiMessage, OpenAiUserMessage, map_claude_model_to_openai, model_tier,
+};
+pub use previous_response::{ResponsesAnchor, ResponsesInputPrefix, apply_previous_response};
 pub use responses_convert::convert_claude_to_responses;
 pub use responses_models::OpenAiResponsesRequest;
+pub use routing::route_claude_request;
+pub use tool_schema::check_tool_schemas;
 pub use tools::is_thinking_requested;
 
-use std::collections::HashSet;
-
 use tracing::{debug, trace, warn};
 
 use crate::config::Config;
-use crate::constants::{ROLE_ASSISTANT, ROLE_USER};
+use crate::constants::{ROLE_ASSISTANT, ROLE_FUNCTION, ROLE_USER};
 use crate::models::{ClaudeMessage, ClaudeMessagesRequest};
 use assistant::convert_claude_assistant_message;
-use models::{OpenAiSystemMessage, map_claude_model_to_openai};
+use message_order::merge_consecutive_roles;
+use models::OpenAiSystemMessage;
 use system::extract_system_text;
+use tool_call_ids::PendingToolCalls;
 use tool_result::{
-    convert_claude_tool_results, has_non_tool_result_content, is_tool_result_user_message,
+    convert_claude_tool_results, convert_legacy_function_message, has_non_tool_result_content,
+    is_tool_result_user_message,
 };
 use tools::{add_optional_request_fields, add_tool_choice, add_tools, derive_reasoning_effort};
 use user::convert_claude_user_message;
 
+/// Value for the upstream `user` field: the logical session id, so upstream
+/// abuse tracking groups requests per session. Opt-in because it exposes a
+/// stable per-client identifier to the upstream provider.
+pub fn session_user(config: &Config, session_id: &str) -> Option<String> {
+    config
+        .propagate_session_id_as_user
+        .then(|| session_id.to_string())
+}
+
 pub fn convert_claude_to_openai(
     request: &ClaudeMessagesRequest,
     config: &Config,
 ) -> OpenAiChatRequest {
-    let mapped_model = map_claude_model_to_openai(&request.model, config);
+    let mapped_model = route_claude_request(request, config);
     let thinking_type = request
         .thinking
         .as_ref()
@@ -47,6 +69,7 @@ pub fn convert_claude_to_openai(
         request.max_tokens,
         &mapped_model,
         config.min_thinking_level.as_deref(),
+        &config.reasoning_models,
     );
 
     debug!(
@@ -60,20 +83,20 @@ pub fn convert_claude_to_openai(
     );
     let mut openai_messages: Vec<OpenAiMessage> = Vec::new();
 
-    push_system_message(request, &mut openai_messages);
-    convert_message_list(
-        &request.messages,
+    push_system_message(
+        request,
+        config.system_prompt_prefix_for(&mapped_model),
+        config,
         &mut openai_messages,
-        config.debug_tool_id_matching,
     );
+    convert_message_list(&request.messages, &mut openai_messages, config);
+    if config.normalize_message_order {
+        merge_consecutive_roles(&mut openai_messages);
+    }
 
-    let mut openai_request = build_request_base(request, mapped_model, openai_messages);
-    add_optional_request_fields(
-        request,
-        &mut openai_request,
-        config.min_thinking_level.as_deref(),
-    );
-    add_tools(request, &mut openai_request);
+    let mut openai_request = build_request_base(request, mapped_model, openai_messages, config);
+    add_optional_request_fields(request, &mut openai_request, config);
+    add_tools(request, &mut openai_request, config);
     add_tool_choice(request, &mut openai_request);
 
     trace!(
@@ -94,7 +117,7 @@ pub fn convert_claude_to_openai(
         upstream_model = %openai_request.model,
         stream = openai_request.stream,
         max_tokens = openai_request.max_tokens,
-        temperature = openai_request.temperature,
+        temperature = ?openai_request.temperature,
         messages_len,
         tools_len,
         has_tool_choice = openai_request.tool_choice.is_some(),
@@ -104,159 +127,146 @@ pub fn convert_claude_to_openai(
     openai_request
 }
 
-fn push_system_message(request: &ClaudeMessagesRequest, openai_messages: &mut Vec<OpenAiMessage>) {
-    let Some(system) = &request.system else {
-        return;
-    };
-    let system_text = extract_system_text(system);
-    if system_text.trim().is_empty() {
+/// A configured per-model prefix is joined ahead of the request's own system
+/// text, and still produces a system message when the request has none.
+fn push_system_message(
+    request: &ClaudeMessagesRequest,
+    prefix: Option<&str>,
+    config: &Config,
+    openai_messages: &mut Vec<OpenAiMessage>,
+) {
+    let separator = config.system_block_separator.as_str();
+    let system_text = request
+        .system
+        .as_ref()
+        .map(|system| extract_system_text(system, separator, &config.cache_boundary_separator))
+        .unwrap_or_default();
+    let parts: Vec<&str> = [prefix.unwrap_or_default(), system_text.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.is_empty() {
         return;
     }
     openai_messages.push(OpenAiMessage::System(OpenAiSystemMessage::from_text(
-        system_text.trim().to_string(),
+        parts.join(separator),
     )));
 }
 
 fn convert_message_list(
     messages: &[ClaudeMessage],
     openai_messages: &mut Vec<OpenAiMessage>,
-    debug_tool_id_matching: bool,
+    config: &Config,
 ) {
-    let mut seen_tool_call_ids = HashSet::new();
+    let mut pending_tool_calls = PendingToolCalls::default();
 
     for message in messages {
-        if message.role == ROLE_USER {
-            if is_tool_result_user_message(message) {
-                for tool_message in convert_claude_tool_results(message) {
-                    let Some(tool_call_id) = tool_message.tool_call_id() else {
-                        warn!(
-                            phase = "drop_tool_result",
-                            reason = "missing_tool_call_id_in_converted_message",
-                            "Dropping converted tool message"
-                        );
-                        continue;
-                    };
-
-                    let normalized_tool_call_id = tool_call_id.trim();
-                    if !seen_tool_call_ids.contains(normalized_tool_call_id) {
-                        if debug_tool_id_matching {
-                            let mut known_tool_call_ids: Vec<&str> =
-                                seen_tool_call_ids.iter().map(String::as_str).collect();
-                            known_tool_call_ids.sort_unstable();
-
-                            warn!(
-                                phase = "drop_tool_result",
-                                reason = "unknown_tool_call_id",
-                                tool_call_id = normalized_tool_call_id,
-                                known_ids_count = known_tool_call_ids.len(),
-                                ?known_tool_call_ids,
-                                "Dropping tool message with unknown tool_call_id"
-                            );
-                        } else {
-                            warn!(
-                                phase = "drop_tool_result",
-                                reason = "unknown_tool_call_id",
-                                tool_call_id = normalized_tool_call_id,
-                                known_ids_count = seen_tool_call_ids.len(),
-                                "Dropping tool message with unknown tool_call_id"
-                            );
-                        }
-                        continue;
-                    }
-
-                    openai_messages.push(tool_message);
-                }
+        match message.role.as_str() {
+            ROLE_USER => {
+                push_user_message(message, &mut pending_tool_calls, config, openai_messages);
             }
-
-            if has_non_tool_result_content(message) {
-                openai_messages.push(convert_claude_user_message(message));
+            ROLE_ASSISTANT => {
+                let assistant_message = convert_claude_assistant_message(
+                    message,
+                    config.propagate_thinking_blocks,
+                    config.preserve_thinking_signatures,
+                );
+
+                pending_tool_calls.open_calls(&assistant_message);
+                openai_messages.push(assistant_message);
             }
-            continue;
+            ROLE_FUNCTION if config.accept_legacy_function_role => {
+                openai_messages.extend(convert_legacy_function_message(message));
+            }
+            ROLE_FUNCTION => warn!(
+                phase = "drop_message",
+                reason = "legacy_function_role_disabled",
+                "Dropping function role message; enable accept_legacy_function_role to convert it"
+            ),
+            _ => {}
         }
+    }
+}
 
-        if message.role == ROLE_ASSISTANT {
-            let assistant_message = convert_claude_assistant_message(message);
-
-            if let Some(tool_calls) = assistant_message.assistant_tool_calls() {
-                for tool_call in tool_calls {
-                    let normalized_tool_call_id = tool_call.id.trim();
-                    if !normalized_tool_call_id.is_empty() {
-                        seen_tool_call_ids.insert(normalized_tool_call_id.to_string());
-                    }
-                }
+fn push_user_message(
+    message: &ClaudeMessage,
+    pending_tool_calls: &mut PendingToolCalls,
+    config: &Config,
+    openai_messages: &mut Vec<OpenAiMessage>,
+) {
+    if is_tool_result_user_message(message) {
+        for tool_message in convert_claude_tool_results(message) {
+            let Some(tool_call_id) = tool_message.tool_call_id() else {
+                warn!(
+                    phase = "drop_tool_result",
+                    reason = "missing_tool_call_id_in_converted_message",
+                    "Dropping converted tool message"
+                );
+                continue;
+            };
+            if !pending_tool_calls.answer(tool_call_id, config.debug_tool_id_matching) {
+                continue;
             }
 
-            openai_messages.push(assistant_message);
+            openai_messages.push(tool_message);
         }
     }
+
+    if has_non_tool_result_content(message) {
+        openai_messages.push(convert_claude_user_message(
+            message,
+            config.document_passthrough,
+            config.video_to_text_placeholder,
+        ));
+    }
 }
 
 fn build_request_base(
     request: &ClaudeMessagesRequest,
     mapped_model: String,
     openai_messages: Vec<OpenAiMessage>,
+    config: &Config,
 ) -> OpenAiChatRequest {
     OpenAiChatRequest {
+        temperature: config.temperature_for(&mapped_model, request.temperature),
         model: mapped_model,
         messages: openai_messages,
         max_tokens: request.max_tokens,
-        temperature: request.temperature.unwrap_or(1.0),
         reasoning_effort: None,
         stream: request.stream.unwrap_or(false),
         stream_options: None,
         stop: None,
         top_p: None,
+        top_k: None,
+        min_output_tokens: None,
         tools: None,
         tool_choice: None,
+        parallel_tool_calls: None,
+        seed: request.seed,
+        frequency_penalty: None,
+        presence_penalty: None,
+        response_format: None,
+        user: None,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, WireApi};
-    use crate::models::{ClaudeContent, ClaudeContentBlock};
+    use crate::models::{ClaudeContent, ClaudeContentBlock, ClaudeSystemContent};
+    use crate::upstream::test_support::test_config;
     use serde_json::json;
-
-    fn test_config() -> Config {
-        Config {
-            openai_api_key: "sk-test".to_string(),
-            anthropic_api_key: None,
-            openai_base_url: "https://api.openai.com/v1".to_string(),
-            azure_api_version: None,
-            host: "127.0.0.1".to_string(),
-            port: 8082,
-            log_level: "INFO".to_string(),
-            request_timeout: 90,
-            stream_request_timeout: None,
-            request_body_max_size: 16 * 1024 * 1024,
-            session_ttl_min_secs: 1800,
-            session_ttl_max_secs: 86400,
-            session_cleanup_interval_secs: 60,
-            debug_tool_id_matching: false,
-            wire_api: WireApi::Chat,
-            big_model: "gpt-4o".to_string(),
-            middle_model: "gpt-4o".to_string(),
-            small_model: "gpt-4o-mini".to_string(),
-            min_thinking_level: None,
-            custom_headers: Default::default(),
-        }
-    }
+    use std::collections::HashMap;
 
     fn make_request(messages: Vec<ClaudeMessage>) -> ClaudeMessagesRequest {
-        ClaudeMessagesRequest {
-            model: "claude-3-5-sonnet-20241022".to_string(),
-            max_tokens: 256,
-            messages,
-            thinking: None,
-            system: None,
-            stop_sequences: None,
-            stream: Some(false),
-            temperature: Some(1.0),
-            top_p: None,
-            tools: None,
-            tool_choice: None,
-        }
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": messages,
+            "stream": false,
+            "temperature": 1.0
+        }))
+        .expect("valid request")
     }
 
     #[test]
@@ -270,6 +280,7 @@ mod tests {
                     input: Some(json!({"command": "cargo fmt"})),
                     extra: Default::default(),
                 }])),
+                name: None,
             },
             ClaudeMessage {
                 role: ROLE_USER.to_string(),
@@ -277,6 +288,7 @@ mod tests {
                     ClaudeContentBlock::ToolResult {
                         tool_use_id: Some("call_test123".to_string()),
                         content: Some(json!("ok")),
+                        is_error: None,
                         extra: Default::default(),
                     },
                     ClaudeContentBlock::Text {
@@ -284,6 +296,7 @@ mod tests {
                         extra: Default::default(),
                     },
                 ])),
+                name: None,
             },
         ]);
 
@@ -297,6 +310,373 @@ mod tests {
         assert_eq!(messages[2].role(), "user");
     }
 
+    #[test]
+    fn replays_thinking_blocks_only_when_propagation_enabled() {
+        let message: ClaudeMessage = serde_json::from_value(json!({
+            "role": "assistant",
+            "content": [
+                {"type": "text", "text": "answer"},
+                {"type": "thinking", "thinking": "plan", "signature": "sig", "index": 0}
+            ]
+        }))
+        .expect("valid message");
+        let Some(ClaudeContent::Blocks(blocks)) = message.content.as_ref() else {
+            panic!("expected content blocks");
+        };
+        assert!(matches!(
+            &blocks[1],
+            ClaudeContentBlock::Thinking { signature: Some(sig), extra, .. }
+                if sig == "sig" && extra.get("index") == Some(&json!(0))
+        ));
+        let request = make_request(vec![message]);
+
+        let dropped = convert_claude_to_openai(&request, &test_config());
+        let payload = serde_json::to_value(&dropped.messages[0]).expect("serialize message");
+        assert_eq!(payload["content"], json!("answer"));
+
+        let mut config = test_config();
+        config.propagate_thinking_blocks = true;
+        config.preserve_thinking_signatures = false;
+        let replayed = convert_claude_to_openai(&request, &config);
+        let payload = serde_json::to_value(&replayed.messages[0]).expect("serialize message");
+        assert_eq!(
+            payload["content"],
+            json!("<thinking>\nplan\n</thinking>\nanswer")
+        );
+
+        config.preserve_thinking_signatures = true;
+        let signed = convert_claude_to_openai(&request, &config);
+        let payload = serde_json::to_value(&signed.messages[0]).expect("serialize message");
+        assert_eq!(
+            payload["content"],
+            json!("<thinking signature=\"sig\">\nplan\n</thinking>\nanswer")
+        );
+    }
+
+    #[test]
+    fn omits_temperature_for_models_that_reject_it() {
+        let mut config = test_config();
+        config.model_no_temperature = vec![config.big_model.to_ascii_lowercase()];
+        let mut request = make_request(vec![]);
+        request.model = "claude-3-opus-20240229".to_string();
+
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config))
+            .expect("serialize request");
+        assert!(payload.get("temperature").is_none());
+    }
+
+    #[test]
+    fn passes_seed_through_to_chat_request() {
+        let mut request = make_request(vec![]);
+        request.seed = Some(7);
+
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &test_config()))
+            .expect("serialize request");
+        assert_eq!(payload["seed"], json!(7));
+
+        request.seed = Some(12345);
+        request.thinking =
+            serde_json::from_value(json!({"type": "enabled", "budget_tokens": 8192}))
+                .expect("valid thinking");
+        let mut config = test_config();
+        config.middle_model = "o3-mini".to_string();
+        let converted = convert_claude_to_openai(&request, &config);
+        assert!(converted.reasoning_effort.is_some());
+        let serialized = serde_json::to_string(&converted).expect("serialize request");
+        assert!(serialized.contains(r#""seed":12345"#), "{serialized}");
+    }
+
+    #[test]
+    fn request_penalties_override_configured_defaults() {
+        let mut config = test_config();
+        config.default_frequency_penalty = Some(0.5);
+        config.default_presence_penalty = Some(0.25);
+        let mut request = make_request(vec![]);
+        request.presence_penalty = Some(1.0);
+
+        let converted = convert_claude_to_openai(&request, &config);
+        assert_eq!(converted.frequency_penalty, Some(0.5));
+        assert_eq!(converted.presence_penalty, Some(1.0));
+
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &test_config()))
+            .expect("serialize request");
+        assert!(payload.get("frequency_penalty").is_none());
+    }
+
+    #[test]
+    fn request_response_format_overrides_configured_default() {
+        let mut config = test_config();
+        config.default_response_format = Some(json!({"type": "json_object"}));
+        let mut request = make_request(vec![]);
+
+        let converted = convert_claude_to_openai(&request, &config);
+        assert_eq!(
+            converted.response_format,
+            Some(json!({"type": "json_object"}))
+        );
+
+        request.response_format = Some(json!({"type": "text"}));
+        let converted = convert_claude_to_openai(&request, &config);
+        assert_eq!(converted.response_format, Some(json!({"type": "text"})));
+    }
+
+    #[test]
+    fn serializes_json_object_and_json_schema_response_formats() {
+        let schema_format = json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": "weather",
+                "strict": true,
+                "schema": {
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}, "celsius": {"type": "number"}},
+                    "required": ["city", "celsius"]
+                }
+            }
+        });
+        for response_format in [json!({"type": "json_object"}), schema_format] {
+            let request: ClaudeMessagesRequest = serde_json::from_value(json!({
+                "model": "claude-3-5-sonnet-20241022",
+                "max_tokens": 64,
+                "messages": [{"role": "user", "content": "weather?"}],
+                "response_format": response_format
+            }))
+            .expect("valid request");
+
+            let payload = serde_json::to_value(convert_claude_to_openai(&request, &test_config()))
+                .expect("serialize request");
+            assert_eq!(payload["response_format"], response_format);
+        }
+    }
+
+    #[test]
+    fn sets_session_user_only_when_enabled() {
+        let mut config = test_config();
+        assert_eq!(session_user(&config, "sess-1"), None);
+
+        config.propagate_session_id_as_user = true;
+        let mut converted = convert_claude_to_openai(&make_request(vec![]), &config);
+        converted.user = session_user(&config, "sess-1");
+        let payload = serde_json::to_value(&converted).expect("serialize request");
+        assert_eq!(payload["user"], json!("sess-1"));
+    }
+
+    #[test]
+    fn passes_top_k_through_only_when_set() {
+        let mut request = make_request(vec![]);
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &test_config()))
+            .expect("serialize request");
+        assert!(payload.get("top_k").is_none());
+
+        request.top_k = Some(40);
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &test_config()))
+            .expect("serialize request");
+        assert_eq!(payload["top_k"], json!(40));
+    }
+
+    #[test]
+    fn sends_min_tokens_for_thinking_requests_when_enforced() {
+        let mut request = make_request(vec![]);
+        request.max_tokens = 4096;
+        request.thinking = Some(
+            serde_json::from_value(json!({"type": "enabled", "budget_tokens": 2000}))
+                .expect("valid thinking"),
+        );
+        let mut config = test_config();
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config))
+            .expect("serialize request");
+        assert!(payload.get("min_tokens").is_none());
+
+        config.enforce_min_tokens_for_thinking = true;
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config))
+            .expect("serialize request");
+        assert_eq!(payload["min_tokens"], json!(1000));
+
+        config.min_tokens_thinking_fraction = 1.0;
+        request.max_tokens = 1500;
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config))
+            .expect("serialize request");
+        assert_eq!(payload["min_tokens"], json!(1500));
+    }
+
+    #[test]
+    fn converts_or_drops_builtin_tools() {
+        let mut request = make_request(vec![]);
+        request.tools = Some(vec![
+            serde_json::from_value(json!({"type": "bash_20250124", "name": "bash"}))
+                .expect("builtin tool"),
+            serde_json::from_value(json!({"name": "Read", "input_schema": {"type": "object"}}))
+                .expect("client tool"),
+        ]);
+
+        let mut config = test_config();
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config))
+            .expect("serialize request");
+        assert_eq!(payload["tools"][0]["function"]["name"], json!("bash"));
+        assert_eq!(
+            payload["tools"][0]["function"]["parameters"]["properties"]["command"],
+            json!({"type": "string"})
+        );
+
+        config.drop_unsupported_tools = true;
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config))
+            .expect("serialize request");
+        assert_eq!(payload["tools"].as_array().map(Vec::len), Some(1));
+        assert_eq!(payload["tools"][0]["function"]["name"], json!("Read"));
+    }
+
+    #[test]
+    fn prepends_system_prompt_prefix_for_mapped_model() {
+        let mut config = test_config();
+        config.system_prompt_prefix =
+            HashMap::from([("gpt-4o".to_string(), "Be safe.".to_string())]);
+        let mut request = make_request(vec![]);
+
+        let converted = convert_claude_to_openai(&request, &config);
+        let payload = serde_json::to_value(&converted.messages).expect("serialize messages");
+        assert_eq!(payload[0], json!({"role": "system", "content": "Be safe."}));
+
+        request.system = Some(ClaudeSystemContent::Text(" be brief ".to_string()));
+        let converted = convert_claude_to_openai(&request, &config);
+        let payload = serde_json::to_value(&converted.messages).expect("serialize messages");
+        assert_eq!(payload[0]["content"], json!("Be safe.\n\nbe brief"));
+    }
+
+    #[test]
+    fn joins_system_blocks_and_prefix_with_configured_separator() {
+        let mut config = test_config();
+        config.system_block_separator = "\n---\n".to_string();
+        config.system_prompt_prefix =
+            HashMap::from([("gpt-4o".to_string(), "Be safe.".to_string())]);
+        let mut request = make_request(vec![]);
+        request.system = Some(
+            serde_json::from_value(json!([
+                {"type": "text", "text": "Format strictly."},
+                {"type": "text", "text": "Context follows."}
+            ]))
+            .expect("valid system blocks"),
+        );
+
+        let converted = convert_claude_to_openai(&request, &config);
+        let payload = serde_json::to_value(&converted.messages).expect("serialize messages");
+        assert_eq!(
+            payload[0]["content"],
+            json!("Be safe.\n---\nFormat strictly.\n---\nContext follows.")
+        );
+    }
+
+    #[test]
+    fn marks_cache_control_boundaries_between_system_blocks() {
+        let mut request = make_request(vec![]);
+        request.system = Some(
+            serde_json::from_value(json!([
+                {"type": "text", "text": "Tools manual.", "cache_control": {"type": "ephemeral"}},
+                {"type": "text", "text": "Project rules."},
+                {"type": "text", "text": "Today is Monday."}
+            ]))
+            .expect("valid system blocks"),
+        );
+
+        let converted = convert_claude_to_openai(&request, &test_config());
+        let payload = serde_json::to_value(&converted.messages).expect("serialize messages");
+        assert_eq!(
+            payload[0]["content"],
+            json!("Tools manual.\n\n---\n\nProject rules.\n\nToday is Monday.")
+        );
+    }
+
+    #[test]
+    fn converts_url_image_source_to_image_url_part() {
+        let message: ClaudeMessage = serde_json::from_value(json!({
+            "role": "user",
+            "content": [
+                {"type": "text", "text": "describe"},
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/cat.png"}}
+            ]
+        }))
+        .expect("valid message");
+
+        let converted = convert_claude_to_openai(&make_request(vec![message]), &test_config());
+        let payload = serde_json::to_value(&converted.messages[0]).expect("serialize message");
+
+        assert_eq!(
+            payload["content"][1],
+            json!({"type": "image_url", "image_url": {"url": "https://example.com/cat.png"}})
+        );
+    }
+
+    #[test]
+    fn converts_base64_document_to_file_part_or_inline_text() {
+        let message: ClaudeMessage = serde_json::from_value(json!({
+            "role": "user",
+            "content": [
+                {"type": "text", "text": "summarize"},
+                {
+                    "type": "document",
+                    "title": "report.pdf",
+                    "source": {"type": "base64", "media_type": "application/pdf", "data": "JVBERi0x"}
+                }
+            ]
+        }))
+        .expect("valid message");
+        let request = make_request(vec![message]);
+
+        let mut config = test_config();
+        config.document_passthrough = true;
+        let converted = convert_claude_to_openai(&request, &config);
+        let payload = serde_json::to_value(&converted.messages[0]).expect("serialize message");
+        assert_eq!(
+            payload["content"][1],
+            json!({
+                "type": "file",
+                "file": {"filename": "report.pdf", "file_data": "data:application/pdf;base64,JVBERi0x"}
+            })
+        );
+
+        let converted = convert_claude_to_openai(&request, &test_config());
+        let payload = serde_json::to_value(&converted.messages[0]).expect("serialize message");
+        assert_eq!(payload["content"][1]["type"], json!("text"));
+        assert_eq!(
+            payload["content"][1]["text"],
+            json!(
+                "[Attached document \"report.pdf\", application/pdf encoded as base64]\nJVBERi0x"
+            )
+        );
+    }
+
+    #[test]
+    fn marks_error_flagged_tool_results() {
+        let request = make_request(vec![
+            ClaudeMessage {
+                role: ROLE_ASSISTANT.to_string(),
+                content: Some(ClaudeContent::Blocks(vec![ClaudeContentBlock::ToolUse {
+                    id: Some("call_err".to_string()),
+                    name: Some("Bash".to_string()),
+                    input: Some(json!({"command": "false"})),
+                    extra: Default::default(),
+                }])),
+                name: None,
+            },
+            ClaudeMessage {
+                role: ROLE_USER.to_string(),
+                content: Some(ClaudeContent::Blocks(vec![
+                    ClaudeContentBlock::ToolResult {
+                        tool_use_id: Some("call_err".to_string()),
+                        content: Some(json!("exit status 1")),
+                        is_error: Some(true),
+                        extra: Default::default(),
+                    },
+                ])),
+                name: None,
+            },
+        ]);
+
+        let converted = convert_claude_to_openai(&request, &test_config());
+        let payload = serde_json::to_value(&converted.messages[1]).expect("serialize message");
+
+        assert_eq!(payload["content"], json!("[ERROR] exit status 1"));
+    }
+
     #[test]
     fn drops_assistant_tool_use_with_empty_id() {
         let request = make_request(vec![ClaudeMessage {
@@ -307,6 +687,7 @@ mod tests {
                 input: Some(json!({"command": "cargo fmt"})),
                 extra: Default::default(),
             }])),
+            name: None,
         }]);
 
         let converted = convert_claude_to_openai(&request, &test_config());
@@ -328,6 +709,7 @@ mod tests {
                     input: Some(json!({"command": "cargo fmt"})),
                     extra: Default::default(),
                 }])),
+                name: None,
             },
             ClaudeMessage {
                 role: ROLE_USER.to_string(),
@@ -335,6 +717,7 @@ mod tests {
                     ClaudeContentBlock::ToolResult {
                         tool_use_id: Some("   ".to_string()),
                         content: Some(json!("ok")),
+                        is_error: None,
                         extra: Default::default(),
                     },
                     ClaudeContentBlock::Text {
@@ -342,6 +725,7 @@ mod tests {
                         extra: Default::default(),
                     },
                 ])),
+                name: None,
             },
         ]);
 
@@ -364,6 +748,7 @@ mod tests {
                     input: Some(json!({"command": "cargo fmt"})),
                     extra: Default::default(),
                 }])),
+                name: None,
             },
             ClaudeMessage {
                 role: ROLE_USER.to_string(),
@@ -371,9 +756,11 @@ mod tests {
                     ClaudeContentBlock::ToolResult {
                         tool_use_id: Some("call_unknown".to_string()),
                         content: Some(json!("ok")),
+                        is_error: None,
                         extra: Default::default(),
                     },
                 ])),
+                name: None,
             },
         ]);
 
@@ -395,6 +782,7 @@ mod tests {
                     input: Some(json!({"command": "cargo fmt"})),
                     extra: Default::default(),
                 }])),
+                name: None,
             },
             ClaudeMessage {
                 role: ROLE_USER.to_string(),
@@ -402,6 +790,7 @@ mod tests {
                     ClaudeContentBlock::ToolResult {
                         tool_use_id: Some("call_unknown".to_string()),
                         content: Some(json!("ok")),
+                        is_error: None,
                         extra: Default::default(),
                     },
                     ClaudeContentBlock::Text {
@@ -409,6 +798,7 @@ mod tests {
                         extra: Default::default(),
                     },
                 ])),
+                name: None,
             },
         ]);
 
@@ -9,7 +9,8 @@ pub struct OpenAiChatRequest {
     pub model: String,
     pub messages: Vec<OpenAiMessage>,
     pub max_tokens: u32,
-    pub temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reasoning_effort: Option<String>,
     pub stream: bool,
@@ -19,10 +20,28 @@ pub struct OpenAiChatRequest {
     pub stop: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f64>,
+    /// Not in the OpenAI spec; honoured by llama.cpp, Ollama and vLLM.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Not in the OpenAI spec; a minimum-length hint for vLLM and llama.cpp.
+    #[serde(rename = "min_tokens", skip_serializing_if = "Option::is_none")]
+    pub min_output_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<OpenAiToolDefinition>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<OpenAiToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
 }
 
 impl OpenAiChatRequest {
@@ -125,11 +144,30 @@ pub enum OpenAiUserContentPart {
     Text { text: String },
     #[serde(rename = "image_url")]
     ImageUrl { image_url: OpenAiImageUrl },
+    #[serde(rename = "file")]
+    File { file: OpenAiFile },
+    #[serde(rename = "video_url")]
+    VideoUrl { video_url: OpenAiVideoUrl },
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct OpenAiImageUrl {
     pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenAiVideoUrl {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenAiFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// A `data:<media_type>;base64,<data>` URL.
+    pub file_data: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -200,6 +238,10 @@ pub struct OpenAiToolDefinition {
     #[serde(rename = "type")]
     pub kind: String,
     pub function: OpenAiFunctionDefinition,
+    /// Provider extension set from an `ephemeral` `cache_control` when
+    /// `tool_schema_cache_hints` is on; OpenAI itself has no such field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -213,6 +255,8 @@ pub struct OpenAiFunctionDefinition {
 #[serde(untagged)]
 pub enum OpenAiToolChoice {
     Auto(String),
+    None(String),
+    Required(String),
     Tool(OpenAiNamedToolChoice),
 }
 
@@ -221,6 +265,14 @@ impl OpenAiToolChoice {
         Self::Auto("auto".to_string())
     }
 
+    pub fn none() -> Self {
+        Self::None("none".to_string())
+    }
+
+    pub fn required() -> Self {
+        Self::Required("required".to_string())
+    }
+
     pub fn tool(name: String) -> Self {
         Self::Tool(OpenAiNamedToolChoice {
             kind: TOOL_FUNCTION.to_string(),
@@ -242,17 +294,48 @@ pub struct OpenAiNamedToolFunction {
 }
 
 pub fn map_claude_model_to_openai(claude_model: &str, config: &Config) -> String {
+    if let Some(target) = config.model_target_for(claude_model).model {
+        return target.to_string();
+    }
+
     if is_upstream_native_model(claude_model) {
         return claude_model.to_string();
     }
 
+    match model_tier(claude_model) {
+        ModelTier::Small => config.small_model.clone(),
+        ModelTier::Middle => config.middle_model.clone(),
+        ModelTier::Big => config.big_model.clone(),
+    }
+}
+
+/// Which of `big_model` / `middle_model` / `small_model` a Claude model name
+/// falls back to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelTier {
+    Big,
+    Middle,
+    Small,
+}
+
+impl ModelTier {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Big => "big",
+            Self::Middle => "middle",
+            Self::Small => "small",
+        }
+    }
+}
+
+pub fn model_tier(claude_model: &str) -> ModelTier {
     let model_lower = claude_model.to_lowercase();
     if model_lower.contains("haiku") {
-        config.small_model.clone()
+        ModelTier::Small
     } else if model_lower.contains("sonnet") {
-        config.middle_model.clone()
+        ModelTier::Middle
     } else {
-        config.big_model.clone()
+        ModelTier::Big
     }
 }
 
@@ -265,10 +348,16 @@ fn is_upstream_native_model(model: &str) -> bool {
         || lowered.starts_with("deepseek-")
 }
 
-pub fn supports_reasoning_effort(model: &str) -> bool {
+pub fn supports_reasoning_effort(model: &str, reasoning_models: &[String]) -> bool {
     let lowered = model.to_lowercase();
     lowered.starts_with("o1")
         || lowered.starts_with("o3")
         || lowered.starts_with("o4")
         || lowered.starts_with("gpt-5")
+        || lowered.starts_with("deepseek-r")
+        || lowered.starts_with("qwq")
+        || lowered.starts_with("qvq")
+        || reasoning_models
+            .iter()
+            .any(|prefix| lowered.starts_with(prefix.as_str()))
 }
@@ -0,0 +1,155 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use super::responses_models::{OpenAiResponsesRequest, ResponsesInputItem};
+use crate::constants::ROLE_ASSISTANT;
+
+/// The input of a stored upstream response, identified by its length and a
+/// hash so a later request can tell whether it extends that conversation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponsesInputPrefix {
+    input_len: usize,
+    input_hash: u64,
+}
+
+/// What the upstream already holds for a session once a response with
+/// `response_id` has been stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponsesAnchor {
+    pub response_id: String,
+    prefix: ResponsesInputPrefix,
+}
+
+impl ResponsesInputPrefix {
+    /// Taken before `apply_previous_response` trims the input, so the next
+    /// request's full history can be compared against it.
+    pub fn of(request: &OpenAiResponsesRequest) -> Self {
+        Self {
+            input_len: request.input.len(),
+            input_hash: hash_items(&request.input),
+        }
+    }
+
+    pub fn into_anchor(self, response_id: String) -> ResponsesAnchor {
+        ResponsesAnchor {
+            response_id,
+            prefix: self,
+        }
+    }
+}
+
+/// Replaces the full history with the items that follow the anchored input
+/// and the assistant turn that answered it, and points the request at the
+/// stored response. Leaves the request untouched and returns `false` when
+/// the history does not extend the anchored one.
+pub fn apply_previous_response(
+    request: &mut OpenAiResponsesRequest,
+    anchor: &ResponsesAnchor,
+) -> bool {
+    let prefix_len = anchor.prefix.input_len;
+    if request.input.len() <= prefix_len
+        || hash_items(&request.input[..prefix_len]) != anchor.prefix.input_hash
+    {
+        return false;
+    }
+    let answered = request.input[prefix_len..]
+        .iter()
+        .take_while(|item| is_assistant_output(item))
+        .count();
+    let start = prefix_len + answered;
+    if start == prefix_len || start == request.input.len() {
+        return false;
+    }
+
+    request.input.drain(..start);
+    request.previous_response_id = Some(anchor.response_id.clone());
+    true
+}
+
+fn is_assistant_output(item: &ResponsesInputItem) -> bool {
+    match item {
+        ResponsesInputItem::Message(message) => message.role == ROLE_ASSISTANT,
+        ResponsesInputItem::FunctionCall(_) => true,
+        ResponsesInputItem::FunctionCallOutput(_) => false,
+    }
+}
+
+fn hash_items(items: &[ResponsesInputItem]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for item in items {
+        serde_json::to_vec(item)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ResponsesInputPrefix, apply_previous_response};
+    use crate::conversion::request::convert_claude_to_responses;
+    use crate::models::ClaudeMessagesRequest;
+
+    fn request(messages: serde_json::Value) -> ClaudeMessagesRequest {
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "system": "Be brief.",
+            "messages": messages
+        }))
+        .expect("valid request")
+    }
+
+    #[test]
+    fn sends_only_new_items_when_history_extends_the_anchor() {
+        let config = crate::upstream::test_support::test_config();
+        let first = convert_claude_to_responses(
+            &request(json!([{"role": "user", "content": "list files"}])),
+            &config,
+        );
+        let anchor = ResponsesInputPrefix::of(&first).into_anchor("resp_1".to_string());
+
+        let mut next = convert_claude_to_responses(
+            &request(json!([
+                {"role": "user", "content": "list files"},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "call_1", "name": "Bash", "input": {"cmd": "ls"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "call_1", "content": "a.txt"}
+                ]}
+            ])),
+            &config,
+        );
+        assert!(apply_previous_response(&mut next, &anchor));
+        assert_eq!(next.previous_response_id.as_deref(), Some("resp_1"));
+        assert_eq!(next.instructions.as_deref(), Some("Be brief."));
+        let input = serde_json::to_value(&next.input).expect("serialize input");
+        assert_eq!(input.as_array().map(Vec::len), Some(1));
+        assert_eq!(input[0]["type"], json!("function_call_output"));
+    }
+
+    #[test]
+    fn keeps_full_history_when_it_diverges() {
+        let config = crate::upstream::test_support::test_config();
+        let first = convert_claude_to_responses(
+            &request(json!([{"role": "user", "content": "list files"}])),
+            &config,
+        );
+        let anchor = ResponsesInputPrefix::of(&first).into_anchor("resp_1".to_string());
+
+        let mut edited = convert_claude_to_responses(
+            &request(json!([
+                {"role": "user", "content": "list all files"},
+                {"role": "assistant", "content": "a.txt"},
+                {"role": "user", "content": "thanks"}
+            ])),
+            &config,
+        );
+        assert!(!apply_previous_response(&mut edited, &anchor));
+        assert_eq!(edited.previous_response_id, None);
+        assert_eq!(edited.input.len(), 3);
+    }
+}
@@ -6,30 +6,33 @@ use crate::models::ClaudeMessagesRequest;
 
 use super::convert_claude_to_openai;
 use super::models::{
-    OpenAiMessage, OpenAiToolChoice, OpenAiToolDefinition, OpenAiUserContent, OpenAiUserContentPart,
+    OpenAiImageUrl, OpenAiMessage, OpenAiToolChoice, OpenAiToolDefinition, OpenAiUserContent,
+    OpenAiUserContentPart,
 };
 use super::responses_models::{
     OpenAiResponsesRequest, ResponsesFunctionCallItem, ResponsesFunctionCallOutputItem,
-    ResponsesInputItem, ResponsesMessageContent, ResponsesMessageContentPart, ResponsesMessageItem,
-    ResponsesReasoning, ResponsesToolDefinition,
+    ResponsesImageSource, ResponsesInputItem, ResponsesMessageContent, ResponsesMessageContentPart,
+    ResponsesMessageItem, ResponsesReasoning, ResponsesToolDefinition,
 };
+use super::user::parse_data_url;
 
 pub fn convert_claude_to_responses(
     request: &ClaudeMessagesRequest,
     config: &Config,
 ) -> OpenAiResponsesRequest {
     let chat_request = convert_claude_to_openai(request, config);
-    convert_chat_request_to_responses(chat_request)
+    convert_chat_request_to_responses(chat_request, config)
 }
 
 fn convert_chat_request_to_responses(
     chat_request: super::models::OpenAiChatRequest,
+    config: &Config,
 ) -> OpenAiResponsesRequest {
     let mut input = Vec::new();
     let mut instructions = None;
 
     for message in chat_request.messages {
-        convert_message_to_input_item(message, &mut input, &mut instructions);
+        convert_message_to_input_item(message, &mut input, &mut instructions, config);
     }
 
     OpenAiResponsesRequest {
@@ -37,12 +40,22 @@ fn convert_chat_request_to_responses(
         input,
         instructions,
         max_output_tokens: Some(chat_request.max_tokens),
-        temperature: Some(chat_request.temperature),
+        temperature: chat_request.temperature,
         top_p: chat_request.top_p,
+        top_k: chat_request.top_k,
+        min_output_tokens: chat_request.min_output_tokens,
         stop: chat_request.stop,
         reasoning: map_reasoning(chat_request.reasoning_effort),
         tools: map_tools(chat_request.tools),
         tool_choice: map_tool_choice(chat_request.tool_choice),
+        parallel_tool_calls: chat_request.parallel_tool_calls,
+        seed: chat_request.seed,
+        frequency_penalty: chat_request.frequency_penalty,
+        presence_penalty: chat_request.presence_penalty,
+        user: chat_request.user,
+        text: map_response_format(chat_request.response_format),
+        previous_response_id: None,
+        include: (!config.responses_include.is_empty()).then(|| config.responses_include.clone()),
         stream: chat_request.stream,
     }
 }
@@ -51,15 +64,21 @@ fn convert_message_to_input_item(
     message: OpenAiMessage,
     input: &mut Vec<ResponsesInputItem>,
     instructions: &mut Option<String>,
+    config: &Config,
 ) {
     match message {
-        OpenAiMessage::System(system_message) => {
-            append_instruction(instructions, &system_message.content)
-        }
+        OpenAiMessage::System(system_message) => append_instruction(
+            instructions,
+            &system_message.content,
+            &config.system_block_separator,
+        ),
         OpenAiMessage::User(user_message) => {
             input.push(ResponsesInputItem::Message(ResponsesMessageItem {
                 role: ROLE_USER.to_string(),
-                content: map_user_content(user_message.content),
+                content: map_user_content(
+                    user_message.content,
+                    config.responses_base64_image_source,
+                ),
             }));
         }
         OpenAiMessage::Assistant(assistant_message) => {
@@ -78,35 +97,80 @@ fn convert_message_to_input_item(
     }
 }
 
-fn append_instruction(instructions: &mut Option<String>, system_text: &str) {
+fn append_instruction(instructions: &mut Option<String>, system_text: &str, separator: &str) {
     if system_text.trim().is_empty() {
         return;
     }
 
     match instructions {
         Some(existing) => {
-            existing.push_str("\n\n");
+            existing.push_str(separator);
             existing.push_str(system_text);
         }
         None => *instructions = Some(system_text.to_string()),
     }
 }
 
-fn map_user_content(content: OpenAiUserContent) -> ResponsesMessageContent {
+fn map_user_content(
+    content: OpenAiUserContent,
+    base64_image_source: bool,
+) -> ResponsesMessageContent {
     match content {
         OpenAiUserContent::Text(text) => ResponsesMessageContent::Text(text),
         OpenAiUserContent::Parts(parts) => {
-            let mapped_parts = parts.into_iter().map(map_user_content_part).collect();
+            let mapped_parts = parts
+                .into_iter()
+                .map(|part| map_user_content_part(part, base64_image_source))
+                .collect();
             ResponsesMessageContent::Parts(mapped_parts)
         }
     }
 }
 
-fn map_user_content_part(part: OpenAiUserContentPart) -> ResponsesMessageContentPart {
+fn map_user_content_part(
+    part: OpenAiUserContentPart,
+    base64_image_source: bool,
+) -> ResponsesMessageContentPart {
     match part {
-        OpenAiUserContentPart::Text { text } => ResponsesMessageContentPart::InputText { text },
-        OpenAiUserContentPart::ImageUrl { image_url } => ResponsesMessageContentPart::InputImage {
-            image_url: image_url.url,
+        OpenAiUserContentPart::Text { text } => ResponsesMessageContentPart::Text { text },
+        OpenAiUserContentPart::ImageUrl { image_url } => {
+            map_image_url(image_url, base64_image_source)
+        }
+        OpenAiUserContentPart::File { file } => ResponsesMessageContentPart::File {
+            filename: file.filename,
+            file_data: file.file_data,
+        },
+        OpenAiUserContentPart::VideoUrl { video_url } => ResponsesMessageContentPart::Video {
+            video_url: video_url.url,
+        },
+    }
+}
+
+/// Inline images travel as data URLs from the chat conversion; some
+/// Responses-compatible servers only accept them as a base64 `source`.
+fn map_image_url(
+    image_url: OpenAiImageUrl,
+    base64_image_source: bool,
+) -> ResponsesMessageContentPart {
+    let OpenAiImageUrl { url, detail } = image_url;
+    let source = base64_image_source
+        .then(|| parse_data_url(&url))
+        .flatten()
+        .map(|(media_type, data)| ResponsesImageSource {
+            source_type: "base64".to_string(),
+            media_type,
+            data,
+        });
+    match source {
+        Some(source) => ResponsesMessageContentPart::Image {
+            image_url: None,
+            source: Some(source),
+            detail,
+        },
+        None => ResponsesMessageContentPart::Image {
+            image_url: Some(url),
+            source: None,
+            detail,
         },
     }
 }
@@ -151,7 +215,11 @@ fn map_reasoning(reasoning_effort: Option<String>) -> Option<ResponsesReasoning>
 
 fn map_tool_choice(tool_choice: Option<OpenAiToolChoice>) -> Option<Value> {
     match tool_choice {
-        Some(OpenAiToolChoice::Auto(_)) => Some(json!("auto")),
+        Some(
+            OpenAiToolChoice::Auto(mode)
+            | OpenAiToolChoice::None(mode)
+            | OpenAiToolChoice::Required(mode),
+        ) => Some(json!(mode)),
         Some(OpenAiToolChoice::Tool(named)) => Some(json!({
             "type": TOOL_FUNCTION,
             "name": named.function.name
@@ -160,6 +228,20 @@ fn map_tool_choice(tool_choice: Option<OpenAiToolChoice>) -> Option<Value> {
     }
 }
 
+/// Chat nests the schema under `json_schema`; Responses expects its fields
+/// (`name`, `schema`, `strict`) directly on the format object.
+fn map_response_format(response_format: Option<Value>) -> Option<Value> {
+    let mut format = response_format?;
+    if let Some(Value::Object(schema)) = format
+        .as_object_mut()
+        .and_then(|object| object.remove("json_schema"))
+        && let Some(object) = format.as_object_mut()
+    {
+        object.extend(schema);
+    }
+    Some(json!({ "format": format }))
+}
+
 fn map_tools(tools: Option<Vec<OpenAiToolDefinition>>) -> Option<Vec<ResponsesToolDefinition>> {
     let tools = tools?;
     let converted: Vec<ResponsesToolDefinition> = tools.into_iter().map(map_single_tool).collect();
@@ -176,6 +258,7 @@ fn map_single_tool(tool: OpenAiToolDefinition) -> ResponsesToolDefinition {
         name: tool.function.name,
         description: tool.function.description,
         parameters: tool.function.parameters,
+        cache: tool.cache,
     }
 }
 
@@ -184,63 +267,35 @@ mod tests {
     use serde_json::Value;
 
     use crate::config::{Config, WireApi};
-    use crate::models::{
-        ClaudeContent, ClaudeContentBlock, ClaudeMessage, ClaudeMessagesRequest, ClaudeToolChoice,
-        ClaudeToolDefinition,
-    };
+    use crate::models::{ClaudeMessage, ClaudeMessagesRequest};
 
-    use super::convert_claude_to_responses;
+    use super::{OpenAiToolChoice, convert_claude_to_responses, map_tool_choice, parse_data_url};
 
     fn test_config() -> Config {
-        Config {
-            openai_api_key: "sk-test".to_string(),
-            anthropic_api_key: None,
-            openai_base_url: "https://api.openai.com/v1".to_string(),
-            azure_api_version: None,
-            host: "127.0.0.1".to_string(),
-            port: 8082,
-            log_level: "INFO".to_string(),
-            request_timeout: 90,
-            stream_request_timeout: None,
-            request_body_max_size: 16 * 1024 * 1024,
-            session_ttl_min_secs: 1800,
-            session_ttl_max_secs: 86400,
-            session_cleanup_interval_secs: 60,
-            debug_tool_id_matching: false,
-            wire_api: WireApi::Responses,
-            big_model: "gpt-4o".to_string(),
-            middle_model: "gpt-4o".to_string(),
-            small_model: "gpt-4o-mini".to_string(),
-            min_thinking_level: None,
-            custom_headers: Default::default(),
-        }
+        let mut config = crate::upstream::test_support::test_config();
+        config.wire_api = WireApi::Responses;
+        config
     }
 
     #[test]
     fn converts_tools_and_tool_choice() {
-        let request = ClaudeMessagesRequest {
-            model: "claude-3-5-sonnet-20241022".to_string(),
-            max_tokens: 256,
-            messages: vec![ClaudeMessage {
-                role: "user".to_string(),
-                content: Some(ClaudeContent::Text("hello".to_string())),
+        let request: ClaudeMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [{"role": "user", "content": "hello"}],
+            "system": "be brief",
+            "stop_sequences": ["stop"],
+            "stream": false,
+            "temperature": 0.5,
+            "top_p": 0.8,
+            "tools": [{
+                "name": "Bash",
+                "description": "run shell",
+                "input_schema": {"type": "object"}
             }],
-            thinking: None,
-            system: Some(crate::models::ClaudeSystemContent::Text(
-                "be brief".to_string(),
-            )),
-            stop_sequences: Some(vec!["stop".to_string()]),
-            stream: Some(false),
-            temperature: Some(0.5),
-            top_p: Some(0.8),
-            tools: Some(vec![ClaudeToolDefinition {
-                name: Some("Bash".to_string()),
-                description: Some("run shell".to_string()),
-                input_schema: Some(serde_json::json!({"type":"object"})),
-                extra: Default::default(),
-            }]),
-            tool_choice: Some(ClaudeToolChoice::Mode("auto".to_string())),
-        };
+            "tool_choice": "auto"
+        }))
+        .expect("valid request");
 
         let converted = convert_claude_to_responses(&request, &test_config());
 
@@ -268,29 +323,193 @@ mod tests {
         );
     }
 
+    #[test]
+    fn maps_json_schema_response_format_to_text_format() {
+        let request: ClaudeMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 64,
+            "messages": [{"role": "user", "content": "hi"}],
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": {"name": "answer", "schema": {"type": "object"}, "strict": true}
+            }
+        }))
+        .expect("valid request");
+
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &test_config()))
+            .expect("serialize request");
+        assert_eq!(
+            payload["text"],
+            serde_json::json!({
+                "format": {
+                    "type": "json_schema",
+                    "name": "answer",
+                    "schema": {"type": "object"},
+                    "strict": true
+                }
+            })
+        );
+        assert!(payload.get("response_format").is_none());
+    }
+
+    #[test]
+    fn forwards_disabled_parallel_tool_calls_and_seed() {
+        let request: ClaudeMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 64,
+            "messages": [{"role": "user", "content": "hi"}],
+            "tools": [{"name": "Bash", "input_schema": {"type": "object"}}],
+            "tool_choice": {"type": "auto", "disable_parallel_tool_use": true},
+            "seed": 42
+        }))
+        .expect("valid request");
+
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &test_config()))
+            .expect("serialize request");
+        assert_eq!(payload["tool_choice"], serde_json::json!("auto"));
+        assert_eq!(payload["parallel_tool_calls"], serde_json::json!(false));
+        assert_eq!(payload["seed"], serde_json::json!(42));
+    }
+
+    #[test]
+    fn forwards_clamped_penalties() {
+        let request: ClaudeMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 64,
+            "messages": [{"role": "user", "content": "hi"}],
+            "frequency_penalty": 0.4,
+            "presence_penalty": 9.0
+        }))
+        .expect("valid request");
+
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &test_config()))
+            .expect("serialize request");
+        assert_eq!(payload["frequency_penalty"], serde_json::json!(0.4));
+        assert_eq!(payload["presence_penalty"], serde_json::json!(2.0));
+    }
+
+    #[test]
+    fn sends_configured_include_entries() {
+        let request: ClaudeMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 64,
+            "messages": [{"role": "user", "content": "hi"}]
+        }))
+        .expect("valid request");
+
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &test_config()))
+            .expect("serialize request");
+        assert!(payload.get("include").is_none());
+
+        let mut config = test_config();
+        config.responses_include = vec!["reasoning.encrypted_content".to_string()];
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &config))
+            .expect("serialize request");
+        assert_eq!(
+            payload["include"],
+            serde_json::json!(["reasoning.encrypted_content"])
+        );
+    }
+
+    #[test]
+    fn converts_url_image_source_to_input_image() {
+        let message: ClaudeMessage = serde_json::from_value(serde_json::json!({
+            "role": "user",
+            "content": [
+                {"type": "text", "text": "describe"},
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/cat.png"}}
+            ]
+        }))
+        .expect("valid message");
+        let request: ClaudeMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [message],
+            "stream": false
+        }))
+        .expect("valid request");
+
+        let converted = convert_claude_to_responses(&request, &test_config());
+        let payload = serde_json::to_value(converted).expect("serialize request");
+
+        assert_eq!(
+            payload["input"][0]["content"][1],
+            serde_json::json!({"type": "input_image", "image_url": "https://example.com/cat.png"})
+        );
+    }
+
+    #[test]
+    fn emits_base64_source_for_inline_images_when_enabled() {
+        let request: ClaudeMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [{
+                "role": "user",
+                "content": [{
+                    "type": "image",
+                    "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0K"}
+                }]
+            }]
+        }))
+        .expect("valid request");
+
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &test_config()))
+            .expect("serialize request");
+        assert_eq!(
+            payload["input"][0]["content"][0]["image_url"],
+            serde_json::json!("data:image/png;base64,iVBORw0K")
+        );
+
+        let mut config = test_config();
+        config.responses_base64_image_source = true;
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &config))
+            .expect("serialize request");
+        assert_eq!(
+            payload["input"][0]["content"][0],
+            serde_json::json!({
+                "type": "input_image",
+                "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0K"}
+            })
+        );
+        assert_eq!(parse_data_url("data:image/png,raw"), None);
+        assert_eq!(parse_data_url("https://example.com/cat.png"), None);
+    }
+
+    #[test]
+    fn maps_tool_choice_modes_and_named_tool() {
+        assert_eq!(
+            map_tool_choice(Some(OpenAiToolChoice::auto())),
+            Some(serde_json::json!("auto"))
+        );
+        assert_eq!(
+            map_tool_choice(Some(OpenAiToolChoice::required())),
+            Some(serde_json::json!("required"))
+        );
+        assert_eq!(
+            map_tool_choice(Some(OpenAiToolChoice::none())),
+            Some(serde_json::json!("none"))
+        );
+        assert_eq!(
+            map_tool_choice(Some(OpenAiToolChoice::tool("Bash".to_string()))),
+            Some(serde_json::json!({"type": "function", "name": "Bash"}))
+        );
+    }
+
     #[test]
     fn converts_assistant_tool_calls_to_function_call_items() {
-        let request = ClaudeMessagesRequest {
-            model: "claude-3-5-sonnet-20241022".to_string(),
-            max_tokens: 256,
-            messages: vec![ClaudeMessage {
-                role: "assistant".to_string(),
-                content: Some(ClaudeContent::Blocks(vec![ClaudeContentBlock::ToolUse {
-                    id: Some("call_abc".to_string()),
-                    name: Some("Bash".to_string()),
-                    input: Some(serde_json::json!({"command":"cargo check"})),
-                    extra: Default::default(),
-                }])),
-            }],
-            thinking: None,
-            system: None,
-            stop_sequences: None,
-            stream: Some(false),
-            temperature: Some(1.0),
-            top_p: None,
-            tools: None,
-            tool_choice: None,
-        };
+        let request: ClaudeMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [{"role": "assistant", "content": [{
+                "type": "tool_use",
+                "id": "call_abc",
+                "name": "Bash",
+                "input": {"command": "cargo check"}
+            }]}],
+            "stream": false,
+            "temperature": 1.0
+        }))
+        .expect("valid request");
 
         let converted = convert_claude_to_responses(&request, &test_config());
         let payload = serde_json::to_value(converted).expect("serialize request");
@@ -13,6 +13,12 @@ pub struct OpenAiResponsesRequest {
     pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f64>,
+    /// Not in the OpenAI spec; honoured by llama.cpp, Ollama and vLLM.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Not in the OpenAI spec; a minimum-length hint for vLLM and llama.cpp.
+    #[serde(rename = "min_tokens", skip_serializing_if = "Option::is_none")]
+    pub min_output_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -21,6 +27,27 @@ pub struct OpenAiResponsesRequest {
     pub tools: Option<Vec<ResponsesToolDefinition>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Not in the OpenAI Responses spec; honoured by compatible servers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Structured-output settings; carries `response_format` as `text.format`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<Value>,
+    /// Continues a stored upstream response; `input` then holds only the
+    /// items that follow it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_response_id: Option<String>,
+    /// Extra output data to return, e.g. `reasoning.encrypted_content`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
     pub stream: bool,
 }
 
@@ -42,6 +69,8 @@ pub struct ResponsesToolDefinition {
     pub name: String,
     pub description: String,
     pub parameters: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -69,9 +98,32 @@ pub enum ResponsesMessageContent {
 #[serde(tag = "type")]
 pub enum ResponsesMessageContentPart {
     #[serde(rename = "input_text")]
-    InputText { text: String },
+    Text { text: String },
     #[serde(rename = "input_image")]
-    InputImage { image_url: String },
+    Image {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        image_url: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source: Option<ResponsesImageSource>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    #[serde(rename = "input_file")]
+    File {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+        file_data: String,
+    },
+    #[serde(rename = "input_video")]
+    Video { video_url: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponsesImageSource {
+    #[serde(rename = "type")]
+    pub source_type: String,
+    pub media_type: String,
+    pub data: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -0,0 +1,86 @@
+use crate::config::{Config, ModelRoutingRule};
+use crate::models::ClaudeMessagesRequest;
+
+use super::models::map_claude_model_to_openai;
+use super::tools::is_thinking_requested;
+
+/// Resolves the upstream model for a request: the name-based mapping first,
+/// then the first matching `model_routing_rules` entry overrides it.
+pub fn route_claude_request(request: &ClaudeMessagesRequest, config: &Config) -> String {
+    let mapped_model = map_claude_model_to_openai(&request.model, config);
+    config
+        .model_routing_rules
+        .iter()
+        .find(|rule| rule_matches(rule, request))
+        .map(|rule| rule.target_model.clone())
+        .unwrap_or(mapped_model)
+}
+
+fn rule_matches(rule: &ModelRoutingRule, request: &ClaudeMessagesRequest) -> bool {
+    let has_tools = request
+        .tools
+        .as_ref()
+        .is_some_and(|tools| !tools.is_empty());
+    let has_thinking = is_thinking_requested(request.thinking.as_ref());
+
+    rule.max_tokens_lte
+        .is_none_or(|limit| request.max_tokens <= limit)
+        && rule
+            .min_tokens_gte
+            .is_none_or(|limit| request.max_tokens >= limit)
+        && rule.has_tools.is_none_or(|expected| expected == has_tools)
+        && rule
+            .has_thinking
+            .is_none_or(|expected| expected == has_thinking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::route_claude_request;
+    use crate::config::ModelRoutingRule;
+    use crate::models::ClaudeMessagesRequest;
+
+    fn request(max_tokens: u32, with_tools: bool) -> ClaudeMessagesRequest {
+        let mut body = serde_json::json!({
+            "model": "claude-3-opus-20240229",
+            "max_tokens": max_tokens,
+            "messages": [{"role": "user", "content": "hi"}]
+        });
+        if with_tools {
+            body["tools"] =
+                serde_json::json!([{"name": "Bash", "input_schema": {"type": "object"}}]);
+        }
+        serde_json::from_value(body).expect("valid request")
+    }
+
+    #[test]
+    fn first_matching_rule_overrides_name_based_mapping() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.model_routing_rules = vec![
+            ModelRoutingRule {
+                max_tokens_lte: Some(256),
+                has_tools: Some(false),
+                target_model: "gpt-4o-mini".to_string(),
+                ..Default::default()
+            },
+            ModelRoutingRule {
+                max_tokens_lte: Some(256),
+                target_model: "gpt-4.1-mini".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            route_claude_request(&request(128, false), &config),
+            "gpt-4o-mini"
+        );
+        assert_eq!(
+            route_claude_request(&request(128, true), &config),
+            "gpt-4.1-mini"
+        );
+        assert_eq!(
+            route_claude_request(&request(1024, false), &config),
+            config.big_model
+        );
+    }
+}
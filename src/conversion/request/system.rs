@@ -1,22 +1,52 @@
+use tracing::debug;
+
 use crate::models::{ClaudeSystemBlock, ClaudeSystemContent};
 
-pub fn extract_system_text(system: &ClaudeSystemContent) -> String {
+/// Joins system blocks with `separator`, except after a block carrying
+/// `cache_control`, where `cache_boundary_separator` marks the boundary so
+/// it stays visible once the blocks collapse into one string.
+pub fn extract_system_text(
+    system: &ClaudeSystemContent,
+    separator: &str,
+    cache_boundary_separator: &str,
+) -> String {
     match system {
         ClaudeSystemContent::Text(text) => text.to_string(),
         ClaudeSystemContent::Blocks(blocks) => {
-            let text_parts: Vec<String> = blocks
-                .iter()
-                .filter_map(extract_system_block_text)
-                .collect();
-            text_parts.join("\n\n")
+            join_system_blocks(blocks, separator, cache_boundary_separator)
         }
         ClaudeSystemContent::Other(_) => String::new(),
     }
 }
 
-fn extract_system_block_text(block: &ClaudeSystemBlock) -> Option<String> {
-    match block {
-        ClaudeSystemBlock::Text { text, .. } => Some(text.clone()),
-        ClaudeSystemBlock::Unknown => None,
+fn join_system_blocks(
+    blocks: &[ClaudeSystemBlock],
+    separator: &str,
+    cache_boundary_separator: &str,
+) -> String {
+    let mut joined = String::new();
+    let mut previous_cached: Option<bool> = None;
+    let mut cache_boundaries = 0usize;
+    for block in blocks {
+        let ClaudeSystemBlock::Text { text, extra } = block else {
+            continue;
+        };
+        match previous_cached {
+            Some(true) => {
+                cache_boundaries += 1;
+                joined.push_str(cache_boundary_separator);
+            }
+            Some(false) => joined.push_str(separator),
+            None => {}
+        }
+        joined.push_str(text);
+        previous_cached = Some(extra.contains_key("cache_control"));
+    }
+    if cache_boundaries > 0 {
+        debug!(
+            phase = "system_cache_boundaries",
+            cache_boundaries, "Joined system blocks across cache_control boundaries"
+        );
     }
+    joined
 }
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use tracing::warn;
+
+use super::models::OpenAiMessage;
+
+/// Tool call ids issued by assistant turns that no tool result has answered
+/// yet. Answering an id consumes it, so an agent that reuses `bash_1` in a
+/// later turn gets exactly one tool message per call rather than every
+/// result for that id being accepted.
+#[derive(Debug, Default)]
+pub struct PendingToolCalls {
+    pending: HashSet<String>,
+    answered: HashSet<String>,
+}
+
+impl PendingToolCalls {
+    pub fn open_calls(&mut self, assistant_message: &OpenAiMessage) {
+        for tool_call in assistant_message.assistant_tool_calls().unwrap_or_default() {
+            let id = tool_call.id.trim();
+            if !id.is_empty() {
+                self.answered.remove(id);
+                self.pending.insert(id.to_string());
+            }
+        }
+    }
+
+    /// Returns whether a tool result for `tool_call_id` should be kept,
+    /// logging why it is dropped otherwise.
+    pub fn answer(&mut self, tool_call_id: &str, debug_tool_id_matching: bool) -> bool {
+        let id = tool_call_id.trim();
+        if self.pending.remove(id) {
+            self.answered.insert(id.to_string());
+            return true;
+        }
+
+        let reason = if self.answered.contains(id) {
+            "duplicate_tool_result"
+        } else {
+            "unknown_tool_call_id"
+        };
+        if debug_tool_id_matching {
+            let mut known_tool_call_ids: Vec<&str> =
+                self.pending.iter().map(String::as_str).collect();
+            known_tool_call_ids.sort_unstable();
+            warn!(
+                phase = "drop_tool_result",
+                reason,
+                tool_call_id = id,
+                known_ids_count = known_tool_call_ids.len(),
+                ?known_tool_call_ids,
+                "Dropping tool message with unknown tool_call_id"
+            );
+        } else {
+            warn!(
+                phase = "drop_tool_result",
+                reason,
+                tool_call_id = id,
+                known_ids_count = self.pending.len(),
+                "Dropping tool message with unknown tool_call_id"
+            );
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::conversion::request::convert_claude_to_openai;
+    use crate::models::ClaudeMessagesRequest;
+
+    fn tool_call_ids(messages: serde_json::Value) -> Vec<String> {
+        let request: ClaudeMessagesRequest = serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": messages
+        }))
+        .expect("valid request");
+        let config = crate::upstream::test_support::test_config();
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config).messages)
+            .expect("serialize messages");
+        payload
+            .as_array()
+            .expect("messages array")
+            .iter()
+            .filter(|message| message["role"] == "tool")
+            .filter_map(|message| message["tool_call_id"].as_str().map(str::to_string))
+            .collect()
+    }
+
+    fn bash_call(id: &str) -> serde_json::Value {
+        json!({"role": "assistant", "content": [
+            {"type": "tool_use", "id": id, "name": "Bash", "input": {"cmd": "ls"}}
+        ]})
+    }
+
+    fn bash_result(id: &str) -> serde_json::Value {
+        json!({"role": "user", "content": [
+            {"type": "tool_result", "tool_use_id": id, "content": "ok"}
+        ]})
+    }
+
+    #[test]
+    fn accepts_an_id_reused_by_a_later_assistant_turn() {
+        let ids = tool_call_ids(json!([
+            {"role": "user", "content": "go"},
+            bash_call("bash_1"),
+            bash_result("bash_1"),
+            {"role": "assistant", "content": "again"},
+            {"role": "user", "content": "once more"},
+            bash_call("bash_1"),
+            bash_result("bash_1")
+        ]));
+        assert_eq!(ids, ["bash_1", "bash_1"]);
+    }
+
+    #[test]
+    fn drops_a_second_result_for_an_already_answered_call() {
+        let ids = tool_call_ids(json!([
+            {"role": "user", "content": "go"},
+            bash_call("bash_1"),
+            bash_result("bash_1"),
+            {"role": "assistant", "content": "done"},
+            bash_result("bash_1")
+        ]));
+        assert_eq!(ids, ["bash_1"]);
+    }
+}
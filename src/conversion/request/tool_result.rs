@@ -3,9 +3,11 @@ use serde_json::Value;
 use tracing::warn;
 
 use crate::constants::{CONTENT_TEXT, ROLE_USER};
-use crate::conversion::request::models::{OpenAiMessage, OpenAiToolMessage};
+use crate::conversion::request::models::{OpenAiMessage, OpenAiToolMessage, OpenAiUserMessage};
 use crate::models::{ClaudeContent, ClaudeContentBlock, ClaudeMessage};
 
+const TOOL_ERROR_MARKER: &str = "[ERROR] ";
+
 pub fn convert_claude_tool_results(message: &ClaudeMessage) -> Vec<OpenAiMessage> {
     let Some(content) = &message.content else {
         return Vec::new();
@@ -21,6 +23,33 @@ pub fn convert_claude_tool_results(message: &ClaudeMessage) -> Vec<OpenAiMessage
         .collect()
 }
 
+/// Legacy `function` messages carry no call id, and a `tool` message without a
+/// matching assistant `tool_calls` entry is rejected upstream, so the result
+/// is folded into a user text message naming the function.
+pub fn convert_legacy_function_message(message: &ClaudeMessage) -> Option<OpenAiMessage> {
+    let Some(name) = message
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    else {
+        warn!(
+            phase = "drop_tool_result",
+            reason = "missing_function_name",
+            "Dropping function role message"
+        );
+        return None;
+    };
+    let content = message
+        .content
+        .as_ref()
+        .and_then(|content| serde_json::to_value(content).ok());
+    let result = normalize_tool_result_content(content.as_ref());
+    Some(OpenAiMessage::User(OpenAiUserMessage::from_text(format!(
+        "Result of function `{name}`:\n{result}"
+    ))))
+}
+
 pub fn is_tool_result_user_message(message: &ClaudeMessage) -> bool {
     if message.role != ROLE_USER {
         return false;
@@ -59,6 +88,7 @@ fn convert_tool_result_block(block: &ClaudeContentBlock) -> Option<OpenAiToolMes
     let ClaudeContentBlock::ToolResult {
         tool_use_id,
         content,
+        is_error,
         ..
     } = block
     else {
@@ -84,14 +114,24 @@ fn convert_tool_result_block(block: &ClaudeContentBlock) -> Option<OpenAiToolMes
         return None;
     }
 
-    let normalized_content = parse_tool_result_content(content.as_ref());
+    let normalized_content = parse_tool_result_content(content.as_ref(), is_error.unwrap_or(false));
     Some(OpenAiToolMessage::new(
         tool_use_id.to_string(),
         normalized_content,
     ))
 }
 
-fn parse_tool_result_content(content: Option<&Value>) -> String {
+/// OpenAI tool messages have no error flag, so failed results carry a text marker.
+fn parse_tool_result_content(content: Option<&Value>, is_error: bool) -> String {
+    let normalized = normalize_tool_result_content(content);
+    if is_error {
+        format!("{TOOL_ERROR_MARKER}{normalized}")
+    } else {
+        normalized
+    }
+}
+
+fn normalize_tool_result_content(content: Option<&Value>) -> String {
     let Some(content) = content else {
         return "No content provided".to_string();
     };
@@ -151,3 +191,81 @@ impl LooseTextBlock {
             .map(ToOwned::to_owned)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::parse_tool_result_content;
+    use crate::config::Config;
+    use crate::conversion::request::{convert_claude_to_openai, convert_claude_to_responses};
+    use crate::models::ClaudeMessagesRequest;
+
+    #[test]
+    fn prefixes_error_results_with_error_marker() {
+        let content = json!("command not found");
+        assert_eq!(
+            parse_tool_result_content(Some(&content), true),
+            "[ERROR] command not found"
+        );
+        assert_eq!(
+            parse_tool_result_content(Some(&content), false),
+            "command not found"
+        );
+    }
+
+    fn legacy_function_request() -> ClaudeMessagesRequest {
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [
+                {"role": "user", "content": "weather in Paris?"},
+                {"role": "function", "name": "get_weather", "content": "18C, cloudy"}
+            ]
+        }))
+        .expect("valid request")
+    }
+
+    fn legacy_function_config(accept_legacy_function_role: bool) -> Config {
+        let mut config = crate::upstream::test_support::test_config();
+        config.accept_legacy_function_role = accept_legacy_function_role;
+        config
+    }
+
+    fn converted_messages(accept_legacy_function_role: bool) -> Value {
+        let config = legacy_function_config(accept_legacy_function_role);
+        serde_json::to_value(convert_claude_to_openai(&legacy_function_request(), &config).messages)
+            .expect("serialize messages")
+    }
+
+    #[test]
+    fn converts_legacy_function_messages_only_when_enabled() {
+        let messages = converted_messages(true);
+        assert_eq!(
+            messages[1],
+            json!({"role": "user", "content": "Result of function `get_weather`:\n18C, cloudy"})
+        );
+
+        let messages = converted_messages(false);
+        assert_eq!(messages.as_array().map(Vec::len), Some(1));
+        assert_eq!(messages[0]["role"], json!("user"));
+    }
+
+    #[test]
+    fn responses_input_gets_the_same_legacy_function_handling() {
+        let input = |accept: bool| {
+            let request = legacy_function_request();
+            let converted = convert_claude_to_responses(&request, &legacy_function_config(accept));
+            serde_json::to_value(converted.input).expect("serialize input")
+        };
+
+        let enabled = input(true);
+        assert_eq!(enabled.as_array().map(Vec::len), Some(2));
+        assert_eq!(enabled[1]["role"], json!("user"));
+        assert_eq!(
+            enabled[1]["content"],
+            json!("Result of function `get_weather`:\n18C, cloudy")
+        );
+        assert_eq!(input(false).as_array().map(Vec::len), Some(1));
+    }
+}
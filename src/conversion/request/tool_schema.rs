@@ -0,0 +1,136 @@
+use serde_json::{Map, Value};
+
+use super::builtin_tools::is_builtin_tool;
+use crate::config::Config;
+use crate::models::ClaudeMessagesRequest;
+
+/// Keywords that give a property a type without a literal `type` field.
+const TYPED_BY_KEYWORDS: [&str; 6] = ["anyOf", "oneOf", "allOf", "$ref", "enum", "const"];
+
+/// Rejects client tool schemas that upstream providers are known to refuse,
+/// so the client gets a 400 naming the tool instead of an opaque upstream
+/// error. A missing top-level `type` passes when `repair_tool_schemas` will
+/// add it during conversion.
+pub fn check_tool_schemas(request: &ClaudeMessagesRequest, config: &Config) -> Result<(), String> {
+    if !config.validate_tool_schemas {
+        return Ok(());
+    }
+    let Some(tools) = &request.tools else {
+        return Ok(());
+    };
+    for (index, tool) in tools.iter().enumerate() {
+        if is_builtin_tool(tool) {
+            continue;
+        }
+        let Some(schema) = &tool.input_schema else {
+            continue;
+        };
+        validate_schema(schema, config.repair_tool_schemas).map_err(|problem| {
+            let name = tool.name.as_deref().unwrap_or_default();
+            format!("tools[{index}] ({name}): input_schema {problem}")
+        })?;
+    }
+    Ok(())
+}
+
+/// Adds `"type": "object"` to an object schema that omits it.
+pub fn repair_tool_schema(schema: &mut Value) {
+    if let Value::Object(object) = schema {
+        object
+            .entry("type")
+            .or_insert_with(|| Value::String("object".to_string()));
+    }
+}
+
+fn validate_schema(schema: &Value, repair: bool) -> Result<(), String> {
+    let Value::Object(object) = schema else {
+        return Err("must be a JSON object".to_string());
+    };
+    match object.get("type") {
+        Some(Value::String(kind)) if kind == "object" => {}
+        None if repair => {}
+        _ => return Err("must have \"type\": \"object\"".to_string()),
+    }
+    match object.get("properties") {
+        None => Ok(()),
+        Some(Value::Object(properties)) => validate_properties(properties),
+        Some(_) => Err("properties must be an object".to_string()),
+    }
+}
+
+fn validate_properties(properties: &Map<String, Value>) -> Result<(), String> {
+    for (name, property) in properties {
+        let Value::Object(property) = property else {
+            return Err(format!("property '{name}' must be an object"));
+        };
+        let typed = property.contains_key("type")
+            || TYPED_BY_KEYWORDS
+                .iter()
+                .any(|keyword| property.contains_key(*keyword));
+        if !typed {
+            return Err(format!("property '{name}' is missing \"type\""));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::check_tool_schemas;
+    use crate::conversion::request::convert_claude_to_openai;
+    use crate::models::ClaudeMessagesRequest;
+
+    fn request(input_schema: serde_json::Value) -> ClaudeMessagesRequest {
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [{"role": "user", "content": "hi"}],
+            "tools": [{"name": "Read", "input_schema": input_schema}]
+        }))
+        .expect("valid request")
+    }
+
+    #[test]
+    fn rejects_malformed_schemas_only_when_enabled() {
+        let mut config = crate::upstream::test_support::test_config();
+        let untyped_property = request(json!({
+            "type": "object",
+            "properties": {"path": {"description": "file"}}
+        }));
+        assert!(check_tool_schemas(&untyped_property, &config).is_ok());
+
+        config.validate_tool_schemas = true;
+        let error = check_tool_schemas(&untyped_property, &config).expect_err("untyped property");
+        assert_eq!(
+            error,
+            "tools[0] (Read): input_schema property 'path' is missing \"type\""
+        );
+        assert!(check_tool_schemas(&request(json!("object")), &config).is_err());
+        assert!(
+            check_tool_schemas(
+                &request(json!({
+                    "type": "object",
+                    "properties": {"mode": {"anyOf": [{"type": "string"}, {"type": "null"}]}}
+                })),
+                &config
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn repair_mode_adds_missing_object_type() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.validate_tool_schemas = true;
+        let untyped = request(json!({"properties": {"path": {"type": "string"}}}));
+        assert!(check_tool_schemas(&untyped, &config).is_err());
+
+        config.repair_tool_schemas = true;
+        assert!(check_tool_schemas(&untyped, &config).is_ok());
+        let converted = convert_claude_to_openai(&untyped, &config);
+        let tools = converted.tools.expect("converted tools");
+        assert_eq!(tools[0].function.parameters["type"], json!("object"));
+    }
+}
@@ -1,17 +1,25 @@
 use serde::Deserialize;
 use serde_json::{Map, Value};
+use tracing::{debug, warn};
 
+use crate::config::Config;
 use crate::constants::TOOL_FUNCTION;
+use crate::conversion::request::builtin_tools::{
+    builtin_tool_function, is_builtin_tool, log_builtin_tool,
+};
 use crate::conversion::request::models::{
     OpenAiChatRequest, OpenAiFunctionDefinition, OpenAiToolChoice, OpenAiToolDefinition,
     supports_reasoning_effort,
 };
-use crate::models::{ClaudeMessagesRequest, ClaudeThinking, ClaudeToolChoice};
+use crate::conversion::request::tool_schema::repair_tool_schema;
+use crate::models::{
+    ClaudeMessagesRequest, ClaudeThinking, ClaudeToolChoice, ClaudeToolDefinition,
+};
 
 pub fn add_optional_request_fields(
     request: &ClaudeMessagesRequest,
     openai_request: &mut OpenAiChatRequest,
-    min_thinking_level: Option<&str>,
+    config: &Config,
 ) {
     if let Some(stop_sequences) = &request.stop_sequences {
         openai_request.stop = Some(stop_sequences.clone());
@@ -19,22 +27,52 @@ pub fn add_optional_request_fields(
     if let Some(top_p) = request.top_p {
         openai_request.top_p = Some(top_p);
     }
+    openai_request.top_k = request.top_k;
+    openai_request.parallel_tool_calls = request.parallel_tool_calls;
+    openai_request.min_output_tokens = min_tokens_for_thinking(request, config);
+    openai_request.frequency_penalty = request
+        .frequency_penalty
+        .or(config.default_frequency_penalty)
+        .map(|value| clamp_penalty("frequency_penalty", value));
+    openai_request.presence_penalty = request
+        .presence_penalty
+        .or(config.default_presence_penalty)
+        .map(|value| clamp_penalty("presence_penalty", value));
+    openai_request.response_format = request
+        .response_format
+        .clone()
+        .or_else(|| config.default_response_format.clone());
 
     openai_request.reasoning_effort = derive_reasoning_effort(
         request.thinking.as_ref(),
         request.max_tokens,
         &openai_request.model,
-        min_thinking_level,
+        config.min_thinking_level.as_deref(),
+        &config.reasoning_models,
     );
 }
 
+/// Half the thinking budget by default (`min_tokens_thinking_fraction`), so
+/// backends that honour `min_tokens` do not stop mid-thought on a stop
+/// sequence. Never exceeds `max_tokens`.
+fn min_tokens_for_thinking(request: &ClaudeMessagesRequest, config: &Config) -> Option<u32> {
+    if !config.enforce_min_tokens_for_thinking || !is_thinking_requested(request.thinking.as_ref())
+    {
+        return None;
+    }
+    let budget_tokens = request.thinking.as_ref()?.budget_tokens?;
+    let min_tokens = (f64::from(budget_tokens) * config.min_tokens_thinking_fraction) as u32;
+    Some(min_tokens.min(request.max_tokens)).filter(|tokens| *tokens > 0)
+}
+
 pub fn derive_reasoning_effort(
     thinking: Option<&ClaudeThinking>,
     max_tokens: u32,
     upstream_model: &str,
     min_thinking_level: Option<&str>,
+    reasoning_models: &[String],
 ) -> Option<String> {
-    if !supports_reasoning_effort(upstream_model) {
+    if !supports_reasoning_effort(upstream_model, reasoning_models) {
         return None;
     }
 
@@ -126,31 +164,63 @@ fn effort_rank(value: &str) -> u8 {
     }
 }
 
-pub fn add_tools(request: &ClaudeMessagesRequest, openai_request: &mut OpenAiChatRequest) {
+pub fn add_tools(
+    request: &ClaudeMessagesRequest,
+    openai_request: &mut OpenAiChatRequest,
+    config: &Config,
+) {
     let Some(tools) = &request.tools else {
         return;
     };
 
-    let converted_tools: Vec<OpenAiToolDefinition> =
-        tools.iter().filter_map(convert_single_tool).collect();
+    let converted_tools: Vec<OpenAiToolDefinition> = tools
+        .iter()
+        .filter(|tool| keep_tool(tool, config.drop_unsupported_tools))
+        .filter_map(|tool| convert_single_tool(tool, config))
+        .collect();
     if converted_tools.is_empty() {
         return;
     }
     openai_request.tools = Some(converted_tools);
 }
 
-fn convert_single_tool(tool: &crate::models::ClaudeToolDefinition) -> Option<OpenAiToolDefinition> {
+fn keep_tool(tool: &ClaudeToolDefinition, drop_unsupported_tools: bool) -> bool {
+    if !is_builtin_tool(tool) {
+        return true;
+    }
+    log_builtin_tool(tool, drop_unsupported_tools);
+    !drop_unsupported_tools
+}
+
+fn convert_single_tool(
+    tool: &ClaudeToolDefinition,
+    config: &Config,
+) -> Option<OpenAiToolDefinition> {
     let name = tool.name.as_deref().unwrap_or_default().trim().to_string();
     if name.is_empty() {
         return None;
     }
 
-    let description = tool.description.as_deref().unwrap_or_default().to_string();
-    let parameters = tool
-        .input_schema
-        .clone()
-        .unwrap_or_else(default_tool_parameters);
+    let (description, parameters) = builtin_tool_function(tool).unwrap_or_else(|| {
+        let description = tool.description.as_deref().unwrap_or_default().to_string();
+        let mut parameters = tool
+            .input_schema
+            .clone()
+            .unwrap_or_else(default_tool_parameters);
+        if config.repair_tool_schemas {
+            repair_tool_schema(&mut parameters);
+        }
+        (description, parameters)
+    });
 
+    let cache = (config.tool_schema_cache_hints && has_ephemeral_cache_control(tool)).then(|| {
+        debug!(
+            phase = "tool_cache_hint",
+            tool_name = %name,
+            "Marking tool definition as cacheable"
+        );
+        true
+    });
     Some(OpenAiToolDefinition {
         kind: TOOL_FUNCTION.to_string(),
         function: OpenAiFunctionDefinition {
@@ -158,26 +228,71 @@ fn convert_single_tool(tool: &crate::models::ClaudeToolDefinition) -> Option<Ope
             description,
             parameters,
         },
+        cache,
     })
 }
 
+fn has_ephemeral_cache_control(tool: &ClaudeToolDefinition) -> bool {
+    tool.extra
+        .get("cache_control")
+        .and_then(|cache_control| cache_control.get("type"))
+        .and_then(Value::as_str)
+        == Some("ephemeral")
+}
+
+const PENALTY_RANGE: std::ops::RangeInclusive<f64> = -2.0..=2.0;
+
+/// OpenAI rejects penalties outside [-2.0, 2.0]; clamp rather than fail the
+/// whole request over a tuning knob.
+fn clamp_penalty(field: &'static str, value: f64) -> f64 {
+    if PENALTY_RANGE.contains(&value) {
+        return value;
+    }
+    let clamped = value.clamp(*PENALTY_RANGE.start(), *PENALTY_RANGE.end());
+    warn!(
+        phase = "clamp_penalty",
+        field, value, clamped, "Clamping out-of-range penalty"
+    );
+    clamped
+}
+
 pub fn add_tool_choice(request: &ClaudeMessagesRequest, openai_request: &mut OpenAiChatRequest) {
     let Some(tool_choice) = &request.tool_choice else {
         return;
     };
 
-    openai_request.tool_choice = Some(match tool_choice {
-        ClaudeToolChoice::Mode(choice_type) => match choice_type.as_str() {
-            "auto" | "any" => OpenAiToolChoice::auto(),
-            _ => OpenAiToolChoice::auto(),
-        },
+    openai_request.tool_choice = Some(map_claude_tool_choice(tool_choice));
+    if disables_parallel_tool_use(tool_choice) {
+        openai_request.parallel_tool_calls = Some(false);
+    }
+}
+
+fn disables_parallel_tool_use(tool_choice: &ClaudeToolChoice) -> bool {
+    match tool_choice {
+        ClaudeToolChoice::Named(named_choice) => {
+            named_choice.disable_parallel_tool_use.unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+fn map_claude_tool_choice(tool_choice: &ClaudeToolChoice) -> OpenAiToolChoice {
+    match tool_choice {
+        ClaudeToolChoice::Mode(choice_type) => create_tool_choice_for_mode(Some(choice_type)),
         ClaudeToolChoice::Named(named_choice) => match named_choice.choice_type.as_deref() {
             Some("tool") => create_tool_choice_payload(named_choice.name.as_deref()),
-            Some("auto") | Some("any") => OpenAiToolChoice::auto(),
-            _ => OpenAiToolChoice::auto(),
+            mode => create_tool_choice_for_mode(mode),
         },
         ClaudeToolChoice::Other(value) => create_tool_choice_from_value(value),
-    });
+    }
+}
+
+fn create_tool_choice_for_mode(mode: Option<&str>) -> OpenAiToolChoice {
+    match mode {
+        Some("any") => OpenAiToolChoice::required(),
+        Some("none") => OpenAiToolChoice::none(),
+        _ => OpenAiToolChoice::auto(),
+    }
 }
 
 fn create_tool_choice_payload(selected_name: Option<&str>) -> OpenAiToolChoice {
@@ -196,9 +311,8 @@ fn create_tool_choice_from_value(value: &Value) -> OpenAiToolChoice {
 
 fn map_loose_tool_choice_payload(payload: LooseToolChoicePayload) -> OpenAiToolChoice {
     match payload.choice_type.as_deref() {
-        Some("auto") | Some("any") => OpenAiToolChoice::auto(),
         Some("tool") => create_tool_choice_payload(payload.name.as_deref()),
-        _ => OpenAiToolChoice::auto(),
+        mode => create_tool_choice_for_mode(mode),
     }
 }
 
@@ -219,12 +333,128 @@ struct LooseToolChoicePayload {
 
 #[cfg(test)]
 mod tests {
-    use super::derive_reasoning_effort;
-    use crate::models::ClaudeThinking;
+    use super::{
+        clamp_penalty, derive_reasoning_effort, disables_parallel_tool_use, map_claude_tool_choice,
+    };
+    use crate::conversion::request::convert_claude_to_openai;
+    use crate::models::{
+        ClaudeMessagesRequest, ClaudeNamedToolChoice, ClaudeThinking, ClaudeToolChoice,
+    };
+    use serde_json::json;
+
+    fn mapped_tool_choice(tool_choice: ClaudeToolChoice) -> serde_json::Value {
+        serde_json::to_value(map_claude_tool_choice(&tool_choice)).expect("serializable")
+    }
+
+    #[test]
+    fn maps_tool_choice_modes_to_openai_strings() {
+        for (mode, expected) in [("auto", "auto"), ("any", "required"), ("none", "none")] {
+            let choice = ClaudeToolChoice::Named(ClaudeNamedToolChoice {
+                choice_type: Some(mode.to_string()),
+                name: None,
+                disable_parallel_tool_use: None,
+                extra: Default::default(),
+            });
+            assert_eq!(mapped_tool_choice(choice), json!(expected), "{mode}");
+            let choice = ClaudeToolChoice::Mode(mode.to_string());
+            assert_eq!(mapped_tool_choice(choice), json!(expected), "{mode}");
+        }
+    }
+
+    #[test]
+    fn serializes_any_tool_choice_as_required_for_both_wire_apis() {
+        let request: crate::models::ClaudeMessagesRequest = serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 64,
+            "messages": [{"role": "user", "content": "list files"}],
+            "tools": [{"name": "Bash", "input_schema": {"type": "object"}}],
+            "tool_choice": {"type": "any"}
+        }))
+        .expect("valid request");
+        let config = crate::upstream::test_support::test_config();
+
+        let chat = serde_json::to_string(&crate::conversion::request::convert_claude_to_openai(
+            &request, &config,
+        ))
+        .expect("serialize chat request");
+        assert!(chat.contains(r#""tool_choice":"required""#), "{chat}");
+
+        let responses = serde_json::to_string(
+            &crate::conversion::request::convert_claude_to_responses(&request, &config),
+        )
+        .expect("serialize responses request");
+        assert!(
+            responses.contains(r#""tool_choice":"required""#),
+            "{responses}"
+        );
+    }
+
+    #[test]
+    fn clamps_out_of_range_penalties() {
+        assert_eq!(clamp_penalty("frequency_penalty", 0.5), 0.5);
+        assert_eq!(clamp_penalty("frequency_penalty", 3.5), 2.0);
+        assert_eq!(clamp_penalty("presence_penalty", -2.5), -2.0);
+    }
+
+    #[test]
+    fn maps_named_tool_choice_to_function_choice() {
+        let choice = ClaudeToolChoice::Named(ClaudeNamedToolChoice {
+            choice_type: Some("tool".to_string()),
+            name: Some("get_weather".to_string()),
+            disable_parallel_tool_use: None,
+            extra: Default::default(),
+        });
+        assert_eq!(
+            mapped_tool_choice(choice),
+            json!({"type": "function", "function": {"name": "get_weather"}})
+        );
+    }
+
+    #[test]
+    fn reads_disable_parallel_tool_use_from_named_choice() {
+        let choice: ClaudeToolChoice =
+            serde_json::from_value(json!({"type": "auto", "disable_parallel_tool_use": true}))
+                .expect("valid tool choice");
+        assert!(disables_parallel_tool_use(&choice));
+        assert_eq!(mapped_tool_choice(choice), json!("auto"));
+        assert!(!disables_parallel_tool_use(&ClaudeToolChoice::Mode(
+            "auto".to_string()
+        )));
+    }
+
+    #[test]
+    fn passes_parallel_tool_calls_through_unless_tool_choice_disables_it() {
+        let serialized = |extra: serde_json::Value| {
+            let mut body = json!({
+                "model": "claude-3-5-sonnet-20241022",
+                "max_tokens": 256,
+                "messages": [{"role": "user", "content": "hi"}]
+            });
+            body.as_object_mut()
+                .expect("object")
+                .extend(extra.as_object().expect("object").clone());
+            let request: ClaudeMessagesRequest = serde_json::from_value(body).expect("request");
+            let config = crate::upstream::test_support::test_config();
+            serde_json::to_value(convert_claude_to_openai(&request, &config)).expect("serialize")
+        };
+
+        assert!(serialized(json!({})).get("parallel_tool_calls").is_none());
+        assert_eq!(
+            serialized(json!({"parallel_tool_calls": true}))["parallel_tool_calls"],
+            json!(true)
+        );
+        assert_eq!(
+            serialized(json!({
+                "parallel_tool_calls": true,
+                "tool_choice": {"type": "auto", "disable_parallel_tool_use": true}
+            }))["parallel_tool_calls"],
+            json!(false)
+        );
+    }
 
     #[test]
     fn defaults_to_low_when_thinking_missing() {
-        let effort = derive_reasoning_effort(None, 4_096, "o3-mini", None);
+        let effort = derive_reasoning_effort(None, 4_096, "o3-mini", None, &[]);
         assert_eq!(effort.as_deref(), Some("low"));
     }
 
@@ -234,7 +464,7 @@ mod tests {
             thinking_type: Some("disabled".to_string()),
             budget_tokens: Some(12_000),
         };
-        let effort = derive_reasoning_effort(Some(&thinking), 4_096, "o3-mini", None);
+        let effort = derive_reasoning_effort(Some(&thinking), 4_096, "o3-mini", None, &[]);
         assert_eq!(effort.as_deref(), Some("low"));
     }
 
@@ -244,7 +474,7 @@ mod tests {
             thinking_type: Some("enabled".to_string()),
             budget_tokens: Some(10_000),
         };
-        let effort = derive_reasoning_effort(Some(&thinking), 16_000, "o3-mini", None);
+        let effort = derive_reasoning_effort(Some(&thinking), 16_000, "o3-mini", None, &[]);
         assert_eq!(effort.as_deref(), Some("high"));
     }
 
@@ -254,13 +484,13 @@ mod tests {
             thinking_type: Some("enabled".to_string()),
             budget_tokens: None,
         };
-        let effort = derive_reasoning_effort(Some(&thinking), 4_096, "o3-mini", None);
+        let effort = derive_reasoning_effort(Some(&thinking), 4_096, "o3-mini", None, &[]);
         assert_eq!(effort.as_deref(), Some("medium"));
     }
 
     #[test]
     fn applies_minimum_floor_from_low_to_medium() {
-        let effort = derive_reasoning_effort(None, 4_096, "o3-mini", Some("medium"));
+        let effort = derive_reasoning_effort(None, 4_096, "o3-mini", Some("medium"), &[]);
         assert_eq!(effort.as_deref(), Some("medium"));
     }
 
@@ -270,7 +500,7 @@ mod tests {
             thinking_type: Some("enabled".to_string()),
             budget_tokens: None,
         };
-        let effort = derive_reasoning_effort(Some(&thinking), 4_096, "o3-mini", Some("high"));
+        let effort = derive_reasoning_effort(Some(&thinking), 4_096, "o3-mini", Some("high"), &[]);
         assert_eq!(effort.as_deref(), Some("high"));
     }
 
@@ -280,7 +510,8 @@ mod tests {
             thinking_type: Some("enabled".to_string()),
             budget_tokens: Some(10_000),
         };
-        let effort = derive_reasoning_effort(Some(&thinking), 16_000, "o3-mini", Some("medium"));
+        let effort =
+            derive_reasoning_effort(Some(&thinking), 16_000, "o3-mini", Some("medium"), &[]);
         assert_eq!(effort.as_deref(), Some("high"));
     }
 
@@ -290,7 +521,60 @@ mod tests {
             thinking_type: Some("enabled".to_string()),
             budget_tokens: Some(8_192),
         };
-        let effort = derive_reasoning_effort(Some(&thinking), 8_192, "gpt-4o", Some("high"));
+        let effort = derive_reasoning_effort(Some(&thinking), 8_192, "gpt-4o", Some("high"), &[]);
         assert!(effort.is_none());
     }
+
+    #[test]
+    fn supports_deepseek_and_qwq_reasoning_models() {
+        for model in [
+            "deepseek-reasoner",
+            "deepseek-r1",
+            "QwQ-32B",
+            "qvq-72b-preview",
+        ] {
+            let effort = derive_reasoning_effort(None, 4_096, model, None, &[]);
+            assert_eq!(effort.as_deref(), Some("low"), "{model}");
+        }
+        assert!(derive_reasoning_effort(None, 4_096, "deepseek-chat", None, &[]).is_none());
+    }
+
+    #[test]
+    fn configured_reasoning_models_extend_builtin_prefixes() {
+        let reasoning_models = vec!["my-reasoner".to_string()];
+        assert!(derive_reasoning_effort(None, 4_096, "my-reasoner-v2", None, &[]).is_none());
+        let effort =
+            derive_reasoning_effort(None, 4_096, "My-Reasoner-v2", None, &reasoning_models);
+        assert_eq!(effort.as_deref(), Some("low"));
+    }
+
+    #[test]
+    fn marks_ephemeral_cached_tools_only_when_hints_enabled() {
+        let request: crate::models::ClaudeMessagesRequest = serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [{"role": "user", "content": "hi"}],
+            "tools": [
+                {"name": "Read", "input_schema": {"type": "object"}, "cache_control": {"type": "ephemeral"}},
+                {"name": "Bash", "input_schema": {"type": "object"}}
+            ]
+        }))
+        .expect("valid request");
+        let mut config = crate::upstream::test_support::test_config();
+        let tools = |config: &crate::config::Config| {
+            serde_json::to_value(
+                crate::conversion::request::convert_claude_to_openai(&request, config).tools,
+            )
+            .expect("serialize tools")
+        };
+        assert!(tools(&config)[0].get("cache").is_none());
+
+        config.tool_schema_cache_hints = true;
+        let chat_tools = tools(&config);
+        assert_eq!(chat_tools[0]["cache"], json!(true));
+        assert!(chat_tools[1].get("cache").is_none());
+        let responses = crate::conversion::request::convert_claude_to_responses(&request, &config);
+        let responses_tools = serde_json::to_value(responses.tools).expect("serialize tools");
+        assert_eq!(responses_tools[0]["cache"], json!(true));
+    }
 }
@@ -1,9 +1,22 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use tracing::warn;
+
 use crate::conversion::request::models::{
-    OpenAiImageUrl, OpenAiMessage, OpenAiUserContentPart, OpenAiUserMessage,
+    OpenAiFile, OpenAiImageUrl, OpenAiMessage, OpenAiUserContentPart, OpenAiUserMessage,
+    OpenAiVideoUrl,
+};
+use crate::models::{
+    ClaudeContent, ClaudeContentBlock, ClaudeDocumentSource, ClaudeImageSource, ClaudeMessage,
+    ClaudeVideoSource,
 };
-use crate::models::{ClaudeContent, ClaudeContentBlock, ClaudeImageSource, ClaudeMessage};
 
-pub fn convert_claude_user_message(message: &ClaudeMessage) -> OpenAiMessage {
+pub fn convert_claude_user_message(
+    message: &ClaudeMessage,
+    document_passthrough: bool,
+    video_to_text_placeholder: bool,
+) -> OpenAiMessage {
     let Some(content) = &message.content else {
         return OpenAiMessage::User(OpenAiUserMessage::from_text(String::new()));
     };
@@ -13,8 +26,12 @@ pub fn convert_claude_user_message(message: &ClaudeMessage) -> OpenAiMessage {
             OpenAiMessage::User(OpenAiUserMessage::from_text(text_content.to_string()))
         }
         ClaudeContent::Blocks(blocks) => {
-            let openai_content: Vec<OpenAiUserContentPart> =
-                blocks.iter().filter_map(convert_user_block).collect();
+            let openai_content: Vec<OpenAiUserContentPart> = blocks
+                .iter()
+                .filter_map(|block| {
+                    convert_user_block(block, document_passthrough, video_to_text_placeholder)
+                })
+                .collect();
 
             if let Some(text) = single_text_content(&openai_content) {
                 OpenAiMessage::User(OpenAiUserMessage::from_text(text.to_string()))
@@ -26,34 +43,123 @@ pub fn convert_claude_user_message(message: &ClaudeMessage) -> OpenAiMessage {
     }
 }
 
-fn convert_user_block(block: &ClaudeContentBlock) -> Option<OpenAiUserContentPart> {
+fn convert_user_block(
+    block: &ClaudeContentBlock,
+    document_passthrough: bool,
+    video_to_text_placeholder: bool,
+) -> Option<OpenAiUserContentPart> {
     match block {
         ClaudeContentBlock::Text { text, .. } => Some(OpenAiUserContentPart::Text {
             text: text.to_string(),
         }),
         ClaudeContentBlock::ToolResult { .. } => None,
         ClaudeContentBlock::Image { source, .. } => convert_image_source(source.as_ref()),
+        ClaudeContentBlock::Document { source, extra } => {
+            convert_document_source(source.as_ref()?, extra, document_passthrough)
+        }
+        ClaudeContentBlock::Video { source, .. } => {
+            convert_video_source(source.as_ref()?, video_to_text_placeholder)
+        }
         _ => None,
     }
 }
 
-fn convert_image_source(source: Option<&ClaudeImageSource>) -> Option<OpenAiUserContentPart> {
-    let source = source?;
-    let source_type = source.source_type.as_deref().unwrap_or_default();
+/// Plain-text documents always become text parts. Base64 documents become a
+/// `file` part when passthrough is enabled; otherwise the encoded data is
+/// inlined as text behind a short note, since most backends reject `file`.
+fn convert_document_source(
+    source: &ClaudeDocumentSource,
+    extra: &BTreeMap<String, Value>,
+    document_passthrough: bool,
+) -> Option<OpenAiUserContentPart> {
+    let data = source.data.as_deref().filter(|data| !data.is_empty())?;
     let media_type = source.media_type.as_deref().unwrap_or_default();
-    let data = source.data.as_deref().unwrap_or_default();
+    let title = extra.get("title").and_then(Value::as_str);
 
-    if source_type != "base64" || media_type.is_empty() || data.is_empty() {
-        return None;
-    }
+    let text = match source.source_type.as_deref().unwrap_or_default() {
+        "text" => data.to_string(),
+        "base64" if document_passthrough && !media_type.is_empty() => {
+            return Some(OpenAiUserContentPart::File {
+                file: OpenAiFile {
+                    filename: title.map(str::to_string),
+                    file_data: format!("data:{media_type};base64,{data}"),
+                },
+            });
+        }
+        "base64" => format!(
+            "[Attached document{}, {media_type} encoded as base64]\n{data}",
+            title
+                .map(|title| format!(" \"{title}\""))
+                .unwrap_or_default()
+        ),
+        _ => return None,
+    };
+    Some(OpenAiUserContentPart::Text { text })
+}
+
+fn convert_image_source(source: Option<&ClaudeImageSource>) -> Option<OpenAiUserContentPart> {
+    let source = source?;
+    let url = match source.source_type.as_deref().unwrap_or_default() {
+        "base64" => base64_data_url(source)?,
+        "url" => source.url.clone().filter(|url| !url.trim().is_empty())?,
+        _ => return None,
+    };
 
     Some(OpenAiUserContentPart::ImageUrl {
         image_url: OpenAiImageUrl {
-            url: format!("data:{media_type};base64,{data}"),
+            url,
+            detail: source.detail.clone(),
+        },
+    })
+}
+
+/// Video URLs go upstream as `video_url` parts, which only some backends
+/// accept; the placeholder keeps the reference visible to text-only models.
+fn convert_video_source(
+    source: &ClaudeVideoSource,
+    video_to_text_placeholder: bool,
+) -> Option<OpenAiUserContentPart> {
+    if source.source_type.as_deref() != Some("url") {
+        return None;
+    }
+    let url = source.url.as_deref().filter(|url| !url.trim().is_empty())?;
+    if video_to_text_placeholder {
+        warn!(
+            phase = "video_placeholder",
+            url, "Replacing video block with a text placeholder"
+        );
+        return Some(OpenAiUserContentPart::Text {
+            text: format!("[Video: {url}]"),
+        });
+    }
+    Some(OpenAiUserContentPart::VideoUrl {
+        video_url: OpenAiVideoUrl {
+            url: url.to_string(),
         },
     })
 }
 
+fn base64_data_url(source: &ClaudeImageSource) -> Option<String> {
+    let media_type = source.media_type.as_deref().unwrap_or_default();
+    let data = source.data.as_deref().unwrap_or_default();
+    if media_type.is_empty() || data.is_empty() {
+        return None;
+    }
+
+    Some(format!("data:{media_type};base64,{data}"))
+}
+
+/// Splits a `data:<media_type>;base64,<data>` URL into its media type and
+/// payload; other URLs (including non-base64 data URLs) yield `None`.
+pub fn parse_data_url(url: &str) -> Option<(String, String)> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let media_type = header.strip_suffix(";base64")?;
+    if media_type.is_empty() || data.is_empty() {
+        return None;
+    }
+    Some((media_type.to_string(), data.to_string()))
+}
+
 fn single_text_content(openai_content: &[OpenAiUserContentPart]) -> Option<&str> {
     if openai_content.len() != 1 {
         return None;
@@ -64,3 +170,122 @@ fn single_text_content(openai_content: &[OpenAiUserContentPart]) -> Option<&str>
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::conversion::request::{convert_claude_to_openai, convert_claude_to_responses};
+    use crate::models::{ClaudeContent, ClaudeContentBlock, ClaudeMessagesRequest};
+
+    fn history_with_video() -> ClaudeMessagesRequest {
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [
+                {"role": "user", "content": [
+                    {"type": "text", "text": "what happens here?"},
+                    {"type": "video", "source": {"type": "url", "url": "https://example.com/clip.mp4"}}
+                ]},
+                {"role": "assistant", "content": "A cat jumps."},
+                {"role": "user", "content": "and then?"}
+            ]
+        }))
+        .expect("valid request")
+    }
+
+    fn history_with_url_image(detail: Option<&str>) -> ClaudeMessagesRequest {
+        let mut source =
+            json!({"type": "url", "url": "https://example.com/cat.png?size=large&v=2"});
+        if let Some(detail) = detail {
+            source["detail"] = json!(detail);
+        }
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [{"role": "user", "content": [
+                {"type": "text", "text": "what is this?"},
+                {"type": "image", "source": source}
+            ]}]
+        }))
+        .expect("valid request")
+    }
+
+    fn first_user_content(payload: &Value) -> &Value {
+        &payload[0]["content"][1]
+    }
+
+    #[test]
+    fn deserializes_video_blocks_in_history() {
+        let request = history_with_video();
+        let Some(ClaudeContent::Blocks(blocks)) = &request.messages[0].content else {
+            panic!("expected content blocks");
+        };
+        let ClaudeContentBlock::Video {
+            source: Some(source),
+            ..
+        } = &blocks[1]
+        else {
+            panic!("expected a video block, got {:?}", blocks[1]);
+        };
+        assert_eq!(source.source_type.as_deref(), Some("url"));
+        assert_eq!(source.url.as_deref(), Some("https://example.com/clip.mp4"));
+    }
+
+    #[test]
+    fn converts_video_to_video_url_or_placeholder() {
+        let request = history_with_video();
+        let mut config = crate::upstream::test_support::test_config();
+
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config).messages)
+            .expect("serialize messages");
+        assert_eq!(
+            first_user_content(&payload),
+            &json!({"type": "video_url", "video_url": {"url": "https://example.com/clip.mp4"}})
+        );
+
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &config))
+            .expect("serialize request");
+        assert_eq!(
+            first_user_content(&payload["input"]),
+            &json!({"type": "input_video", "video_url": "https://example.com/clip.mp4"})
+        );
+
+        config.video_to_text_placeholder = true;
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config).messages)
+            .expect("serialize messages");
+        assert_eq!(
+            first_user_content(&payload),
+            &json!({"type": "text", "text": "[Video: https://example.com/clip.mp4]"})
+        );
+    }
+
+    #[test]
+    fn forwards_url_images_verbatim_with_optional_detail() {
+        let config = crate::upstream::test_support::test_config();
+        let url = "https://example.com/cat.png?size=large&v=2";
+
+        let request = history_with_url_image(None);
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config).messages)
+            .expect("serialize messages");
+        assert_eq!(
+            first_user_content(&payload),
+            &json!({"type": "image_url", "image_url": {"url": url}})
+        );
+
+        let request = history_with_url_image(Some("low"));
+        let payload = serde_json::to_value(convert_claude_to_openai(&request, &config).messages)
+            .expect("serialize messages");
+        assert_eq!(
+            first_user_content(&payload),
+            &json!({"type": "image_url", "image_url": {"url": url, "detail": "low"}})
+        );
+
+        let payload = serde_json::to_value(convert_claude_to_responses(&request, &config))
+            .expect("serialize request");
+        assert_eq!(
+            first_user_content(&payload["input"]),
+            &json!({"type": "input_image", "image_url": url, "detail": "low"})
+        );
+    }
+}
@@ -1,17 +1,21 @@
 use serde::Deserialize;
 use serde_json::Value;
+use tracing::warn;
 
+use crate::conversion::request::is_thinking_requested;
 use crate::models::ClaudeMessagesRequest;
 
-use super::map_finish_reason;
 use super::types::{
-    ClaudeContentBlock, ClaudeResponse, ClaudeUsage, build_claude_response, map_tool_use_block,
-    maybe_push_text, maybe_push_thinking,
+    ClaudeContentBlock, ClaudeResponse, ClaudeUsage, build_claude_response,
+    fold_thinking_into_text, map_tool_use_block, maybe_push_text, maybe_push_thinking,
 };
+use super::{inferred_stop_sequence, map_finish_reason, matched_stop_sequence};
 
 pub(crate) fn convert_openai_to_claude_response(
     openai_response: &OpenAiChatResponse,
     original_request: &ClaudeMessagesRequest,
+    infer_stop_sequence: bool,
+    thinking_as_text: bool,
 ) -> Result<ClaudeResponse, String> {
     let choice = openai_response
         .choices
@@ -25,13 +29,24 @@ pub(crate) fn convert_openai_to_claude_response(
     let mut content_blocks = Vec::new();
     push_message_content(message, &mut content_blocks);
     push_tool_use_content(&message.tool_calls, &mut content_blocks);
+    if thinking_as_text && !is_thinking_requested(original_request.thinking.as_ref()) {
+        fold_thinking_into_text(&mut content_blocks);
+    }
 
-    let stop_reason = map_finish_reason(choice.finish_reason.as_deref().unwrap_or("stop"));
+    let finish_reason = choice.finish_reason.as_deref().unwrap_or("stop");
+    let inferred = inferred_stop_sequence(original_request, infer_stop_sequence);
+    let stop_sequence = matched_stop_sequence(
+        finish_reason,
+        choice.stop_reason.as_ref(),
+        inferred.as_deref(),
+    );
+    let stop_reason = map_finish_reason(finish_reason, stop_sequence.as_deref());
     Ok(build_claude_response(
         openai_response.id.clone(),
         original_request.model.clone(),
         content_blocks,
         stop_reason,
+        stop_sequence,
         usage_from_chat(openai_response.usage.as_ref()),
     ))
 }
@@ -40,16 +55,23 @@ fn push_message_content(
     message: &OpenAiResponseMessage,
     content_blocks: &mut Vec<ClaudeContentBlock>,
 ) {
+    if let Some(refusal) = refusal_without_content(message) {
+        warn!(
+            phase = "upstream_refusal",
+            refusal_len = refusal.len(),
+            "Upstream model refused; mapping refusal to text block"
+        );
+        maybe_push_text(content_blocks, Some(refusal));
+    }
+
     match message.content.as_ref() {
         Some(OpenAiResponseContent::Text(text)) => maybe_push_text(content_blocks, Some(text)),
-        Some(OpenAiResponseContent::Other(content_json)) => {
-            if !content_json.is_null() {
-                content_blocks.push(ClaudeContentBlock::Text {
-                    text: content_json.to_string(),
-                });
-            }
+        Some(OpenAiResponseContent::Other(content_json)) if !content_json.is_null() => {
+            content_blocks.push(ClaudeContentBlock::Text {
+                text: content_json.to_string(),
+            });
         }
-        None => {}
+        Some(OpenAiResponseContent::Other(_)) | None => {}
     }
     maybe_push_thinking(
         content_blocks,
@@ -61,6 +83,19 @@ fn push_message_content(
     );
 }
 
+fn refusal_without_content(message: &OpenAiResponseMessage) -> Option<&str> {
+    let refusal = message
+        .refusal
+        .as_deref()
+        .filter(|value| !value.is_empty())?;
+    let has_content = match message.content.as_ref() {
+        Some(OpenAiResponseContent::Text(text)) => !text.is_empty(),
+        Some(OpenAiResponseContent::Other(value)) => !value.is_null(),
+        None => false,
+    };
+    (!has_content).then_some(refusal)
+}
+
 fn push_tool_use_content(
     tool_calls: &[OpenAiResponseToolCall],
     content_blocks: &mut Vec<ClaudeContentBlock>,
@@ -85,6 +120,11 @@ fn usage_from_chat(usage: Option<&OpenAiUsage>) -> ClaudeUsage {
     ClaudeUsage {
         input_tokens: usage.and_then(|value| value.prompt_tokens).unwrap_or(0),
         output_tokens: usage.and_then(|value| value.completion_tokens).unwrap_or(0),
+        cache_read_input_tokens: usage
+            .and_then(|value| value.prompt_tokens_details.as_ref())
+            .and_then(|details| details.cached_tokens)
+            .filter(|tokens| *tokens > 0),
+        cache_creation_input_tokens: None,
     }
 }
 
@@ -101,17 +141,19 @@ impl OpenAiChatResponse {
         self.id.as_deref()
     }
 
-    pub(crate) fn total_tokens(&self) -> u64 {
+    /// `(input, output)` tokens, zero when the upstream omitted usage.
+    pub(crate) fn token_counts(&self) -> (u64, u64) {
         self.usage
             .as_ref()
-            .map(OpenAiUsage::total_tokens)
-            .unwrap_or(0)
+            .map(OpenAiUsage::token_counts)
+            .unwrap_or_default()
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct OpenAiChoice {
     finish_reason: Option<String>,
+    stop_reason: Option<Value>,
     message: Option<OpenAiResponseMessage>,
 }
 
@@ -120,6 +162,7 @@ struct OpenAiResponseMessage {
     content: Option<OpenAiResponseContent>,
     reasoning_content: Option<String>,
     reasoning: Option<String>,
+    refusal: Option<String>,
     signature: Option<String>,
     #[serde(default)]
     tool_calls: Vec<OpenAiResponseToolCall>,
@@ -150,13 +193,20 @@ struct OpenAiFunctionPayload {
 struct OpenAiUsage {
     prompt_tokens: Option<u64>,
     completion_tokens: Option<u64>,
+    prompt_tokens_details: Option<OpenAiTokenDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiTokenDetails {
+    cached_tokens: Option<u64>,
 }
 
 impl OpenAiUsage {
-    fn total_tokens(&self) -> u64 {
-        self.prompt_tokens
-            .unwrap_or(0)
-            .saturating_add(self.completion_tokens.unwrap_or(0))
+    fn token_counts(&self) -> (u64, u64) {
+        (
+            self.prompt_tokens.unwrap_or(0),
+            self.completion_tokens.unwrap_or(0),
+        )
     }
 }
 
@@ -168,19 +218,14 @@ mod tests {
     use crate::models::ClaudeMessagesRequest;
 
     fn empty_request() -> ClaudeMessagesRequest {
-        ClaudeMessagesRequest {
-            model: "claude-3-5-sonnet-20241022".to_string(),
-            max_tokens: 256,
-            messages: vec![],
-            thinking: None,
-            system: None,
-            stop_sequences: None,
-            stream: Some(false),
-            temperature: Some(1.0),
-            top_p: None,
-            tools: None,
-            tool_choice: None,
-        }
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [],
+            "stream": false,
+            "temperature": 1.0
+        }))
+        .expect("valid request")
     }
 
     #[test]
@@ -205,7 +250,7 @@ mod tests {
 
         let parsed: OpenAiChatResponse =
             serde_json::from_value(openai_response).expect("response should deserialize");
-        let converted = convert_openai_to_claude_response(&parsed, &empty_request())
+        let converted = convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
             .expect("conversion should succeed");
 
         let payload = serde_json::to_value(converted).expect("serialize");
@@ -218,6 +263,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn maps_cached_prompt_tokens_to_cache_read_usage() {
+        let openai_response = json!({
+            "id": "chatcmpl_cached",
+            "choices": [{"finish_reason": "stop", "message": {"content": "hi"}}],
+            "usage": {
+                "prompt_tokens": 20,
+                "completion_tokens": 5,
+                "prompt_tokens_details": {"cached_tokens": 16}
+            }
+        });
+
+        let parsed: OpenAiChatResponse =
+            serde_json::from_value(openai_response).expect("response should deserialize");
+        let converted = convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
+            .expect("conversion should succeed");
+        let payload = serde_json::to_value(converted).expect("serialize");
+
+        assert_eq!(
+            payload["usage"],
+            json!({"input_tokens": 20, "output_tokens": 5, "cache_read_input_tokens": 16})
+        );
+    }
+
+    #[test]
+    fn omits_cache_usage_when_upstream_reports_none() {
+        let openai_response = json!({
+            "id": "chatcmpl_uncached",
+            "choices": [{"finish_reason": "stop", "message": {"content": "hi"}}],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 2}
+        });
+
+        let parsed: OpenAiChatResponse =
+            serde_json::from_value(openai_response).expect("response should deserialize");
+        let converted = convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
+            .expect("conversion should succeed");
+        let payload = serde_json::to_value(converted).expect("serialize");
+
+        assert_eq!(
+            payload["usage"],
+            json!({"input_tokens": 3, "output_tokens": 2})
+        );
+    }
+
+    fn stop_response(stop_reason: Value) -> OpenAiChatResponse {
+        serde_json::from_value(json!({
+            "id": "chatcmpl_stop",
+            "choices": [{
+                "finish_reason": "stop",
+                "stop_reason": stop_reason,
+                "message": {"content": "done"}
+            }]
+        }))
+        .expect("response should deserialize")
+    }
+
+    #[test]
+    fn infers_single_stop_sequence_when_enabled() {
+        let mut request = empty_request();
+        request.stop_sequences = Some(vec!["###".to_string()]);
+        let parsed = stop_response(Value::Null);
+
+        let inferred = serde_json::to_value(
+            convert_openai_to_claude_response(&parsed, &request, true, false).expect("convert"),
+        )
+        .expect("serialize");
+        assert_eq!(inferred["stop_reason"], json!("stop_sequence"));
+        assert_eq!(inferred["stop_sequence"], json!("###"));
+
+        let disabled = serde_json::to_value(
+            convert_openai_to_claude_response(&parsed, &request, false, false).expect("convert"),
+        )
+        .expect("serialize");
+        assert_eq!(disabled["stop_reason"], json!("end_turn"));
+        assert_eq!(disabled["stop_sequence"], Value::Null);
+    }
+
+    #[test]
+    fn does_not_infer_with_multiple_stop_sequences() {
+        let mut request = empty_request();
+        request.stop_sequences = Some(vec!["###".to_string(), "END".to_string()]);
+        let parsed = stop_response(Value::Null);
+
+        let payload = serde_json::to_value(
+            convert_openai_to_claude_response(&parsed, &request, true, false).expect("convert"),
+        )
+        .expect("serialize");
+        assert_eq!(payload["stop_reason"], json!("end_turn"));
+        assert_eq!(payload["stop_sequence"], Value::Null);
+    }
+
+    #[test]
+    fn uses_upstream_reported_stop_string() {
+        let parsed = stop_response(json!("END"));
+
+        let payload = serde_json::to_value(
+            convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
+                .expect("convert"),
+        )
+        .expect("serialize");
+        assert_eq!(payload["stop_reason"], json!("stop_sequence"));
+        assert_eq!(payload["stop_sequence"], json!("END"));
+    }
+
     #[test]
     fn skips_non_function_tool_call_type() {
         let openai_response = json!({
@@ -241,7 +390,7 @@ mod tests {
 
         let parsed: OpenAiChatResponse =
             serde_json::from_value(openai_response).expect("response should deserialize");
-        let converted = convert_openai_to_claude_response(&parsed, &empty_request())
+        let converted = convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
             .expect("conversion should succeed");
 
         let payload = serde_json::to_value(converted).expect("serialize");
@@ -249,9 +398,11 @@ mod tests {
             .get("content")
             .and_then(Value::as_array)
             .expect("content array");
-        assert!(content
-            .iter()
-            .all(|block| block.get("type").and_then(Value::as_str) != Some("tool_use")));
+        assert!(
+            content
+                .iter()
+                .all(|block| block.get("type").and_then(Value::as_str) != Some("tool_use"))
+        );
     }
 
     #[test]
@@ -277,7 +428,7 @@ mod tests {
 
         let parsed: OpenAiChatResponse =
             serde_json::from_value(openai_response).expect("response should deserialize");
-        let converted = convert_openai_to_claude_response(&parsed, &empty_request())
+        let converted = convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
             .expect("conversion should succeed");
 
         let payload = serde_json::to_value(converted).expect("serialize");
@@ -310,7 +461,7 @@ mod tests {
 
         let parsed: OpenAiChatResponse =
             serde_json::from_value(openai_response).expect("response should deserialize");
-        let converted = convert_openai_to_claude_response(&parsed, &empty_request())
+        let converted = convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
             .expect("conversion should succeed");
 
         let payload = serde_json::to_value(converted).expect("serialize");
@@ -324,4 +475,147 @@ mod tests {
             Some("thinking")
         );
     }
+
+    #[test]
+    fn folds_unrequested_reasoning_into_text_when_enabled() {
+        let openai_response = json!({
+            "id": "chatcmpl_test",
+            "choices": [{
+                "finish_reason": "stop",
+                "message": {"content": "done", "reasoning_content": "step by step"}
+            }]
+        });
+        let parsed: OpenAiChatResponse =
+            serde_json::from_value(openai_response).expect("response should deserialize");
+
+        let payload = serde_json::to_value(
+            convert_openai_to_claude_response(&parsed, &empty_request(), false, true)
+                .expect("convert"),
+        )
+        .expect("serialize");
+        assert_eq!(
+            payload["content"],
+            json!([{"type": "text", "text": "<thinking>\nstep by step\n</thinking>\n\ndone"}])
+        );
+
+        let mut request = empty_request();
+        request.thinking = serde_json::from_value(json!({"type": "enabled"})).ok();
+        let payload = serde_json::to_value(
+            convert_openai_to_claude_response(&parsed, &request, false, true).expect("convert"),
+        )
+        .expect("serialize");
+        assert_eq!(payload["content"][1]["type"], json!("thinking"));
+    }
+
+    #[test]
+    fn maps_refusal_to_text_block_when_content_missing() {
+        let openai_response = json!({
+            "id": "chatcmpl_test",
+            "choices": [{
+                "finish_reason": "stop",
+                "message": {
+                    "content": null,
+                    "refusal": "I can't help with that."
+                }
+            }],
+            "usage": {"prompt_tokens": 1, "completion_tokens": 1}
+        });
+
+        let parsed: OpenAiChatResponse =
+            serde_json::from_value(openai_response).expect("response should deserialize");
+        let converted = convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
+            .expect("conversion should succeed");
+
+        let payload = serde_json::to_value(converted).expect("serialize");
+        let content = payload
+            .get("content")
+            .and_then(Value::as_array)
+            .expect("content array");
+        assert_eq!(content.len(), 1);
+        assert_eq!(
+            content[0].get("text").and_then(Value::as_str),
+            Some("I can't help with that.")
+        );
+    }
+
+    #[test]
+    fn fills_only_empty_responses_with_fallback_text() {
+        let convert = |message: Value| {
+            let parsed: OpenAiChatResponse = serde_json::from_value(json!({
+                "id": "chatcmpl_test",
+                "choices": [{"finish_reason": "stop", "message": message}]
+            }))
+            .expect("response should deserialize");
+            let mut converted =
+                convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
+                    .expect("conversion should succeed");
+            converted.fill_empty_content("Please try again.");
+            serde_json::to_value(converted).expect("serialize")["content"].clone()
+        };
+
+        assert_eq!(
+            convert(json!({"content": null})),
+            json!([{"type": "text", "text": "Please try again."}])
+        );
+        assert_eq!(
+            convert(json!({"content": "done"})),
+            json!([{"type": "text", "text": "done"}])
+        );
+    }
+
+    #[test]
+    fn content_filter_finish_is_resolved_to_replacement_text() {
+        let openai_response = json!({
+            "id": "chatcmpl_test",
+            "choices": [{
+                "finish_reason": "content_filter",
+                "message": {"content": "partial"}
+            }]
+        });
+
+        let parsed: OpenAiChatResponse =
+            serde_json::from_value(openai_response).expect("response should deserialize");
+        let mut converted =
+            convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
+                .expect("conversion should succeed");
+        assert!(converted.is_content_filtered());
+
+        converted.replace_filtered_content("blocked");
+        let payload = serde_json::to_value(converted).expect("serialize");
+        assert_eq!(payload["stop_reason"], json!("end_turn"));
+        assert_eq!(
+            payload["content"],
+            json!([{"type": "text", "text": "blocked"}])
+        );
+    }
+
+    #[test]
+    fn ignores_refusal_when_content_present() {
+        let openai_response = json!({
+            "id": "chatcmpl_test",
+            "choices": [{
+                "finish_reason": "stop",
+                "message": {
+                    "content": "partial answer",
+                    "refusal": "refused"
+                }
+            }]
+        });
+
+        let parsed: OpenAiChatResponse =
+            serde_json::from_value(openai_response).expect("response should deserialize");
+        let converted = convert_openai_to_claude_response(&parsed, &empty_request(), false, false)
+            .expect("conversion should succeed");
+
+        let payload = serde_json::to_value(converted).expect("serialize");
+        let content = payload
+            .get("content")
+            .and_then(Value::as_array)
+            .expect("content array");
+        assert_eq!(content.len(), 1);
+        assert_eq!(
+            content[0].get("text").and_then(Value::as_str),
+            Some("partial answer")
+        );
+    }
 }
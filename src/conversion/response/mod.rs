@@ -3,22 +3,66 @@ mod responses;
 mod types;
 
 pub(crate) use chat::{OpenAiChatResponse, convert_openai_to_claude_response};
-pub(crate) use responses::{OpenAiResponsesResponse, convert_openai_responses_to_claude_response};
+pub(crate) use responses::{
+    OpenAiResponsesResponse, convert_openai_responses_to_claude_response, reasoning_signature,
+};
+pub(crate) use types::{ClaudeResponse, build_dry_run_response, build_synthetic_response};
 
-use crate::constants::{STOP_END_TURN, STOP_MAX_TOKENS, STOP_TOOL_USE};
+use serde_json::Value;
 
-pub fn map_finish_reason(finish_reason: &str) -> &'static str {
+use crate::constants::{
+    STOP_CONTENT_FILTERED, STOP_END_TURN, STOP_MAX_TOKENS, STOP_SEQUENCE, STOP_TOOL_USE,
+};
+use crate::models::ClaudeMessagesRequest;
+
+pub fn map_finish_reason(finish_reason: &str, matched_stop: Option<&str>) -> &'static str {
     match finish_reason {
+        "stop" if matched_stop.is_some() => STOP_SEQUENCE,
         "length" => STOP_MAX_TOKENS,
         "tool_calls" | "function_call" => STOP_TOOL_USE,
+        "content_filter" => STOP_CONTENT_FILTERED,
         _ => STOP_END_TURN,
     }
 }
 
+/// The single requested stop sequence, when `infer_stop_sequence` is enabled
+/// and the request leaves no doubt which sequence a `stop` finish matched.
+pub fn inferred_stop_sequence(
+    request: &ClaudeMessagesRequest,
+    infer_stop_sequence: bool,
+) -> Option<String> {
+    if !infer_stop_sequence {
+        return None;
+    }
+    match request.stop_sequences.as_deref() {
+        Some([single]) => Some(single.clone()),
+        _ => None,
+    }
+}
+
+/// Some OpenAI-compatible servers (e.g. vLLM) report the matched stop string in
+/// `choices[].stop_reason`; otherwise falls back to the inferred sequence.
+pub fn matched_stop_sequence(
+    finish_reason: &str,
+    upstream_stop_reason: Option<&Value>,
+    inferred: Option<&str>,
+) -> Option<String> {
+    if finish_reason != "stop" {
+        return None;
+    }
+    if let Some(Value::String(matched)) = upstream_stop_reason
+        && !matched.is_empty()
+    {
+        return Some(matched.clone());
+    }
+    inferred.map(str::to_string)
+}
+
 pub fn map_responses_incomplete_reason(reason: Option<&str>) -> &'static str {
     match reason {
         Some("max_output_tokens") => STOP_MAX_TOKENS,
         Some("tool_use") | Some("function_call") => STOP_TOOL_USE,
+        Some("content_filter") => STOP_CONTENT_FILTERED,
         _ => STOP_END_TURN,
     }
 }
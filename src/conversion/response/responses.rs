@@ -1,18 +1,21 @@
 use serde::Deserialize;
 use serde_json::Value;
+use tracing::warn;
 
 use crate::constants::TOOL_FUNCTION;
+use crate::conversion::request::is_thinking_requested;
 use crate::models::ClaudeMessagesRequest;
 
 use super::map_responses_incomplete_reason;
 use super::types::{
-    ClaudeContentBlock, ClaudeResponse, ClaudeUsage, build_claude_response, map_tool_use_block,
-    maybe_push_text, maybe_push_thinking,
+    ClaudeContentBlock, ClaudeResponse, ClaudeUsage, build_claude_response,
+    fold_thinking_into_text, map_tool_use_block, maybe_push_text, maybe_push_thinking,
 };
 
 pub(crate) fn convert_openai_responses_to_claude_response(
     responses: &OpenAiResponsesResponse,
     original_request: &ClaudeMessagesRequest,
+    thinking_as_text: bool,
 ) -> Result<ClaudeResponse, String> {
     if responses.output.is_empty() && responses.output_text.is_none() {
         return Err("missing output in upstream responses payload".to_string());
@@ -25,6 +28,9 @@ pub(crate) fn convert_openai_responses_to_claude_response(
         saw_tool_use |= append_output_item(item, &mut content_blocks);
     }
     append_output_text_fallback(responses, &mut content_blocks);
+    if thinking_as_text && !is_thinking_requested(original_request.thinking.as_ref()) {
+        fold_thinking_into_text(&mut content_blocks);
+    }
 
     let stop_reason = resolve_stop_reason(responses, saw_tool_use);
     Ok(build_claude_response(
@@ -32,6 +38,7 @@ pub(crate) fn convert_openai_responses_to_claude_response(
         original_request.model.clone(),
         content_blocks,
         stop_reason,
+        None,
         usage_from_responses(responses.usage.as_ref()),
     ))
 }
@@ -64,13 +71,28 @@ fn append_message_item(item: &Value, content_blocks: &mut Vec<ClaudeContentBlock
                 .get("refusal")
                 .and_then(Value::as_str)
                 .or_else(|| part.get("text").and_then(Value::as_str));
+            warn!(
+                phase = "upstream_refusal",
+                refusal_len = refusal_text.map(str::len).unwrap_or(0),
+                "Upstream model refused; mapping refusal to text block"
+            );
             maybe_push_text(content_blocks, refusal_text);
         }
     }
 }
 
+/// The opaque reasoning state to hand back as the thinking signature; OpenAI
+/// returns it as `encrypted_content` when `include` asks for it.
+pub(crate) fn reasoning_signature(item: &Value) -> Option<&str> {
+    item.get("signature")
+        .or_else(|| item.get("encrypted_content"))
+        .and_then(Value::as_str)
+        .filter(|signature| !signature.is_empty())
+}
+
 fn append_reasoning_item(item: &Value, content_blocks: &mut Vec<ClaudeContentBlock>) {
-    let signature = item.get("signature").and_then(Value::as_str);
+    let signature = reasoning_signature(item);
+    let blocks_before = content_blocks.len();
 
     if let Some(summary) = item.get("summary").and_then(Value::as_array) {
         for summary_item in summary {
@@ -87,6 +109,16 @@ fn append_reasoning_item(item: &Value, content_blocks: &mut Vec<ClaudeContentBlo
         .and_then(Value::as_str)
         .or_else(|| item.get("reasoning").and_then(Value::as_str));
     maybe_push_thinking(content_blocks, text, signature);
+
+    // Encrypted reasoning often comes without a summary; keep the blob anyway.
+    if content_blocks.len() == blocks_before
+        && let Some(signature) = signature
+    {
+        content_blocks.push(ClaudeContentBlock::Thinking {
+            thinking: String::new(),
+            signature: signature.to_string(),
+        });
+    }
 }
 
 fn append_function_call(item: &Value, content_blocks: &mut Vec<ClaudeContentBlock>) -> bool {
@@ -139,6 +171,11 @@ fn usage_from_responses(usage: Option<&OpenAiResponsesUsage>) -> ClaudeUsage {
     ClaudeUsage {
         input_tokens: usage.and_then(|value| value.input_tokens).unwrap_or(0),
         output_tokens: usage.and_then(|value| value.output_tokens).unwrap_or(0),
+        cache_read_input_tokens: usage
+            .and_then(|value| value.input_tokens_details.as_ref())
+            .and_then(|details| details.cached_tokens)
+            .filter(|tokens| *tokens > 0),
+        cache_creation_input_tokens: None,
     }
 }
 
@@ -197,11 +234,12 @@ impl OpenAiResponsesResponse {
         self.id.as_deref()
     }
 
-    pub(crate) fn total_tokens(&self) -> u64 {
+    /// `(input, output)` tokens, zero when the upstream omitted usage.
+    pub(crate) fn token_counts(&self) -> (u64, u64) {
         self.usage
             .as_ref()
-            .map(OpenAiResponsesUsage::total_tokens)
-            .unwrap_or(0)
+            .map(OpenAiResponsesUsage::token_counts)
+            .unwrap_or_default()
     }
 }
 
@@ -209,13 +247,20 @@ impl OpenAiResponsesResponse {
 struct OpenAiResponsesUsage {
     input_tokens: Option<u64>,
     output_tokens: Option<u64>,
+    input_tokens_details: Option<OpenAiResponsesTokenDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiResponsesTokenDetails {
+    cached_tokens: Option<u64>,
 }
 
 impl OpenAiResponsesUsage {
-    fn total_tokens(&self) -> u64 {
-        self.input_tokens
-            .unwrap_or(0)
-            .saturating_add(self.output_tokens.unwrap_or(0))
+    fn token_counts(&self) -> (u64, u64) {
+        (
+            self.input_tokens.unwrap_or(0),
+            self.output_tokens.unwrap_or(0),
+        )
     }
 }
 
@@ -227,19 +272,14 @@ mod tests {
     use crate::models::ClaudeMessagesRequest;
 
     fn empty_request() -> ClaudeMessagesRequest {
-        ClaudeMessagesRequest {
-            model: "claude-3-5-sonnet-20241022".to_string(),
-            max_tokens: 256,
-            messages: vec![],
-            thinking: None,
-            system: None,
-            stop_sequences: None,
-            stream: Some(false),
-            temperature: Some(1.0),
-            top_p: None,
-            tools: None,
-            tool_choice: None,
-        }
+        serde_json::from_value(json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [],
+            "stream": false,
+            "temperature": 1.0
+        }))
+        .expect("valid request")
     }
 
     #[test]
@@ -256,8 +296,9 @@ mod tests {
         });
 
         let parsed: OpenAiResponsesResponse = serde_json::from_value(payload).expect("deserialize");
-        let converted = convert_openai_responses_to_claude_response(&parsed, &empty_request())
-            .expect("convert");
+        let converted =
+            convert_openai_responses_to_claude_response(&parsed, &empty_request(), false)
+                .expect("convert");
         let json = serde_json::to_value(converted).expect("serialize");
         let content = json
             .get("content")
@@ -270,6 +311,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn maps_cached_input_tokens_to_cache_read_usage() {
+        let payload = json!({
+            "id": "resp_4",
+            "status": "completed",
+            "output": [{"type":"message","content":[{"type":"output_text","text":"hi"}]}],
+            "usage": {"input_tokens": 20, "output_tokens": 5, "input_tokens_details": {"cached_tokens": 16}}
+        });
+
+        let parsed: OpenAiResponsesResponse = serde_json::from_value(payload).expect("deserialize");
+        let converted =
+            convert_openai_responses_to_claude_response(&parsed, &empty_request(), false)
+                .expect("convert");
+        let json = serde_json::to_value(converted).expect("serialize");
+
+        assert_eq!(
+            json["usage"],
+            json!({"input_tokens": 20, "output_tokens": 5, "cache_read_input_tokens": 16})
+        );
+    }
+
     #[test]
     fn maps_incomplete_reason_to_max_tokens() {
         let payload = json!({
@@ -280,8 +342,9 @@ mod tests {
         });
 
         let parsed: OpenAiResponsesResponse = serde_json::from_value(payload).expect("deserialize");
-        let converted = convert_openai_responses_to_claude_response(&parsed, &empty_request())
-            .expect("convert");
+        let converted =
+            convert_openai_responses_to_claude_response(&parsed, &empty_request(), false)
+                .expect("convert");
         let json = serde_json::to_value(converted).expect("serialize");
 
         assert_eq!(
@@ -289,4 +352,55 @@ mod tests {
             Some("max_tokens")
         );
     }
+
+    #[test]
+    fn maps_refusal_part_to_text_block() {
+        let payload = json!({
+            "id": "resp_4",
+            "status": "completed",
+            "output": [{
+                "type": "message",
+                "content": [{"type": "refusal", "refusal": "I can't help with that."}]
+            }]
+        });
+
+        let parsed: OpenAiResponsesResponse = serde_json::from_value(payload).expect("deserialize");
+        let converted =
+            convert_openai_responses_to_claude_response(&parsed, &empty_request(), false)
+                .expect("convert");
+        let json = serde_json::to_value(converted).expect("serialize");
+        let content = json
+            .get("content")
+            .and_then(Value::as_array)
+            .expect("content array");
+
+        assert_eq!(
+            content[0].get("text").and_then(Value::as_str),
+            Some("I can't help with that.")
+        );
+    }
+
+    #[test]
+    fn maps_encrypted_reasoning_to_thinking_signature() {
+        let payload = json!({
+            "id": "resp_5",
+            "status": "completed",
+            "output": [
+                {"type": "reasoning", "summary": [], "encrypted_content": "gAAAAB-opaque"},
+                {"type": "message", "content": [{"type": "output_text", "text": "done"}]}
+            ]
+        });
+
+        let parsed: OpenAiResponsesResponse = serde_json::from_value(payload).expect("deserialize");
+        let converted =
+            convert_openai_responses_to_claude_response(&parsed, &empty_request(), false)
+                .expect("convert");
+        let json = serde_json::to_value(converted).expect("serialize");
+
+        assert_eq!(
+            json["content"][0],
+            json!({"type": "thinking", "thinking": "", "signature": "gAAAAB-opaque"})
+        );
+        assert_eq!(json["content"][1]["text"], json!("done"));
+    }
 }
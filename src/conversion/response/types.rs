@@ -3,7 +3,7 @@ use serde_json::Value;
 use tracing::warn;
 use uuid::Uuid;
 
-use crate::constants::{ROLE_ASSISTANT, TOOL_FUNCTION};
+use crate::constants::{ROLE_ASSISTANT, STOP_CONTENT_FILTERED, STOP_END_TURN, TOOL_FUNCTION};
 
 #[derive(Debug, Serialize)]
 pub(crate) struct ClaudeResponse {
@@ -18,10 +18,43 @@ pub(crate) struct ClaudeResponse {
     usage: ClaudeUsage,
 }
 
+impl ClaudeResponse {
+    pub(crate) fn usage(&self) -> &ClaudeUsage {
+        &self.usage
+    }
+
+    /// Swaps the empty text block `build_claude_response` inserts for
+    /// `text`, leaving responses with any real content untouched.
+    pub(crate) fn fill_empty_content(&mut self, text: &str) {
+        if let [ClaudeContentBlock::Text { text: existing }] = self.content.as_mut_slice()
+            && existing.is_empty()
+        {
+            *existing = text.to_string();
+        }
+    }
+
+    pub(crate) fn is_content_filtered(&self) -> bool {
+        self.stop_reason == STOP_CONTENT_FILTERED
+    }
+
+    /// Replaces whatever the content filter let through with `text` and
+    /// reports `end_turn`.
+    pub(crate) fn replace_filtered_content(&mut self, text: &str) {
+        self.content = vec![ClaudeContentBlock::Text {
+            text: text.to_string(),
+        }];
+        self.stop_reason = STOP_END_TURN.to_string();
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct ClaudeUsage {
     pub input_tokens: u64,
     pub output_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -44,6 +77,7 @@ pub(crate) fn build_claude_response(
     model: String,
     mut content: Vec<ClaudeContentBlock>,
     stop_reason: &str,
+    stop_sequence: Option<String>,
     usage: ClaudeUsage,
 ) -> ClaudeResponse {
     ensure_non_empty_content(&mut content);
@@ -54,11 +88,35 @@ pub(crate) fn build_claude_response(
         model,
         content,
         stop_reason: stop_reason.to_string(),
-        stop_sequence: None,
+        stop_sequence,
         usage,
     }
 }
 
+pub(crate) fn build_dry_run_response(model: &str) -> ClaudeResponse {
+    build_synthetic_response(model, "")
+}
+
+/// A zero-usage `end_turn` response with a single text block, for replies
+/// the bridge produces without a usable upstream answer.
+pub(crate) fn build_synthetic_response(model: &str, text: &str) -> ClaudeResponse {
+    build_claude_response(
+        None,
+        model.to_string(),
+        vec![ClaudeContentBlock::Text {
+            text: text.to_string(),
+        }],
+        STOP_END_TURN,
+        None,
+        ClaudeUsage {
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_input_tokens: None,
+            cache_creation_input_tokens: None,
+        },
+    )
+}
+
 fn ensure_non_empty_content(content_blocks: &mut Vec<ClaudeContentBlock>) {
     if content_blocks.is_empty() {
         content_blocks.push(ClaudeContentBlock::Text {
@@ -97,6 +155,39 @@ pub(crate) fn maybe_push_thinking(
     });
 }
 
+/// Replaces thinking blocks with a `<thinking>…</thinking>` prefix on the
+/// first text block, for clients that did not ask for extended thinking.
+pub(crate) fn fold_thinking_into_text(content_blocks: &mut Vec<ClaudeContentBlock>) {
+    let mut thinking_parts = Vec::new();
+    content_blocks.retain(|block| match block {
+        ClaudeContentBlock::Thinking { thinking, .. } => {
+            thinking_parts.push(thinking.trim().to_string());
+            false
+        }
+        _ => true,
+    });
+    if thinking_parts.is_empty() {
+        return;
+    }
+
+    let prefix = format!(
+        "<thinking>\n{}\n</thinking>\n\n",
+        thinking_parts.join("\n\n")
+    );
+    match content_blocks.iter_mut().find_map(|block| match block {
+        ClaudeContentBlock::Text { text } => Some(text),
+        _ => None,
+    }) {
+        Some(text) => text.insert_str(0, &prefix),
+        None => content_blocks.insert(
+            0,
+            ClaudeContentBlock::Text {
+                text: prefix.trim_end().to_string(),
+            },
+        ),
+    }
+}
+
 pub(crate) fn map_tool_use_block(
     id: Option<&str>,
     kind: Option<&str>,
@@ -0,0 +1,66 @@
+use salvo::http::body::BodySender;
+
+use crate::conversion::stream::pipeline::message_id;
+use crate::conversion::stream::sse::{send_start_sequence, send_stop_sequence, send_text_delta};
+use crate::conversion::stream::state::StreamState;
+
+/// Emits the shortest valid Claude SSE sequence: one empty text block that
+/// is opened and closed, then `end_turn` with zero usage.
+pub async fn stream_dry_run_sse(sender: BodySender, original_model: String) {
+    stream_text_sse(sender, original_model, String::new()).await;
+}
+
+/// The same sequence with `text` as the block's only delta, for replies the
+/// bridge produces without an upstream stream.
+pub async fn stream_text_sse(mut sender: BodySender, original_model: String, text: String) {
+    let state = StreamState::new(false);
+    if send_start_sequence(&mut sender, &original_model, &message_id())
+        .await
+        .is_err()
+    {
+        return;
+    }
+    if !text.is_empty() && send_text_delta(&mut sender, &state, &text).await.is_err() {
+        return;
+    }
+    let _ = send_stop_sequence(&mut sender, &state).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stream_dry_run_sse;
+    use futures_util::StreamExt;
+    use salvo::http::body::ResBody;
+
+    #[tokio::test]
+    async fn emits_minimal_event_sequence() {
+        let (sender, body) = ResBody::channel();
+        let task = tokio::spawn(stream_dry_run_sse(sender, "claude-3-5-sonnet".to_string()));
+
+        let mut output = String::new();
+        let mut body = body;
+        while let Some(Ok(frame)) = body.next().await {
+            if let Ok(data) = frame.into_data() {
+                output.push_str(&String::from_utf8_lossy(&data));
+            }
+        }
+        task.await.expect("task");
+
+        let events: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                "message_start",
+                "content_block_start",
+                "ping",
+                "content_block_stop",
+                "message_delta",
+                "message_stop"
+            ]
+        );
+        assert!(output.contains(r#""stop_reason":"end_turn""#));
+    }
+}
@@ -0,0 +1,73 @@
+use salvo::http::body::BodySender;
+use tracing::warn;
+
+use crate::constants::{STOP_CONTENT_FILTERED, STOP_END_TURN};
+use crate::conversion::stream::sse::{send_error_sse, send_stop_sequence, send_text_delta};
+use crate::conversion::stream::state::StreamState;
+use crate::errors::CONTENT_FILTER_ERROR_MESSAGE;
+
+/// Closes the stream. A `content_filter` finish is resolved first: an
+/// `error` event under `content_filter_mode = "error"`, otherwise the
+/// replacement text followed by `end_turn`.
+pub async fn send_final_sequence(
+    sender: &mut BodySender,
+    state: &mut StreamState,
+) -> std::io::Result<()> {
+    if state.final_stop_reason == STOP_CONTENT_FILTERED {
+        warn!(
+            phase = "content_filtered",
+            "Upstream filtered the streamed response"
+        );
+        let Some(text) = state.content_filter_text.take() else {
+            return send_error_sse(sender, CONTENT_FILTER_ERROR_MESSAGE).await;
+        };
+        if !text.is_empty() {
+            send_text_delta(sender, state, &text).await?;
+        }
+        state.final_stop_reason = STOP_END_TURN.to_string();
+    }
+    send_stop_sequence(sender, state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use salvo::http::ResBody;
+
+    use super::send_final_sequence;
+    use crate::constants::STOP_CONTENT_FILTERED;
+    use crate::conversion::stream::state::StreamState;
+
+    async fn collect_final_sequence(content_filter_text: Option<&str>) -> String {
+        let mut state = StreamState::new(false);
+        state.final_stop_reason = STOP_CONTENT_FILTERED.to_string();
+        state.content_filter_text = content_filter_text.map(str::to_string);
+        let (mut sender, mut body) = ResBody::channel();
+        tokio::spawn(async move {
+            let _ = send_final_sequence(&mut sender, &mut state).await;
+        });
+
+        let mut output = String::new();
+        while let Some(Ok(frame)) = body.next().await {
+            if let Ok(data) = frame.into_data() {
+                output.push_str(&String::from_utf8_lossy(&data));
+            }
+        }
+        output
+    }
+
+    #[tokio::test]
+    async fn content_filter_finish_sends_replacement_and_end_turn() {
+        let output = collect_final_sequence(Some("blocked")).await;
+        assert!(output.contains(r#""text":"blocked""#));
+        assert!(output.contains(r#""stop_reason":"end_turn""#));
+        assert!(!output.contains(STOP_CONTENT_FILTERED));
+    }
+
+    #[tokio::test]
+    async fn content_filter_finish_sends_error_event_in_error_mode() {
+        let output = collect_final_sequence(None).await;
+        assert!(output.starts_with("event: error\n"));
+        assert!(!output.contains("message_stop"));
+    }
+}
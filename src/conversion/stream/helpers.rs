@@ -1,9 +1,8 @@
 use serde::Deserialize;
-use serde::de::IgnoredAny;
 use serde_json::Value;
 
-use crate::conversion::response::map_finish_reason;
-use crate::conversion::stream::state::{StreamState, StreamUsage};
+use crate::conversion::response::{map_finish_reason, matched_stop_sequence};
+use crate::conversion::stream::state::{StreamState, StreamUsage, started_tool_index};
 
 pub fn first_choice(parsed_chunk: &OpenAiStreamChunk) -> Option<&StreamChoice> {
     parsed_chunk.choices.first()
@@ -30,6 +29,8 @@ pub fn update_usage(parsed_chunk: &OpenAiStreamChunk, state: &mut StreamState) {
         input_tokens,
         output_tokens,
         cache_read_input_tokens: (cached_tokens > 0).then_some(cached_tokens),
+        response_id: None,
+        ttft: None,
     };
 }
 
@@ -37,7 +38,16 @@ pub fn update_finish_reason(choice: &StreamChoice, state: &mut StreamState) {
     let Some(finish_reason) = choice.finish_reason.as_deref() else {
         return;
     };
-    state.final_stop_reason = map_finish_reason(finish_reason).to_string();
+    state.stop_sequence = matched_stop_sequence(
+        finish_reason,
+        choice.stop_reason.as_ref(),
+        state.inferred_stop_sequence.as_deref(),
+    );
+    state.final_stop_reason =
+        map_finish_reason(finish_reason, state.stop_sequence.as_deref()).to_string();
+    for tool_call_state in state.tool_calls.values_mut() {
+        tool_call_state.json_sent = true;
+    }
 }
 
 pub fn tool_call_index(tool_call_delta: &ToolCallDelta) -> usize {
@@ -165,34 +175,34 @@ pub fn tool_call_deltas(choice: &StreamChoice) -> Option<&Vec<ToolCallDelta>> {
         .and_then(|delta| delta.tool_calls.as_ref())
 }
 
-pub fn tool_started(state: &StreamState, tool_call_index: usize) -> bool {
-    state
-        .tool_calls
-        .get(&tool_call_index)
-        .map(|tool| tool.started)
-        .unwrap_or(false)
-}
-
-pub fn snapshot_json_state(
+/// Buffers `arguments_delta` and returns the not-yet-emitted part of the
+/// arguments once the tool block has started, so a delta that arrived before
+/// the call's id and name is flushed with the first one after. Under
+/// `strict_json_validation` nothing is returned until the buffer is valid JSON.
+pub fn take_unsent_arguments(
     state: &mut StreamState,
     tool_call_index: usize,
     arguments_delta: &str,
-) -> (bool, bool, Option<usize>, String) {
-    let tool_call_state = state
-        .tool_calls
-        .get_mut(&tool_call_index)
-        .expect("tool call state should exist");
+) -> Option<(usize, String)> {
+    let tool_call_state = state.tool_calls.get_mut(&tool_call_index)?;
+    if tool_call_state.json_sent {
+        return None;
+    }
 
     tool_call_state.args_buffer.push_str(arguments_delta);
-    let has_complete_json =
-        serde_json::from_str::<IgnoredAny>(&tool_call_state.args_buffer).is_ok();
-
-    (
-        tool_call_state.json_sent,
-        has_complete_json,
-        tool_call_state.claude_index,
-        tool_call_state.args_buffer.clone(),
-    )
+    let claude_index = started_tool_index(tool_call_state)?;
+    if tool_call_state.args_sent_len >= tool_call_state.args_buffer.len() {
+        return None;
+    }
+    if state.strict_json_validation
+        && serde_json::from_str::<Value>(&tool_call_state.args_buffer).is_err()
+    {
+        return None;
+    }
+
+    let unsent = tool_call_state.args_buffer[tool_call_state.args_sent_len..].to_string();
+    tool_call_state.args_sent_len = tool_call_state.args_buffer.len();
+    Some((claude_index, unsent))
 }
 
 #[derive(Debug, Deserialize)]
@@ -205,6 +215,7 @@ pub struct OpenAiStreamChunk {
 #[derive(Debug, Deserialize)]
 pub struct StreamChoice {
     pub finish_reason: Option<String>,
+    pub stop_reason: Option<Value>,
     pub delta: Option<StreamDelta>,
     pub reasoning_content: Option<Value>,
     pub reasoning: Option<Value>,
@@ -222,13 +233,35 @@ pub struct StreamDelta {
 
 #[cfg(test)]
 mod tests {
-    use super::{StreamChoice, StreamDelta, thinking_delta, thinking_signature_delta};
+    use super::{
+        StreamChoice, StreamDelta, first_choice, parse_stream_chunk, take_unsent_arguments,
+        thinking_delta, thinking_signature_delta, update_finish_reason, update_usage,
+    };
+    use crate::conversion::stream::state::StreamState;
+    use crate::models::StreamingToolCallState;
     use serde_json::json;
 
+    #[test]
+    fn usage_only_chunk_updates_stream_usage() {
+        let chunk = parse_stream_chunk(
+            r#"{"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":34,"prompt_tokens_details":{"cached_tokens":8}}}"#,
+        )
+        .expect("chunk should parse");
+        let mut state = StreamState::new(false);
+
+        update_usage(&chunk, &mut state);
+
+        assert!(first_choice(&chunk).is_none());
+        assert_eq!(state.usage_data.input_tokens, 12);
+        assert_eq!(state.usage_data.output_tokens, 34);
+        assert_eq!(state.usage_data.cache_read_input_tokens, Some(8));
+    }
+
     #[test]
     fn reads_reasoning_content_string_delta() {
         let choice = StreamChoice {
             finish_reason: None,
+            stop_reason: None,
             delta: Some(StreamDelta {
                 content: None,
                 reasoning_content: Some(json!("step one")),
@@ -248,6 +281,7 @@ mod tests {
     fn reads_reasoning_text_from_object_delta() {
         let choice = StreamChoice {
             finish_reason: None,
+            stop_reason: None,
             delta: Some(StreamDelta {
                 content: None,
                 reasoning_content: Some(json!({"text":"hidden thought"})),
@@ -267,6 +301,7 @@ mod tests {
     fn reads_reasoning_text_from_array_delta() {
         let choice = StreamChoice {
             finish_reason: None,
+            stop_reason: None,
             delta: Some(StreamDelta {
                 content: None,
                 reasoning_content: None,
@@ -289,6 +324,7 @@ mod tests {
     fn reads_choice_level_reasoning_when_delta_missing() {
         let choice = StreamChoice {
             finish_reason: None,
+            stop_reason: None,
             delta: Some(StreamDelta {
                 content: Some("answer".to_string()),
                 reasoning_content: None,
@@ -308,6 +344,7 @@ mod tests {
     fn reads_signature_from_object_delta() {
         let choice = StreamChoice {
             finish_reason: None,
+            stop_reason: None,
             delta: Some(StreamDelta {
                 content: None,
                 reasoning_content: None,
@@ -322,6 +359,92 @@ mod tests {
 
         assert_eq!(thinking_signature_delta(&choice), Some("sig_abc"));
     }
+
+    #[test]
+    fn emits_each_argument_chunk_once_the_tool_block_has_started() {
+        let mut state = StreamState::new(false);
+        state
+            .tool_calls
+            .insert(0, StreamingToolCallState::default());
+
+        assert_eq!(take_unsent_arguments(&mut state, 0, "{\"cmd\""), None);
+
+        let tool_call_state = state.tool_calls.get_mut(&0).expect("tool call state");
+        tool_call_state.claude_index = Some(1);
+        tool_call_state.started = true;
+        assert_eq!(
+            take_unsent_arguments(&mut state, 0, ":\"ls"),
+            Some((1, "{\"cmd\":\"ls".to_string()))
+        );
+        assert_eq!(take_unsent_arguments(&mut state, 0, ""), None);
+        assert_eq!(
+            take_unsent_arguments(&mut state, 0, "\"}"),
+            Some((1, "\"}".to_string()))
+        );
+
+        state
+            .tool_calls
+            .get_mut(&0)
+            .expect("tool call state")
+            .json_sent = true;
+        assert_eq!(take_unsent_arguments(&mut state, 0, "x"), None);
+    }
+
+    #[test]
+    fn strict_validation_holds_arguments_until_they_parse() {
+        let mut state = StreamState::new(false).with_strict_json_validation(true);
+        state.tool_calls.insert(
+            0,
+            StreamingToolCallState {
+                claude_index: Some(1),
+                started: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(take_unsent_arguments(&mut state, 0, "{\"cmd\":"), None);
+        assert_eq!(
+            take_unsent_arguments(&mut state, 0, "\"ls\"}"),
+            Some((1, "{\"cmd\":\"ls\"}".to_string()))
+        );
+    }
+
+    fn finish_chunk_state(data_line: &str, inferred: Option<&str>) -> StreamState {
+        let chunk = parse_stream_chunk(data_line).expect("chunk should parse");
+        let mut state = StreamState::new(false);
+        state.inferred_stop_sequence = inferred.map(str::to_string);
+        update_finish_reason(first_choice(&chunk).expect("choice"), &mut state);
+        state
+    }
+
+    #[test]
+    fn finish_reports_upstream_matched_stop_sequence() {
+        let state = finish_chunk_state(
+            r#"{"choices":[{"delta":{},"finish_reason":"stop","stop_reason":"END"}]}"#,
+            Some("###"),
+        );
+        assert_eq!(state.final_stop_reason, "stop_sequence");
+        assert_eq!(state.stop_sequence.as_deref(), Some("END"));
+    }
+
+    #[test]
+    fn finish_falls_back_to_inferred_stop_sequence() {
+        let stop = r#"{"choices":[{"delta":{},"finish_reason":"stop","stop_reason":null}]}"#;
+        let inferred = finish_chunk_state(stop, Some("###"));
+        assert_eq!(inferred.final_stop_reason, "stop_sequence");
+        assert_eq!(inferred.stop_sequence.as_deref(), Some("###"));
+
+        let plain = finish_chunk_state(stop, None);
+        assert_eq!(plain.final_stop_reason, "end_turn");
+        assert_eq!(plain.stop_sequence, None);
+
+        let length = finish_chunk_state(
+            r#"{"choices":[{"delta":{},"finish_reason":"length"}]}"#,
+            Some("###"),
+        );
+        assert_eq!(length.final_stop_reason, "max_tokens");
+        assert_eq!(length.stop_sequence, None);
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -1,11 +1,17 @@
+mod dry_run;
+mod finish;
 mod helpers;
 mod pipeline;
 mod pipeline_responses;
 mod responses_helpers;
 mod responses_tools;
+mod send_failures;
 mod sse;
+mod sse_frame;
 mod state;
 mod thinking;
 
+pub use dry_run::{stream_dry_run_sse, stream_text_sse};
 pub use pipeline::stream_openai_to_claude_sse;
 pub use pipeline_responses::stream_openai_responses_to_claude_sse;
+pub use state::{StreamOptions, StreamUsage};
@@ -3,74 +3,81 @@ use salvo::http::body::BodySender;
 use tracing::{error, warn};
 use uuid::Uuid;
 
+use crate::conversion::stream::finish::send_final_sequence;
 use crate::conversion::stream::helpers::{
     StreamChoice, ToolCallDelta, content_delta, first_choice, parse_stream_chunk,
-    snapshot_json_state, tool_arguments_delta, tool_call_deltas, tool_call_index, tool_started,
+    take_unsent_arguments, tool_arguments_delta, tool_call_deltas, tool_call_index,
     update_finish_reason, update_tool_identity, update_usage,
 };
 use crate::conversion::stream::sse::{
-    send_error_sse, send_start_sequence, send_stop_sequence, send_text_delta,
-    send_tool_block_start, send_tool_json_delta,
+    send_error_sse, send_start_sequence, send_text_delta, send_tool_block_start,
+    send_tool_json_delta,
 };
-use crate::conversion::stream::state::{StreamState, StreamUsage};
+use crate::conversion::stream::sse_frame::SseFrameContext;
+use crate::conversion::stream::state::{StreamOptions, StreamState, StreamUsage};
 use crate::conversion::stream::thinking::{
     ThinkingFallbackContext, handle_thinking_delta, maybe_emit_realtime_fallback,
 };
+use crate::metrics::metrics;
 
 pub async fn stream_openai_to_claude_sse(
     upstream_response: reqwest::Response,
     mut sender: BodySender,
     original_model: String,
-    thinking_requested: bool,
+    options: StreamOptions,
 ) -> StreamUsage {
-    let mut state = StreamState::new(thinking_requested);
+    let mut state = StreamState::from_options(options);
     let message_id = message_id();
     if send_start_sequence(&mut sender, &original_model, &message_id)
         .await
         .is_err()
     {
-        return state.usage_data;
+        return state.into_usage();
     }
 
+    let mut frame = SseFrameContext::default();
     let mut line_buffer = String::new();
     let mut upstream_stream = upstream_response.bytes_stream();
 
     while let Some(chunk_result) = upstream_stream.next().await {
         let Ok(chunk) = chunk_result else {
             if let Some(error) = chunk_result.err() {
-                log_stream_read_error(&error);
+                log_stream_read_error(&error, &frame);
                 let _ = send_error_sse(
                     &mut sender,
                     &format!("streaming error from upstream: {error}"),
                 )
                 .await;
             }
-            return state.usage_data;
+            return state.into_usage();
         };
+        metrics().record_stream_chunk();
 
         line_buffer.push_str(&String::from_utf8_lossy(&chunk));
-        process_complete_lines(
+        let should_stop = process_complete_lines(
             &mut line_buffer,
+            &mut frame,
             &mut sender,
             &mut state,
             &original_model,
             &message_id,
         )
         .await;
-
-        if line_buffer.contains("data: [DONE]") {
+        if should_stop {
             break;
         }
     }
 
-    let _ = send_stop_sequence(&mut sender, &state).await;
-    state.usage_data
+    let _ = send_final_sequence(&mut sender, &mut state).await;
+    state.into_usage()
 }
 
-fn log_stream_read_error(error: &reqwest::Error) {
+fn log_stream_read_error(error: &reqwest::Error, frame: &SseFrameContext) {
     if error.is_timeout() {
         error!(
             phase = "upstream_stream_timeout",
+            last_event_id = ?frame.last_event_id,
+            retry_ms = ?frame.retry_ms,
             "Streaming interrupted by upstream read timeout"
         );
         return;
@@ -78,11 +85,13 @@ fn log_stream_read_error(error: &reqwest::Error) {
 
     error!(
         phase = "upstream_stream_error",
+        last_event_id = ?frame.last_event_id,
+        retry_ms = ?frame.retry_ms,
         "Streaming interrupted while reading upstream body: {error}"
     );
 }
 
-fn message_id() -> String {
+pub(super) fn message_id() -> String {
     format!(
         "msg_{}",
         Uuid::new_v4()
@@ -96,11 +105,12 @@ fn message_id() -> String {
 
 async fn process_complete_lines(
     line_buffer: &mut String,
+    frame: &mut SseFrameContext,
     sender: &mut BodySender,
     state: &mut StreamState,
     original_model: &str,
     message_id: &str,
-) {
+) -> bool {
     let fallback_context = ThinkingFallbackContext {
         model: original_model,
         message_id,
@@ -113,11 +123,11 @@ async fn process_complete_lines(
             continue;
         }
 
-        let Some(data_line) = line.strip_prefix("data: ") else {
+        let Some(data_line) = frame.observe(line) else {
             continue;
         };
         if data_line.trim() == "[DONE]" {
-            break;
+            return true;
         }
 
         let Ok(parsed_chunk) = parse_stream_chunk(data_line) else {
@@ -130,34 +140,37 @@ async fn process_complete_lines(
             continue;
         };
 
-        if maybe_emit_realtime_fallback(choice, sender, state, &fallback_context)
-            .await
-            .is_err()
-        {
-            return;
+        let result = maybe_emit_realtime_fallback(choice, sender, state, &fallback_context).await;
+        if state.send_failures.record(result, sender) {
+            return true;
         }
-
-        if handle_thinking_delta(choice, sender, state).await.is_err() {
-            return;
+        let result = handle_thinking_delta(choice, sender, state).await;
+        if state.send_failures.record(result, sender) {
+            return true;
         }
-        if handle_content_delta(choice, sender, state).await.is_err() {
-            return;
+        let result = handle_content_delta(choice, sender, state).await;
+        if state.send_failures.record(result, sender) {
+            return true;
         }
-        if process_tool_deltas(choice, sender, state).await.is_err() {
-            return;
+        let result = process_tool_deltas(choice, sender, state).await;
+        if state.send_failures.record(result, sender) {
+            return true;
         }
         update_finish_reason(choice, state);
     }
+
+    false
 }
 
 async fn handle_content_delta(
     choice: &StreamChoice,
     sender: &mut BodySender,
-    state: &StreamState,
+    state: &mut StreamState,
 ) -> std::io::Result<()> {
     let Some(content_delta) = content_delta(choice) else {
         return Ok(());
     };
+    state.note_text_delta(content_delta);
 
     send_text_delta(sender, state, content_delta).await
 }
@@ -228,29 +241,12 @@ async fn send_tool_json_if_ready(
     state: &mut StreamState,
     tool_call_index: usize,
 ) -> std::io::Result<()> {
-    let Some(arguments_delta) = tool_arguments_delta(tool_call_delta) else {
-        return Ok(());
-    };
-
-    if !tool_started(state, tool_call_index) {
-        return Ok(());
-    }
-
-    let snapshot = snapshot_json_state(state, tool_call_index, arguments_delta);
-    let (json_sent, has_complete_json, claude_index, payload_json) = snapshot;
-
-    if json_sent || !has_complete_json {
-        return Ok(());
-    }
-
-    let Some(claude_index) = claude_index else {
+    let arguments_delta = tool_arguments_delta(tool_call_delta).unwrap_or_default();
+    let Some((claude_index, unsent)) =
+        take_unsent_arguments(state, tool_call_index, arguments_delta)
+    else {
         return Ok(());
     };
 
-    send_tool_json_delta(sender, claude_index, &payload_json).await?;
-
-    if let Some(tool_call_state) = state.tool_calls.get_mut(&tool_call_index) {
-        tool_call_state.json_sent = true;
-    }
-    Ok(())
+    send_tool_json_delta(sender, claude_index, &unsent).await
 }
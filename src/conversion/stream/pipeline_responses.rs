@@ -4,6 +4,8 @@ use serde_json::Value;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::conversion::response::reasoning_signature;
+use crate::conversion::stream::finish::send_final_sequence;
 use crate::conversion::stream::responses_helpers::{
     ResponsesStreamContext, event_error_message, event_type, has_tool_event, text_delta, tool_kind,
     update_from_completed,
@@ -12,46 +14,51 @@ use crate::conversion::stream::responses_tools::{
     handle_function_arguments_delta, handle_function_arguments_done, handle_output_item_added,
 };
 use crate::conversion::stream::sse::{
-    send_error_sse, send_start_sequence, send_stop_sequence, send_text_delta,
-    send_thinking_block_start, send_thinking_delta,
+    send_error_sse, send_signature_delta, send_start_sequence, send_text_delta, send_thinking_delta,
 };
-use crate::conversion::stream::state::{StreamState, StreamUsage};
+use crate::conversion::stream::sse_frame::SseFrameContext;
+use crate::conversion::stream::state::{StreamOptions, StreamState, StreamUsage};
+use crate::conversion::stream::thinking::start_thinking_block;
+use crate::metrics::metrics;
 
 pub async fn stream_openai_responses_to_claude_sse(
     upstream_response: reqwest::Response,
     mut sender: BodySender,
     original_model: String,
-    thinking_requested: bool,
+    options: StreamOptions,
 ) -> StreamUsage {
-    let mut state = StreamState::new(thinking_requested);
+    let mut state = StreamState::from_options(options);
     let message_id = message_id();
     if send_start_sequence(&mut sender, &original_model, &message_id)
         .await
         .is_err()
     {
-        return state.usage_data;
+        return state.into_usage();
     }
 
     let mut context = ResponsesStreamContext::default();
+    let mut frame = SseFrameContext::default();
     let mut line_buffer = String::new();
     let mut upstream_stream = upstream_response.bytes_stream();
 
     while let Some(chunk_result) = upstream_stream.next().await {
         let Ok(chunk) = chunk_result else {
             if let Some(error) = chunk_result.err() {
-                log_stream_read_error(&error);
+                log_stream_read_error(&error, &frame);
                 let _ = send_error_sse(
                     &mut sender,
                     &format!("streaming error from upstream: {error}"),
                 )
                 .await;
             }
-            return state.usage_data;
+            return state.into_usage();
         };
+        metrics().record_stream_chunk();
 
         line_buffer.push_str(&String::from_utf8_lossy(&chunk));
         let should_stop = process_lines(
             &mut line_buffer,
+            &mut frame,
             &mut sender,
             &mut state,
             &mut context,
@@ -64,14 +71,16 @@ pub async fn stream_openai_responses_to_claude_sse(
         }
     }
 
-    let _ = send_stop_sequence(&mut sender, &state).await;
-    state.usage_data
+    let _ = send_final_sequence(&mut sender, &mut state).await;
+    state.into_usage()
 }
 
-fn log_stream_read_error(error: &reqwest::Error) {
+fn log_stream_read_error(error: &reqwest::Error, frame: &SseFrameContext) {
     if error.is_timeout() {
         error!(
             phase = "upstream_stream_timeout",
+            last_event_id = ?frame.last_event_id,
+            retry_ms = ?frame.retry_ms,
             "Streaming interrupted by upstream read timeout"
         );
         return;
@@ -79,6 +88,8 @@ fn log_stream_read_error(error: &reqwest::Error) {
 
     error!(
         phase = "upstream_stream_error",
+        last_event_id = ?frame.last_event_id,
+        retry_ms = ?frame.retry_ms,
         "Streaming interrupted while reading upstream body: {error}"
     );
 }
@@ -97,6 +108,7 @@ fn message_id() -> String {
 
 async fn process_lines(
     line_buffer: &mut String,
+    frame: &mut SseFrameContext,
     sender: &mut BodySender,
     state: &mut StreamState,
     context: &mut ResponsesStreamContext,
@@ -110,7 +122,7 @@ async fn process_lines(
             continue;
         }
 
-        let Some(data_line) = line.strip_prefix("data: ") else {
+        let Some(data_line) = frame.observe(line) else {
             continue;
         };
         if data_line.trim() == "[DONE]" {
@@ -144,45 +156,46 @@ async fn handle_event(
     maybe_start_thinking_fallback(event_type, event, sender, state, original_model, message_id)
         .await;
 
-    match event_type {
+    let result = match event_type {
         Some("response.output_text.delta") | Some("response.refusal.delta") => {
-            if let Some(delta) = text_delta(event) {
-                let _ = send_text_delta(sender, state, delta).await;
+            match text_delta(event) {
+                Some(delta) => {
+                    state.note_text_delta(delta);
+                    send_text_delta(sender, state, delta).await
+                }
+                None => Ok(()),
             }
-            false
         }
         Some("response.reasoning_text.delta")
         | Some("response.reasoning_summary_text.delta")
         | Some("response.reasoning.delta")
         | Some("response.reasoning_summary.delta") => {
-            let _ = handle_thinking_delta(event, sender, state).await;
-            false
+            handle_thinking_delta(event, sender, state).await
         }
-        Some("response.output_item.added") => {
-            if tool_kind(event) == Some("function_call") {
-                let _ = handle_output_item_added(event, sender, state, context).await;
-            }
-            false
+        Some("response.output_item.added") if tool_kind(event) == Some("function_call") => {
+            handle_output_item_added(event, sender, state, context).await
+        }
+        Some("response.output_item.done") if tool_kind(event) == Some("reasoning") => {
+            handle_reasoning_item_done(event, sender, state).await
         }
         Some("response.function_call_arguments.delta") => {
-            let _ = handle_function_arguments_delta(event, sender, state, context).await;
-            false
+            handle_function_arguments_delta(event, sender, state, context).await
         }
         Some("response.function_call_arguments.done") => {
-            let _ = handle_function_arguments_done(event, sender, state, context).await;
-            false
+            handle_function_arguments_done(event, sender, state, context).await
         }
         Some("response.completed") => {
             update_from_completed(event, state);
-            true
+            return true;
         }
         Some("response.failed") | Some("error") => {
             let message = event_error_message(event);
             let _ = send_error_sse(sender, &message).await;
-            true
+            return true;
         }
-        _ => false,
-    }
+        _ => Ok(()),
+    };
+    state.send_failures.record(result, sender)
 }
 
 async fn handle_thinking_delta(
@@ -193,17 +206,39 @@ async fn handle_thinking_delta(
     let Some(delta) = text_delta(event) else {
         return Ok(());
     };
-    if !state.thinking_started {
+    if !state.thinking_started() {
         start_thinking_block(sender, state).await?;
     }
 
-    let Some(thinking_index) = state.thinking_block_index else {
+    let Some(thinking_index) = state.record_thinking_delta() else {
         return Ok(());
     };
-    state.saw_thinking_delta = true;
     send_thinking_delta(sender, thinking_index, delta).await
 }
 
+/// Sends the finished reasoning item's encrypted content as the thinking
+/// block's signature, opening the block when the upstream sent no summary.
+async fn handle_reasoning_item_done(
+    event: &Value,
+    sender: &mut BodySender,
+    state: &mut StreamState,
+) -> std::io::Result<()> {
+    let Some(signature) = event.get("item").and_then(reasoning_signature) else {
+        return Ok(());
+    };
+    if !state.thinking_started() {
+        if !state.thinking_requested {
+            return Ok(());
+        }
+        start_thinking_block(sender, state).await?;
+    }
+
+    let Some(thinking_index) = state.record_thinking_delta() else {
+        return Ok(());
+    };
+    send_signature_delta(sender, thinking_index, signature).await
+}
+
 async fn maybe_start_thinking_fallback(
     event_type: Option<&str>,
     event: &Value,
@@ -212,7 +247,7 @@ async fn maybe_start_thinking_fallback(
     original_model: &str,
     message_id: &str,
 ) {
-    if !state.thinking_requested || state.thinking_started || state.saw_thinking_delta {
+    if !state.thinking_requested || state.thinking_started() || state.saw_thinking_delta() {
         return;
     }
     if matches!(
@@ -237,7 +272,7 @@ async fn maybe_start_thinking_fallback(
             phase = "thinking_fallback_start",
             model = original_model,
             message_id,
-            claude_index = state.thinking_block_index.unwrap_or(0),
+            claude_index = state.current_thinking_index().unwrap_or(0),
             has_content_delta = has_content,
             has_tool_delta = has_tools,
             has_finish_reason = has_finish,
@@ -245,14 +280,3 @@ async fn maybe_start_thinking_fallback(
         );
     }
 }
-
-async fn start_thinking_block(
-    sender: &mut BodySender,
-    state: &mut StreamState,
-) -> std::io::Result<()> {
-    state.tool_block_counter += 1;
-    let claude_index = state.text_block_index + state.tool_block_counter;
-    state.thinking_block_index = Some(claude_index);
-    state.thinking_started = true;
-    send_thinking_block_start(sender, claude_index).await
-}
@@ -22,6 +22,11 @@ pub(crate) fn update_from_completed(event: &Value, state: &mut StreamState) {
             .and_then(|v| v.get("cached_tokens"))
             .and_then(Value::as_u64)
             .filter(|v| *v > 0),
+        response_id: payload
+            .get("id")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        ttft: None,
     };
 
     state.final_stop_reason = resolve_completed_stop_reason(payload).to_string();
@@ -1,7 +1,7 @@
 use salvo::http::body::BodySender;
 use serde_json::Value;
 
-use crate::conversion::stream::helpers::snapshot_json_state;
+use crate::conversion::stream::helpers::take_unsent_arguments;
 use crate::conversion::stream::responses_helpers::{
     ResponsesStreamContext, arguments_from_item, resolve_tool_index, update_tool_identity,
     update_tool_maps, value_to_string,
@@ -21,7 +21,7 @@ pub(crate) async fn handle_output_item_added(
     maybe_start_tool_block(tool_index, sender, state).await?;
 
     if let Some(arguments) = arguments_from_item(event) {
-        send_tool_json_if_complete(tool_index, arguments, sender, state).await?;
+        send_unsent_arguments(tool_index, arguments, sender, state).await?;
     }
     Ok(())
 }
@@ -40,7 +40,7 @@ pub(crate) async fn handle_function_arguments_delta(
     let Some(delta) = event.get("delta").and_then(Value::as_str) else {
         return Ok(());
     };
-    send_tool_json_if_complete(tool_index, delta, sender, state).await
+    send_unsent_arguments(tool_index, delta, sender, state).await
 }
 
 pub(crate) async fn handle_function_arguments_done(
@@ -92,27 +92,21 @@ async fn maybe_start_tool_block(
     .await
 }
 
-async fn send_tool_json_if_complete(
+async fn send_unsent_arguments(
     tool_index: usize,
     delta: &str,
     sender: &mut BodySender,
     state: &mut StreamState,
 ) -> std::io::Result<()> {
-    let snapshot = snapshot_json_state(state, tool_index, delta);
-    let (json_sent, has_complete_json, claude_index, payload_json) = snapshot;
-    if json_sent || !has_complete_json {
+    let Some((claude_index, unsent)) = take_unsent_arguments(state, tool_index, delta) else {
         return Ok(());
-    }
-
-    if let Some(claude_index) = claude_index {
-        send_tool_json_delta(sender, claude_index, &payload_json).await?;
-        if let Some(tool_state) = state.tool_calls.get_mut(&tool_index) {
-            tool_state.json_sent = true;
-        }
-    }
-    Ok(())
+    };
+    send_tool_json_delta(sender, claude_index, &unsent).await
 }
 
+/// The done event carries the full arguments; only the part not covered by
+/// earlier deltas is emitted. If they disagree, the full value is sent when
+/// nothing has been emitted yet and dropped otherwise.
 async fn send_tool_json_on_done(
     tool_index: usize,
     arguments: &str,
@@ -122,15 +116,17 @@ async fn send_tool_json_on_done(
     let Some(tool_state) = state.tool_calls.get_mut(&tool_index) else {
         return Ok(());
     };
-    if tool_state.json_sent {
-        return Ok(());
-    }
-
-    tool_state.args_buffer = arguments.to_string();
-    let Some(claude_index) = tool_state.claude_index else {
-        return Ok(());
+    let remainder = match arguments.strip_prefix(tool_state.args_buffer.as_str()) {
+        Some(remainder) => remainder.to_string(),
+        None if tool_state.args_sent_len == 0 => {
+            tool_state.args_buffer.clear();
+            arguments.to_string()
+        }
+        None => String::new(),
     };
-    send_tool_json_delta(sender, claude_index, &tool_state.args_buffer).await?;
-    tool_state.json_sent = true;
+    send_unsent_arguments(tool_index, &remainder, sender, state).await?;
+    if let Some(tool_state) = state.tool_calls.get_mut(&tool_index) {
+        tool_state.json_sent = true;
+    }
     Ok(())
 }
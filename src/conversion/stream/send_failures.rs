@@ -0,0 +1,147 @@
+use salvo::http::body::BodySender;
+use tracing::warn;
+
+/// Counts back-to-back failed writes to the downstream body. A failed write
+/// usually means the client went away, so once `max_consecutive` is reached
+/// the pipeline stops reading upstream instead of converting into the void.
+#[derive(Clone, Copy, Debug)]
+pub struct SendFailureTracker {
+    consecutive: u32,
+    max_consecutive: u32,
+}
+
+impl SendFailureTracker {
+    pub fn new(max_consecutive: u32) -> Self {
+        Self {
+            consecutive: 0,
+            max_consecutive: max_consecutive.max(1),
+        }
+    }
+
+    /// Returns true when the stream should be aborted. A closed channel
+    /// means the client is gone, so that aborts at once; steps that sent
+    /// nothing also report `Ok` and must not hide it.
+    pub fn record(&mut self, result: std::io::Result<()>, sender: &BodySender) -> bool {
+        if sender.is_closed() {
+            warn!(
+                phase = "downstream_send_failed",
+                "Aborting stream because the downstream body channel is closed"
+            );
+            return true;
+        }
+        let Err(error) = result else {
+            self.consecutive = 0;
+            return false;
+        };
+        self.consecutive += 1;
+        if self.consecutive < self.max_consecutive {
+            return false;
+        }
+
+        warn!(
+            phase = "downstream_send_failed",
+            consecutive_failures = self.consecutive,
+            "Aborting stream after repeated downstream write failures: {error}"
+        );
+        true
+    }
+}
+
+impl Default for SendFailureTracker {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures_util::StreamExt;
+    use salvo::http::body::ResBody;
+
+    use super::SendFailureTracker;
+    use crate::conversion::stream::{
+        StreamOptions, stream_openai_responses_to_claude_sse, stream_openai_to_claude_sse,
+    };
+
+    fn failure() -> std::io::Result<()> {
+        Err(std::io::Error::other("closed"))
+    }
+
+    #[test]
+    fn aborts_only_after_consecutive_failures() {
+        let (sender, _body) = ResBody::channel();
+        let mut tracker = SendFailureTracker::new(3);
+        assert!(!tracker.record(failure(), &sender));
+        assert!(!tracker.record(failure(), &sender));
+        assert!(!tracker.record(Ok(()), &sender));
+        assert!(!tracker.record(failure(), &sender));
+        assert!(!tracker.record(failure(), &sender));
+        assert!(tracker.record(failure(), &sender));
+
+        assert!(SendFailureTracker::new(0).record(failure(), &sender));
+    }
+
+    #[test]
+    fn aborts_at_once_when_the_channel_is_closed() {
+        let (sender, body) = ResBody::channel();
+        drop(body);
+        assert!(SendFailureTracker::new(3).record(Ok(()), &sender));
+    }
+
+    const UPSTREAM_CHUNKS: usize = 1_000;
+
+    /// Streams `UPSTREAM_CHUNKS` copies of `line`, counting how many the
+    /// pipeline pulled from upstream.
+    fn counting_upstream(line: &'static str, polled: Arc<AtomicUsize>) -> reqwest::Response {
+        let chunks = futures_util::stream::iter(0..UPSTREAM_CHUNKS).map(move |_| {
+            polled.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, std::io::Error>(format!("data: {line}\n\n"))
+        });
+        salvo::hyper::Response::new(reqwest::Body::wrap_stream(chunks)).into()
+    }
+
+    /// Reads up to the first text delta, then hangs up like a disconnecting
+    /// client.
+    async fn read_first_delta_then_disconnect(mut body: ResBody) {
+        while let Some(Ok(frame)) = body.next().await {
+            let Ok(data) = frame.into_data() else {
+                continue;
+            };
+            if String::from_utf8_lossy(&data).contains("content_block_delta") {
+                return;
+            }
+        }
+        panic!("stream ended before any text delta");
+    }
+
+    #[tokio::test]
+    async fn pipelines_stop_reading_upstream_after_the_client_disconnects() {
+        let cases = [
+            r#"{"choices":[{"index":0,"delta":{"content":"hi"}}]}"#,
+            r#"{"type":"response.output_text.delta","delta":"hi"}"#,
+        ];
+        for (index, line) in cases.into_iter().enumerate() {
+            let polled = Arc::new(AtomicUsize::new(0));
+            let upstream = counting_upstream(line, polled.clone());
+            let (sender, body) = ResBody::channel();
+            let model = "claude-3-5-sonnet".to_string();
+            let options = StreamOptions {
+                max_consecutive_send_errors: 3,
+                ..StreamOptions::default()
+            };
+            let reader = tokio::spawn(read_first_delta_then_disconnect(body));
+            if index == 0 {
+                stream_openai_to_claude_sse(upstream, sender, model, options).await;
+            } else {
+                stream_openai_responses_to_claude_sse(upstream, sender, model, options).await;
+            }
+            reader.await.expect("reader");
+
+            let polled = polled.load(Ordering::SeqCst);
+            assert!(polled < 10, "case {index} read {polled} upstream chunks");
+        }
+    }
+}
@@ -172,7 +172,7 @@ pub async fn send_stop_sequence(
     )
     .await?;
 
-    if let Some(thinking_index) = state.thinking_block_index {
+    for &thinking_index in state.thinking_blocks.keys() {
         send_sse(
             sender,
             EVENT_CONTENT_BLOCK_STOP,
@@ -206,7 +206,7 @@ pub async fn send_stop_sequence(
         event_type: EVENT_MESSAGE_DELTA,
         delta: MessageDeltaPayload {
             stop_reason: state.final_stop_reason.as_str(),
-            stop_sequence: None,
+            stop_sequence: state.stop_sequence.as_deref(),
         },
         usage: &state.usage_data,
     };
@@ -360,7 +360,7 @@ struct ContentBlockDeltaEvent<T: Serialize> {
 #[derive(Serialize)]
 struct MessageDeltaPayload<'a> {
     stop_reason: &'a str,
-    stop_sequence: Option<String>,
+    stop_sequence: Option<&'a str>,
 }
 
 #[derive(Serialize)]
@@ -384,3 +384,71 @@ struct ErrorEvent<'a> {
     event_type: &'static str,
     error: ApiErrorPayload<'a>,
 }
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use salvo::http::ResBody;
+
+    use super::*;
+
+    async fn collect_stop_sequence(state: StreamState) -> String {
+        let (mut sender, body) = ResBody::channel();
+        tokio::spawn(async move {
+            let _ = send_stop_sequence(&mut sender, &state).await;
+        });
+
+        let mut output = String::new();
+        let mut body = body;
+        while let Some(Ok(frame)) = body.next().await {
+            if let Ok(data) = frame.into_data() {
+                output.push_str(&String::from_utf8_lossy(&data));
+            }
+        }
+        output
+    }
+
+    #[tokio::test]
+    async fn stop_sequence_closes_thinking_block_before_message_delta() {
+        let mut state = StreamState::new(true);
+        state.open_thinking_block();
+
+        let output = collect_stop_sequence(state).await;
+        let text_stop = output.find(r#""type":"content_block_stop","index":0"#);
+        let thinking_stop = output.find(r#""type":"content_block_stop","index":1"#);
+        let message_delta = output.find(EVENT_MESSAGE_DELTA);
+
+        assert!(text_stop.is_some());
+        assert!(thinking_stop.is_some());
+        assert!(thinking_stop < message_delta);
+    }
+
+    #[tokio::test]
+    async fn stop_sequence_closes_every_interleaved_thinking_block() {
+        let mut state = StreamState::new(true).with_interleaved_thinking(true);
+        state.open_thinking_block();
+        state.tool_block_counter += 1;
+        state.open_thinking_block();
+
+        let output = collect_stop_sequence(state).await;
+        assert!(output.contains(r#""type":"content_block_stop","index":1"#));
+        assert!(output.contains(r#""type":"content_block_stop","index":3"#));
+    }
+
+    #[tokio::test]
+    async fn stop_sequence_skips_thinking_stop_without_thinking_block() {
+        let output = collect_stop_sequence(StreamState::new(false)).await;
+
+        assert_eq!(output.matches(r#""type":"content_block_stop""#).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn message_delta_carries_matched_stop_sequence() {
+        let mut state = StreamState::new(false);
+        state.final_stop_reason = "stop_sequence".to_string();
+        state.stop_sequence = Some("END".to_string());
+
+        let output = collect_stop_sequence(state).await;
+        assert!(output.contains(r#""stop_reason":"stop_sequence","stop_sequence":"END""#));
+    }
+}
@@ -0,0 +1,60 @@
+/// Non-data SSE fields seen on the upstream stream. Only `data:` payloads
+/// drive conversion; `id:` and `retry:` are kept so a broken stream can be
+/// reported with the last event id and the upstream's reconnection hint.
+#[derive(Debug, Default)]
+pub struct SseFrameContext {
+    pub last_event_id: Option<String>,
+    pub retry_ms: Option<u64>,
+}
+
+impl SseFrameContext {
+    /// Records `id:` / `retry:` fields and returns the payload of a `data:`
+    /// line. Comment lines (`:`), `event:` and unknown fields yield `None`.
+    pub fn observe<'a>(&mut self, line: &'a str) -> Option<&'a str> {
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "data" => return Some(value),
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(retry_ms) = value.parse::<u64>() {
+                    self.retry_ms = Some(retry_ms);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SseFrameContext;
+
+    #[test]
+    fn returns_data_payloads_and_captures_id_and_retry() {
+        let mut frame = SseFrameContext::default();
+
+        assert_eq!(frame.observe(": keep-alive"), None);
+        assert_eq!(frame.observe("event: delta"), None);
+        assert_eq!(frame.observe("id: evt_7"), None);
+        assert_eq!(frame.observe("retry: 1500"), None);
+        assert_eq!(frame.observe("data: {\"a\":1}"), Some("{\"a\":1}"));
+        assert_eq!(frame.observe("data:[DONE]"), Some("[DONE]"));
+
+        assert_eq!(frame.last_event_id.as_deref(), Some("evt_7"));
+        assert_eq!(frame.retry_ms, Some(1500));
+    }
+
+    #[test]
+    fn ignores_malformed_retry_and_keeps_previous_value() {
+        let mut frame = SseFrameContext::default();
+        frame.observe("retry: 200");
+        frame.observe("retry: soon");
+
+        assert_eq!(frame.retry_ms, Some(200));
+    }
+}
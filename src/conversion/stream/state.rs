@@ -1,49 +1,179 @@
 use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
 use serde::Serialize;
 
+use crate::conversion::stream::send_failures::SendFailureTracker;
 use crate::models::StreamingToolCallState;
 
+/// Per-request switches for a streaming conversion, resolved by the handler
+/// from the request and config.
+#[derive(Clone, Debug, Default)]
+pub struct StreamOptions {
+    pub thinking_requested: bool,
+    pub interleaved_thinking: bool,
+    pub strict_json_validation: bool,
+    pub max_consecutive_send_errors: u32,
+    /// From [`Config::content_filter_text`](crate::config::Config::content_filter_text).
+    pub content_filter_text: Option<String>,
+    /// Unsigned prefill reasoning replayed when the thinking block opens,
+    /// set by `thinking_continuation_mode`.
+    pub thinking_seed: Option<String>,
+    /// Reported on a `stop` finish when upstream does not name the matched
+    /// sequence, set by `infer_stop_sequence`.
+    pub inferred_stop_sequence: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct StreamUsage {
     pub input_tokens: u64,
     pub output_tokens: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_read_input_tokens: Option<u64>,
+    /// Upstream Responses API id, kept for `previous_response_id` chaining;
+    /// never sent to the client.
+    #[serde(skip)]
+    pub response_id: Option<String>,
+    /// Time from stream start until the first non-empty text delta.
+    #[serde(skip)]
+    pub ttft: Option<Duration>,
 }
 
-impl StreamUsage {
-    pub fn total_tokens(&self) -> u64 {
-        self.input_tokens.saturating_add(self.output_tokens)
-    }
+#[derive(Debug, Default)]
+pub struct ThinkingBlockState {
+    /// False for the empty block opened when upstream sends no reasoning.
+    pub saw_delta: bool,
 }
 
 pub struct StreamState {
     pub text_block_index: usize,
-    pub thinking_block_index: Option<usize>,
-    pub thinking_started: bool,
+    /// Keyed by Claude content-block index; the highest index is the block
+    /// that thinking deltas currently go to.
+    pub thinking_blocks: BTreeMap<usize, ThinkingBlockState>,
     pub thinking_requested: bool,
-    pub saw_thinking_delta: bool,
+    /// Set by the `interleaved-thinking-*` beta: thinking that follows a tool
+    /// call opens a new block instead of extending the first one.
+    pub interleaved_thinking: bool,
+    /// Holds tool arguments back until the buffer parses as JSON instead of
+    /// forwarding each upstream fragment as it arrives.
+    pub strict_json_validation: bool,
     pub tool_block_counter: usize,
     pub tool_calls: BTreeMap<usize, StreamingToolCallState>,
     pub final_stop_reason: String,
+    /// Sent as `message_delta.stop_sequence` alongside a `stop_sequence` stop.
+    pub stop_sequence: Option<String>,
+    pub inferred_stop_sequence: Option<String>,
     pub usage_data: StreamUsage,
+    pub send_failures: SendFailureTracker,
+    /// Sent in place of a `content_filter` finish; `None` ends the stream
+    /// with an error event instead.
+    pub content_filter_text: Option<String>,
+    pub thinking_seed: Option<String>,
+    started_at: Instant,
+    ttft: Option<Duration>,
 }
 
 impl StreamState {
     pub fn new(thinking_requested: bool) -> Self {
         Self {
             text_block_index: 0,
-            thinking_block_index: None,
-            thinking_started: false,
+            thinking_blocks: BTreeMap::new(),
             thinking_requested,
-            saw_thinking_delta: false,
+            interleaved_thinking: false,
+            strict_json_validation: false,
             tool_block_counter: 0,
             tool_calls: BTreeMap::new(),
             final_stop_reason: "end_turn".to_string(),
+            stop_sequence: None,
+            inferred_stop_sequence: None,
             usage_data: StreamUsage::default(),
+            send_failures: SendFailureTracker::default(),
+            content_filter_text: None,
+            thinking_seed: None,
+            started_at: Instant::now(),
+            ttft: None,
+        }
+    }
+
+    pub fn from_options(options: StreamOptions) -> Self {
+        let mut state = Self::new(options.thinking_requested)
+            .with_interleaved_thinking(options.interleaved_thinking)
+            .with_strict_json_validation(options.strict_json_validation);
+        state.send_failures = SendFailureTracker::new(options.max_consecutive_send_errors);
+        state.content_filter_text = options.content_filter_text;
+        state.thinking_seed = options.thinking_seed;
+        state.inferred_stop_sequence = options.inferred_stop_sequence;
+        state
+    }
+
+    /// Notes a text delta about to be sent, for time-to-first-token.
+    pub fn note_text_delta(&mut self, text: &str) {
+        if self.ttft.is_none() && !text.is_empty() {
+            self.ttft = Some(self.started_at.elapsed());
         }
     }
+
+    /// The usage to report once the stream ends, with its time-to-first-token.
+    pub fn into_usage(self) -> StreamUsage {
+        StreamUsage {
+            ttft: self.ttft,
+            ..self.usage_data
+        }
+    }
+
+    pub fn with_interleaved_thinking(mut self, interleaved_thinking: bool) -> Self {
+        self.interleaved_thinking = interleaved_thinking;
+        self
+    }
+
+    pub fn with_strict_json_validation(mut self, strict_json_validation: bool) -> Self {
+        self.strict_json_validation = strict_json_validation;
+        self
+    }
+
+    pub fn current_thinking_index(&self) -> Option<usize> {
+        self.thinking_blocks.keys().next_back().copied()
+    }
+
+    pub fn thinking_started(&self) -> bool {
+        !self.thinking_blocks.is_empty()
+    }
+
+    pub fn saw_thinking_delta(&self) -> bool {
+        self.thinking_blocks.values().any(|block| block.saw_delta)
+    }
+
+    /// Allocates the next content-block index for a thinking block and makes
+    /// it current.
+    pub fn open_thinking_block(&mut self) -> usize {
+        self.tool_block_counter += 1;
+        let claude_index = self.text_block_index + self.tool_block_counter;
+        self.thinking_blocks
+            .insert(claude_index, ThinkingBlockState::default());
+        claude_index
+    }
+
+    /// Marks the current thinking block as having received content and
+    /// returns its index.
+    pub fn record_thinking_delta(&mut self) -> Option<usize> {
+        let (&claude_index, block) = self.thinking_blocks.iter_mut().next_back()?;
+        block.saw_delta = true;
+        Some(claude_index)
+    }
+
+    /// True when a tool block was opened after the current thinking block, so
+    /// further reasoning belongs in a fresh block.
+    pub fn thinking_block_superseded(&self) -> bool {
+        let Some(thinking_index) = self.current_thinking_index() else {
+            return false;
+        };
+        self.interleaved_thinking
+            && self
+                .tool_calls
+                .values()
+                .filter_map(started_tool_index)
+                .any(|index| index > thinking_index)
+    }
 }
 
 pub fn started_tool_index(tool_call_state: &StreamingToolCallState) -> Option<usize> {
@@ -52,3 +182,58 @@ pub fn started_tool_index(tool_call_state: &StreamingToolCallState) -> Option<us
     }
     tool_call_state.claude_index
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamState, ThinkingBlockState};
+    use crate::models::StreamingToolCallState;
+
+    fn state_with_tool_after_thinking(interleaved: bool) -> StreamState {
+        let mut state = StreamState::new(true).with_interleaved_thinking(interleaved);
+        state
+            .thinking_blocks
+            .insert(1, ThinkingBlockState::default());
+        state.tool_calls.insert(
+            0,
+            StreamingToolCallState {
+                claude_index: Some(2),
+                started: true,
+                ..StreamingToolCallState::default()
+            },
+        );
+        state
+    }
+
+    #[test]
+    fn thinking_block_is_superseded_only_when_interleaving() {
+        assert!(state_with_tool_after_thinking(true).thinking_block_superseded());
+        assert!(!state_with_tool_after_thinking(false).thinking_block_superseded());
+    }
+
+    #[test]
+    fn thinking_after_tool_call_opens_block_with_next_index() {
+        let mut state = StreamState::new(true).with_interleaved_thinking(true);
+        assert_eq!(state.open_thinking_block(), 1);
+        assert_eq!(state.record_thinking_delta(), Some(1));
+
+        state.tool_block_counter += 1;
+        state.tool_calls.insert(
+            0,
+            StreamingToolCallState {
+                claude_index: Some(2),
+                started: true,
+                ..StreamingToolCallState::default()
+            },
+        );
+        assert!(state.thinking_block_superseded());
+
+        assert_eq!(state.open_thinking_block(), 3);
+        assert_eq!(state.current_thinking_index(), Some(3));
+        assert!(!state.thinking_block_superseded());
+        assert!(!state.thinking_blocks[&3].saw_delta);
+        assert_eq!(
+            state.thinking_blocks.keys().copied().collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+    }
+}
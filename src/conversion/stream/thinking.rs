@@ -42,7 +42,7 @@ pub async fn maybe_emit_realtime_fallback(
 }
 
 fn should_emit_realtime_fallback(choice: &StreamChoice, state: &StreamState) -> bool {
-    if !state.thinking_requested || state.thinking_started || state.saw_thinking_delta {
+    if !state.thinking_requested || state.thinking_started() || state.saw_thinking_delta() {
         return false;
     }
 
@@ -63,19 +63,28 @@ async fn maybe_start_thinking_block_from_delta(
     sender: &mut BodySender,
     state: &mut StreamState,
 ) -> io::Result<()> {
-    if state.thinking_started || thinking_delta(choice).is_none() {
+    if thinking_delta(choice).is_none() {
+        return Ok(());
+    }
+    if state.thinking_started() && !state.thinking_block_superseded() {
         return Ok(());
     }
 
     start_thinking_block(sender, state).await
 }
 
-async fn start_thinking_block(sender: &mut BodySender, state: &mut StreamState) -> io::Result<()> {
-    state.tool_block_counter += 1;
-    let claude_index = state.text_block_index + state.tool_block_counter;
-    state.thinking_block_index = Some(claude_index);
-    state.thinking_started = true;
-    send_thinking_block_start(sender, claude_index).await
+/// Opens a thinking block, first replaying any seeded prefill reasoning so
+/// the client sees the upstream reasoning as its continuation.
+pub async fn start_thinking_block(
+    sender: &mut BodySender,
+    state: &mut StreamState,
+) -> io::Result<()> {
+    let claude_index = state.open_thinking_block();
+    send_thinking_block_start(sender, claude_index).await?;
+    match state.thinking_seed.take() {
+        Some(seed) => send_thinking_delta(sender, claude_index, &seed).await,
+        None => Ok(()),
+    }
 }
 
 async fn maybe_send_thinking_delta(
@@ -83,14 +92,13 @@ async fn maybe_send_thinking_delta(
     sender: &mut BodySender,
     state: &mut StreamState,
 ) -> io::Result<()> {
-    let Some(claude_index) = state.thinking_block_index else {
+    let Some(payload) = thinking_delta(choice) else {
         return Ok(());
     };
-    let Some(payload) = thinking_delta(choice) else {
+    let Some(claude_index) = state.record_thinking_delta() else {
         return Ok(());
     };
 
-    state.saw_thinking_delta = true;
     send_thinking_delta(sender, claude_index, payload).await
 }
 
@@ -99,14 +107,13 @@ async fn maybe_send_signature_delta(
     sender: &mut BodySender,
     state: &mut StreamState,
 ) -> io::Result<()> {
-    let Some(claude_index) = state.thinking_block_index else {
+    let Some(payload) = thinking_signature_delta(choice) else {
         return Ok(());
     };
-    let Some(payload) = thinking_signature_delta(choice) else {
+    let Some(claude_index) = state.record_thinking_delta() else {
         return Ok(());
     };
 
-    state.saw_thinking_delta = true;
     send_signature_delta(sender, claude_index, payload).await
 }
 
@@ -119,7 +126,7 @@ fn log_fallback_start(
         phase = "thinking_fallback_start",
         model = context.model,
         message_id = context.message_id,
-        claude_index = state.thinking_block_index.unwrap_or(0),
+        claude_index = state.current_thinking_index().unwrap_or(0),
         stop_reason = state.final_stop_reason,
         has_content_delta = content_delta(choice).is_some(),
         has_tool_delta = tool_call_deltas(choice)
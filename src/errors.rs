@@ -2,10 +2,122 @@ use salvo::http::StatusCode;
 use serde::Deserialize;
 use serde::de::{Deserializer, IgnoredAny};
 
+use crate::upstream_metadata::UpstreamMetadata;
+
 #[derive(Debug)]
 pub struct UpstreamError {
     pub status: StatusCode,
     pub message: String,
+    pub kind: UpstreamErrorKind,
+    pub metadata: UpstreamMetadata,
+}
+
+/// What an upstream error means beyond its status, taken from the error
+/// body's `code` fields rather than from its message text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpstreamErrorKind {
+    #[default]
+    Other,
+    ContentFiltered,
+}
+
+pub const CONTENT_FILTER_ERROR_MESSAGE: &str =
+    "Request was blocked by the upstream content filter. Please revise the prompt.";
+
+const RETRYABLE_STATUS_CODES: [u16; 4] = [429, 502, 503, 504];
+const NON_RETRYABLE_STATUS_CODES: [u16; 6] = [400, 401, 403, 404, 413, 422];
+/// OpenAI and Azure `error.code` / `innererror.code` values for a request
+/// refused by the content filter, compared case-insensitively.
+const CONTENT_FILTER_CODES: [&str; 3] = [
+    "content_filter",
+    "content_policy_violation",
+    "ResponsibleAIPolicyViolation",
+];
+const SEND_FAILURE_MARKER: &str = "upstream request failed";
+const STREAM_READ_FAILURE_MARKERS: [&str; 2] = [
+    "failed to read upstream response body",
+    "streaming error from upstream",
+];
+
+impl UpstreamError {
+    /// An upstream non-success response, classified from its raw body.
+    pub fn from_error_body(status: StatusCode, body: &str, metadata: UpstreamMetadata) -> Self {
+        let kind = error_kind_from_body(body);
+        let message = match kind {
+            UpstreamErrorKind::ContentFiltered => CONTENT_FILTER_ERROR_MESSAGE.to_string(),
+            UpstreamErrorKind::Other => {
+                classify_openai_error(&extract_error_message_from_body(body))
+            }
+        };
+        Self {
+            status,
+            message,
+            kind,
+            metadata,
+        }
+    }
+
+    pub fn error_type(&self) -> &'static str {
+        if self.is_rate_limited() {
+            return "rate_limit_error";
+        }
+        error_type_for_status(self.status).unwrap_or("api_error")
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        let status = self.status.as_u16();
+        if NON_RETRYABLE_STATUS_CODES.contains(&status) {
+            return false;
+        }
+        RETRYABLE_STATUS_CODES.contains(&status) || self.message.contains(SEND_FAILURE_MARKER)
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        if self.status == StatusCode::TOO_MANY_REQUESTS {
+            return true;
+        }
+        let lowered = self.message.to_lowercase();
+        lowered.contains("rate limit") || lowered.contains("rate_limit")
+    }
+
+    /// Refused by the upstream content filter rather than malformed.
+    pub fn is_content_filtered(&self) -> bool {
+        self.kind == UpstreamErrorKind::ContentFiltered
+    }
+
+    /// The error a content-filtered response becomes under
+    /// `content_filter_mode = "error"`.
+    pub fn content_filtered(metadata: UpstreamMetadata) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: CONTENT_FILTER_ERROR_MESSAGE.to_string(),
+            kind: UpstreamErrorKind::ContentFiltered,
+            metadata,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn can_retry_for_stream(&self) -> bool {
+        if NON_RETRYABLE_STATUS_CODES.contains(&self.status.as_u16()) {
+            return false;
+        }
+        self.is_retryable()
+            || STREAM_READ_FAILURE_MARKERS
+                .iter()
+                .any(|marker| self.message.contains(marker))
+    }
+}
+
+pub fn error_type_for_status(status: StatusCode) -> Option<&'static str> {
+    match status.as_u16() {
+        400 | 413 | 422 => Some("invalid_request_error"),
+        401 => Some("authentication_error"),
+        403 => Some("permission_error"),
+        404 => Some("not_found_error"),
+        429 => Some("rate_limit_error"),
+        code if code >= 500 => Some("api_error"),
+        _ => None,
+    }
 }
 
 pub fn classify_openai_error(detail: &str) -> String {
@@ -17,6 +129,10 @@ pub fn classify_openai_error(detail: &str) -> String {
         return "OpenAI API is not available in your region. Consider using Azure OpenAI or a compatible regional provider.".to_string();
     }
 
+    if let Some(message) = classify_azure_error(&lowered) {
+        return message.to_string();
+    }
+
     if lowered.contains("invalid_api_key") || lowered.contains("unauthorized") {
         return "Invalid API key. Please verify OPENAI_API_KEY configuration.".to_string();
     }
@@ -41,6 +157,57 @@ pub fn classify_openai_error(detail: &str) -> String {
     detail.to_string()
 }
 
+/// Azure OpenAI reports these through `error.code` / `innererror.code`,
+/// which `extract_error_message_from_body` folds into the message. Content
+/// filtering is typed instead; see `error_kind_from_body`.
+fn classify_azure_error(lowered: &str) -> Option<&'static str> {
+    if lowered.contains("context_length_exceeded") {
+        return Some(
+            "Prompt exceeds the upstream model's context window. Please shorten the conversation or lower max_tokens.",
+        );
+    }
+    if lowered.contains("deploymentnotfound")
+        || lowered.contains("deployment_not_found")
+        || (lowered.contains("deployment") && lowered.contains("does not exist"))
+    {
+        return Some(
+            "Azure deployment not found. Please check that BIG_MODEL / MIDDLE_MODEL / SMALL_MODEL match deployment names.",
+        );
+    }
+    if lowered.contains("billing_hard_limit_reached") {
+        return Some(
+            "Upstream billing hard limit reached. Please raise the limit or verify account billing status.",
+        );
+    }
+    None
+}
+
+/// A body that is not a JSON error envelope, or whose codes are not known,
+/// is `Other`.
+pub fn error_kind_from_body(body: &str) -> UpstreamErrorKind {
+    let Ok(UpstreamErrorEnvelope {
+        error: Some(UpstreamErrorField::Payload(payload)),
+        ..
+    }) = serde_json::from_str::<UpstreamErrorEnvelope>(body)
+    else {
+        return UpstreamErrorKind::Other;
+    };
+    let inner_code = payload.innererror.and_then(|inner| inner.code);
+    let filtered = [payload.code, inner_code]
+        .into_iter()
+        .flatten()
+        .any(|code| {
+            CONTENT_FILTER_CODES
+                .iter()
+                .any(|known| code.eq_ignore_ascii_case(known))
+        });
+    if filtered {
+        UpstreamErrorKind::ContentFiltered
+    } else {
+        UpstreamErrorKind::Other
+    }
+}
+
 pub fn extract_error_message_from_body(body: &str) -> String {
     if let Ok(parsed) = serde_json::from_str::<UpstreamErrorEnvelope>(body) {
         if let Some(message) = parsed.error.and_then(UpstreamErrorField::into_message) {
@@ -84,7 +251,7 @@ enum UpstreamErrorField {
 impl UpstreamErrorField {
     fn into_message(self) -> Option<String> {
         match self {
-            UpstreamErrorField::Payload(payload) => payload.message,
+            UpstreamErrorField::Payload(payload) => payload.into_message(),
             UpstreamErrorField::Other(_) => None,
         }
     }
@@ -94,6 +261,40 @@ impl UpstreamErrorField {
 struct UpstreamErrorPayload {
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     message: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    code: Option<String>,
+    #[serde(default)]
+    innererror: Option<UpstreamInnerError>,
+}
+
+/// Azure's nested detail, e.g. `{"code": "ResponsibleAIPolicyViolation"}`.
+#[derive(Debug, Default, Deserialize)]
+struct UpstreamInnerError {
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    code: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    message: Option<String>,
+}
+
+impl UpstreamErrorPayload {
+    /// `message`, or `innererror.message` when absent, followed by the outer
+    /// and inner codes so they can be classified and shown to the client.
+    fn into_message(self) -> Option<String> {
+        let inner = self.innererror.unwrap_or_default();
+        let mut message = match (self.message, inner.message) {
+            (Some(outer), Some(inner)) if inner != outer => format!("{outer}: {inner}"),
+            (outer, inner) => outer.or(inner)?,
+        };
+        let codes: Vec<String> = [self.code, inner.code]
+            .into_iter()
+            .flatten()
+            .filter(|code| !code.is_empty() && !message.contains(code.as_str()))
+            .collect();
+        if !codes.is_empty() {
+            message.push_str(&format!(" (code: {})", codes.join(", ")));
+        }
+        Some(message)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -114,7 +315,85 @@ impl LooseString {
 
 #[cfg(test)]
 mod tests {
-    use super::extract_error_message_from_body;
+    use super::{
+        UpstreamError, UpstreamErrorKind, classify_openai_error, error_kind_from_body,
+        extract_error_message_from_body,
+    };
+    use crate::upstream_metadata::UpstreamMetadata;
+    use salvo::http::StatusCode;
+
+    fn upstream_error(status: u16, message: &str) -> UpstreamError {
+        UpstreamError {
+            status: StatusCode::from_u16(status).expect("valid status"),
+            message: message.to_string(),
+            kind: UpstreamErrorKind::Other,
+            metadata: UpstreamMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn error_type_classifies_by_status_and_rate_limit_message() {
+        assert_eq!(
+            upstream_error(401, "bad key").error_type(),
+            "authentication_error"
+        );
+        assert_eq!(upstream_error(503, "down").error_type(), "api_error");
+        assert_eq!(
+            upstream_error(400, "rate_limit exceeded").error_type(),
+            "rate_limit_error"
+        );
+    }
+
+    #[test]
+    fn retryable_status_codes_are_retried() {
+        for status in [429, 502, 503, 504] {
+            assert!(upstream_error(status, "boom").is_retryable(), "{status}");
+        }
+    }
+
+    #[test]
+    fn client_error_status_codes_are_not_retried() {
+        for status in [400, 401, 403, 404, 413, 422] {
+            assert!(!upstream_error(status, "boom").is_retryable(), "{status}");
+            assert!(
+                !upstream_error(status, "boom").can_retry_for_stream(),
+                "{status}"
+            );
+        }
+    }
+
+    #[test]
+    fn boundary_status_codes_are_not_retried() {
+        for status in [200, 408, 500, 501, 505] {
+            assert!(!upstream_error(status, "boom").is_retryable(), "{status}");
+        }
+    }
+
+    #[test]
+    fn send_failures_are_retried_regardless_of_status() {
+        let error = upstream_error(500, "upstream request failed: connection reset");
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn detects_rate_limit_by_status_or_message() {
+        assert!(upstream_error(429, "slow down").is_rate_limited());
+        assert!(
+            upstream_error(
+                400,
+                "Rate limit exceeded. Please retry later or upgrade your upstream quota."
+            )
+            .is_rate_limited()
+        );
+        assert!(!upstream_error(503, "unavailable").is_rate_limited());
+    }
+
+    #[test]
+    fn stream_read_errors_are_retryable_only_for_streams() {
+        let error = upstream_error(500, "failed to read upstream response body (status: 200)");
+        assert!(!error.is_retryable());
+        assert!(error.can_retry_for_stream());
+    }
 
     #[test]
     fn extracts_nested_error_message() {
@@ -140,6 +419,66 @@ mod tests {
         assert_eq!(extract_error_message_from_body(body), "nested");
     }
 
+    #[test]
+    fn folds_azure_codes_and_innererror_into_message() {
+        let body = r#"{"error":{"code":"content_filter","message":"The response was filtered","innererror":{"code":"ResponsibleAIPolicyViolation"}}}"#;
+        let message = extract_error_message_from_body(body);
+        assert_eq!(
+            message,
+            "The response was filtered (code: content_filter, ResponsibleAIPolicyViolation)"
+        );
+        assert_eq!(
+            error_kind_from_body(body),
+            UpstreamErrorKind::ContentFiltered
+        );
+
+        let body = r#"{"error":{"code":"DeploymentNotFound","innererror":{"message":"no such deployment"}}}"#;
+        assert_eq!(
+            extract_error_message_from_body(body),
+            "no such deployment (code: DeploymentNotFound)"
+        );
+    }
+
+    #[test]
+    fn classifies_azure_error_codes() {
+        for (detail, expected) in [
+            ("code: context_length_exceeded", "context window"),
+            ("(code: DeploymentNotFound)", "Azure deployment not found"),
+            ("billing_hard_limit_reached", "billing hard limit"),
+        ] {
+            assert!(classify_openai_error(detail).contains(expected), "{detail}");
+        }
+    }
+
+    #[test]
+    fn content_filtering_is_read_from_error_codes_not_message_text() {
+        let filtered = r#"{"error":{"code":"content_policy_violation","message":"blocked"}}"#;
+        let error = UpstreamError::from_error_body(
+            StatusCode::BAD_REQUEST,
+            filtered,
+            UpstreamMetadata::default(),
+        );
+        assert!(error.is_content_filtered());
+        assert!(error.message.contains("content filter"));
+
+        let mentions = r#"{"error":{"code":"invalid_value","message":"content_filter is not a valid tool name"}}"#;
+        let error = UpstreamError::from_error_body(
+            StatusCode::BAD_REQUEST,
+            mentions,
+            UpstreamMetadata::default(),
+        );
+        assert!(!error.is_content_filtered());
+        assert!(
+            error
+                .message
+                .contains("content_filter is not a valid tool name")
+        );
+        assert_eq!(
+            error_kind_from_body("content_filter"),
+            UpstreamErrorKind::Other
+        );
+    }
+
     #[test]
     fn returns_default_message_for_empty_body() {
         assert_eq!(
@@ -0,0 +1,45 @@
+use salvo::prelude::*;
+use std::time::{Duration, Instant};
+
+use super::error_response::{rate_limited, session_request_limit_reached};
+use crate::state::app_state;
+
+/// Takes one token from the identity's rate-limit bucket; `Err` carries how
+/// long until the next token is available.
+pub(crate) async fn check_rate_limit(identity_key: &str) -> Result<(), Duration> {
+    app_state()
+        .rate_limiter
+        .check(identity_key, Instant::now())
+        .await
+}
+
+/// Counts the request against `max_requests_per_session`; `Err` carries the
+/// cap once the identity's current session has used it up.
+pub(crate) async fn check_session_limit(identity_key: &str) -> Result<(), u64> {
+    let state = app_state();
+    match state.config.max_requests_per_session {
+        Some(max_requests)
+            if !state
+                .sessions
+                .try_admit_request(identity_key, max_requests)
+                .await =>
+        {
+            Err(max_requests)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rate limit and per-session request cap; renders the rejection and returns
+/// `false` when the request may not go upstream.
+pub(super) async fn admit_message(res: &mut Response, identity_key: &str) -> bool {
+    if let Err(retry_after) = check_rate_limit(identity_key).await {
+        rate_limited(res, retry_after);
+        return false;
+    }
+    if let Err(max_requests) = check_session_limit(identity_key).await {
+        session_request_limit_reached(res, max_requests);
+        return false;
+    }
+    true
+}
@@ -0,0 +1,240 @@
+use salvo::prelude::*;
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, SocketAddr as StdSocketAddr};
+
+use crate::constants::ANTHROPIC_BETA_HEADER;
+use crate::state::app_state;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ClientAuth {
+    base_key: Option<String>,
+    pub(super) device_tag: Option<String>,
+}
+
+pub(crate) fn build_identity_key(req: &Request, client_auth: &ClientAuth) -> String {
+    let ip_component = resolve_client_ip(req)
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let key_component = client_auth.base_key.as_deref().unwrap_or("anonymous");
+    let device_component = client_auth.device_tag.as_deref().unwrap_or("-");
+
+    let identity_source = format!("{ip_component}|{key_component}|{device_component}");
+    let mut hasher = Sha256::new();
+    hasher.update(identity_source.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn resolve_client_ip(req: &Request) -> Option<IpAddr> {
+    forwarded_ip(req).or_else(|| remote_peer_ip(req))
+}
+
+fn forwarded_ip(req: &Request) -> Option<IpAddr> {
+    for header_name in ["x-forwarded-for", "x-real-ip"] {
+        let Some(raw_value) = req
+            .headers()
+            .get(header_name)
+            .and_then(|value| value.to_str().ok())
+        else {
+            continue;
+        };
+
+        if let Some(ip) = parse_ip_from_header(raw_value) {
+            return Some(ip);
+        }
+    }
+
+    None
+}
+
+fn parse_ip_from_header(raw_value: &str) -> Option<IpAddr> {
+    raw_value.split(',').find_map(|segment| {
+        let candidate = segment.trim().trim_matches('"');
+        parse_ip_candidate(candidate)
+    })
+}
+
+fn parse_ip_candidate(candidate: &str) -> Option<IpAddr> {
+    if candidate.is_empty() || candidate.eq_ignore_ascii_case("unknown") {
+        return None;
+    }
+
+    if let Ok(ip) = candidate.parse::<IpAddr>() {
+        return Some(ip);
+    }
+
+    if let Ok(addr) = candidate.parse::<StdSocketAddr>() {
+        return Some(addr.ip());
+    }
+
+    None
+}
+
+fn remote_peer_ip(req: &Request) -> Option<IpAddr> {
+    if let Some(addr) = req.remote_addr().as_ipv4() {
+        return Some(IpAddr::V4(*addr.ip()));
+    }
+    if let Some(addr) = req.remote_addr().as_ipv6() {
+        return Some(IpAddr::V6(*addr.ip()));
+    }
+    None
+}
+
+pub(crate) fn validate_client_api_key_header(req: &Request) -> Result<ClientAuth, String> {
+    let config = &app_state().config;
+    let client_auth = extract_client_auth(req);
+
+    if config.anthropic_api_key.is_none() {
+        return Ok(client_auth.unwrap_or_default());
+    }
+
+    let Some(client_auth) = client_auth else {
+        return Err("Invalid API key. Please provide a valid Anthropic API key.".to_string());
+    };
+
+    if config.validate_client_api_key(client_auth.base_key.as_deref()) {
+        Ok(client_auth)
+    } else {
+        Err("Invalid API key. Please provide a valid Anthropic API key.".to_string())
+    }
+}
+
+fn extract_client_auth(req: &Request) -> Option<ClientAuth> {
+    let raw_key = extract_raw_client_key(req)?;
+    parse_client_auth(raw_key)
+}
+
+pub(super) fn extract_anthropic_beta(req: &Request) -> Option<Vec<String>> {
+    let flags: Vec<String> = req
+        .headers()
+        .get_all(ANTHROPIC_BETA_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(parse_beta_flags)
+        .collect();
+    (!flags.is_empty()).then_some(flags)
+}
+
+fn parse_beta_flags(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|flag| !flag.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+fn extract_raw_client_key(req: &Request) -> Option<&str> {
+    let x_api_key = req
+        .headers()
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+
+    if x_api_key.is_some() {
+        return x_api_key;
+    }
+
+    req.headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_bearer_token)
+}
+
+pub(crate) fn parse_bearer_token(authorization: &str) -> Option<&str> {
+    let (scheme, token) = authorization.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+    let token = token.trim();
+    if token.is_empty() { None } else { Some(token) }
+}
+
+fn parse_client_auth(raw_key: &str) -> Option<ClientAuth> {
+    let normalized = raw_key.trim();
+    if normalized.is_empty() {
+        return None;
+    }
+
+    let (base_key_raw, device_tag_raw) = match normalized.split_once('|') {
+        Some((base_key, device_tag)) => (base_key, Some(device_tag)),
+        None => (normalized, None),
+    };
+
+    let base_key = base_key_raw.trim();
+    if base_key.is_empty() {
+        return None;
+    }
+
+    Some(ClientAuth {
+        base_key: Some(base_key.to_string()),
+        device_tag: device_tag_raw
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_bearer_token, parse_beta_flags, parse_client_auth, parse_ip_candidate,
+        parse_ip_from_header,
+    };
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn parses_comma_separated_beta_flags() {
+        assert_eq!(
+            parse_beta_flags(
+                " interleaved-thinking-2025-05-14, ,token-efficient-tools-2025-02-19 "
+            ),
+            vec![
+                "interleaved-thinking-2025-05-14".to_string(),
+                "token-efficient-tools-2025-02-19".to_string()
+            ]
+        );
+        assert!(parse_beta_flags(" , ").is_empty());
+    }
+
+    #[test]
+    fn parses_plain_client_key() {
+        let auth = parse_client_auth("sk-ant-test").expect("client auth");
+        assert_eq!(auth.base_key.as_deref(), Some("sk-ant-test"));
+        assert_eq!(auth.device_tag.as_deref(), None);
+    }
+
+    #[test]
+    fn parses_client_key_with_device_suffix() {
+        let auth = parse_client_auth("sk-ant-test|device_001").expect("client auth");
+        assert_eq!(auth.base_key.as_deref(), Some("sk-ant-test"));
+        assert_eq!(auth.device_tag.as_deref(), Some("device_001"));
+    }
+
+    #[test]
+    fn rejects_client_key_with_empty_base() {
+        assert!(parse_client_auth("|device_001").is_none());
+        assert!(parse_client_auth("   ").is_none());
+    }
+
+    #[test]
+    fn parses_bearer_token_case_insensitively() {
+        assert_eq!(parse_bearer_token("Bearer abc"), Some("abc"));
+        assert_eq!(parse_bearer_token("bearer abc"), Some("abc"));
+        assert_eq!(parse_bearer_token("Basic abc"), None);
+    }
+
+    #[test]
+    fn parses_first_valid_ip_from_forwarded_header() {
+        let ip = parse_ip_from_header("unknown, 203.0.113.7, 198.51.100.9").expect("ip");
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)));
+    }
+
+    #[test]
+    fn parses_ip_candidates() {
+        let ipv4 = parse_ip_candidate("192.168.1.9").expect("ipv4");
+        assert_eq!(ipv4, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 9)));
+
+        let socket_ipv4 = parse_ip_candidate("10.0.0.5:8080").expect("socket ipv4");
+        assert_eq!(socket_ipv4, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)));
+    }
+}
@@ -0,0 +1,107 @@
+use salvo::http::StatusCode;
+use salvo::prelude::*;
+use serde::Serialize;
+use std::time::Duration;
+use tracing::error;
+
+pub(crate) fn unauthorized(res: &mut Response, message: &str) {
+    res.status_code(StatusCode::UNAUTHORIZED);
+    res.render(Json(DetailResponse {
+        detail: message.to_string(),
+    }));
+}
+
+pub(crate) fn bad_request(res: &mut Response, message: &str) {
+    res.status_code(StatusCode::BAD_REQUEST);
+    res.render(Json(DetailResponse {
+        detail: message.to_string(),
+    }));
+}
+
+pub(crate) fn payload_too_large(res: &mut Response, message: &str) {
+    res.status_code(StatusCode::PAYLOAD_TOO_LARGE);
+    res.render(Json(DetailResponse {
+        detail: message.to_string(),
+    }));
+}
+
+pub(crate) fn not_found(res: &mut Response, message: &str) {
+    res.status_code(StatusCode::NOT_FOUND);
+    res.render(Json(DetailResponse {
+        detail: message.to_string(),
+    }));
+}
+
+pub(crate) fn conflict(res: &mut Response, message: &str) {
+    res.status_code(StatusCode::CONFLICT);
+    res.render(Json(DetailResponse {
+        detail: message.to_string(),
+    }));
+}
+
+pub(crate) fn not_supported(res: &mut Response, message: &str) {
+    res.status_code(StatusCode::NOT_IMPLEMENTED);
+    res.render(Json(ErrorEnvelope {
+        envelope_type: "error",
+        error: ErrorBody {
+            error_type: "not_supported_error",
+            message: message.to_string(),
+        },
+    }));
+}
+
+pub(crate) fn rate_limited(res: &mut Response, retry_after: Duration) {
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let _ = res.add_header("Retry-After", retry_after_secs.to_string(), true);
+    res.status_code(StatusCode::TOO_MANY_REQUESTS);
+    res.render(Json(DetailResponse {
+        detail: format!("rate limit exceeded; retry after {retry_after_secs}s"),
+    }));
+}
+
+/// No `Retry-After`: the count only resets once the session expires.
+pub(super) fn session_request_limit_reached(res: &mut Response, max_requests: u64) {
+    res.status_code(StatusCode::TOO_MANY_REQUESTS);
+    res.render(Json(DetailResponse {
+        detail: session_limit_message(max_requests),
+    }));
+}
+
+pub(crate) fn session_limit_message(max_requests: u64) -> String {
+    format!("session request limit of {max_requests} reached")
+}
+
+pub(super) fn internal_error(res: &mut Response, message: &str) {
+    res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+    res.render(Json(DetailResponse {
+        detail: message.to_string(),
+    }));
+}
+
+pub(super) fn upstream_failed(res: &mut Response, status: StatusCode, message: &str) {
+    error!("Upstream error: {message}");
+    res.status_code(status);
+    res.render(Json(DetailResponse {
+        detail: message.to_string(),
+    }));
+}
+
+#[derive(Debug, Serialize)]
+struct DetailResponse {
+    detail: String,
+}
+
+/// Anthropic-shaped error body, for clients that branch on `error.type`.
+#[derive(Debug, Serialize)]
+struct ErrorEnvelope {
+    #[serde(rename = "type")]
+    envelope_type: &'static str,
+    error: ErrorBody,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    #[serde(rename = "type")]
+    error_type: &'static str,
+    message: String,
+}
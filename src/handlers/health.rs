@@ -0,0 +1,166 @@
+use salvo::http::StatusCode;
+use salvo::prelude::*;
+use serde::Serialize;
+use tracing::error;
+
+use crate::config::WireApi;
+use crate::conversion::request::{OpenAiChatRequest, OpenAiMessage, OpenAiUserMessage};
+use crate::metrics::metrics;
+use crate::middleware::access_log::AccessLogHandle;
+use crate::state::app_state;
+use crate::token_count::TOKEN_COUNT_APPROXIMATION_NOTE;
+use crate::ttft::TtftPercentiles;
+use crate::upstream::{RequestIds, UpstreamProbe};
+use crate::utils::now_timestamp_string;
+
+#[handler]
+pub async fn health_check(res: &mut Response) {
+    let state = app_state();
+    let config = &state.config;
+    let upstream_connection_warm = state.upstream.is_warm();
+    let upstreams = state.upstream.cached_probes().await;
+    let status = if upstreams.iter().all(|probe| probe.reachable) {
+        "healthy"
+    } else {
+        "degraded"
+    };
+    res.render(Json(HealthCheckResponse {
+        status: status.to_string(),
+        timestamp: now_timestamp_string(),
+        openai_api_configured: !config.openai_api_key.is_empty(),
+        api_key_valid: config.validate_openai_api_key_format(),
+        client_api_key_validation: config.anthropic_api_key.is_some(),
+        upstream_connection_warm,
+        upstreams,
+        ttft_ms: metrics().ttft_percentiles(),
+        warnings: vec![TOKEN_COUNT_APPROXIMATION_NOTE.to_string()],
+    }));
+}
+
+#[handler]
+pub async fn test_connection(depot: &mut Depot, res: &mut Response) {
+    let state = app_state();
+    let request_id = AccessLogHandle::from_depot(depot).request_id();
+    let ids = RequestIds {
+        session_id: "connection-test",
+        request_id: &request_id,
+    };
+
+    let upstream_result = match state.config.wire_api {
+        WireApi::Chat => run_chat_connection_test(state, ids).await,
+        WireApi::Responses => run_responses_connection_test(state, ids).await,
+    };
+
+    match upstream_result {
+        Ok(response_id) => res.render(Json(ConnectionTestSuccessResponse {
+            status: "success".to_string(),
+            message: "Successfully connected to upstream OpenAI-compatible API".to_string(),
+            model_used: state.config.small_model.clone(),
+            timestamp: now_timestamp_string(),
+            response_id,
+        })),
+        Err(error) => {
+            error!("Connection test failed: {}", error.message);
+            res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+            res.render(Json(ConnectionTestFailureResponse {
+                status: "failed".to_string(),
+                error_type: "API Error".to_string(),
+                message: error.message,
+                timestamp: now_timestamp_string(),
+                suggestions: vec![
+                    "Check OPENAI_API_KEY".to_string(),
+                    "Verify model permissions".to_string(),
+                    "Check provider rate limits".to_string(),
+                ],
+            }));
+        }
+    }
+}
+
+/// Keeps the chat connection test reply reproducible on upstreams that honour
+/// `seed`.
+const CONNECTION_TEST_SEED: i64 = 42;
+
+async fn run_chat_connection_test(
+    state: &crate::state::AppState,
+    ids: RequestIds<'_>,
+) -> Result<String, crate::errors::UpstreamError> {
+    let test_request = OpenAiChatRequest {
+        model: state.config.small_model.clone(),
+        messages: vec![OpenAiMessage::User(OpenAiUserMessage::from_text(
+            "Hello".to_string(),
+        ))],
+        max_tokens: 5,
+        temperature: Some(1.0),
+        reasoning_effort: None,
+        stream: false,
+        stream_options: None,
+        stop: None,
+        top_p: None,
+        top_k: None,
+        min_output_tokens: None,
+        tools: None,
+        tool_choice: None,
+        parallel_tool_calls: None,
+        seed: Some(CONNECTION_TEST_SEED),
+        frequency_penalty: None,
+        presence_penalty: None,
+        response_format: None,
+        user: None,
+    };
+
+    let (response, _) = state
+        .upstream
+        .chat_completion(&test_request, &state.config.small_model, ids)
+        .await?;
+    Ok(response.id().unwrap_or("unknown").to_string())
+}
+
+async fn run_responses_connection_test(
+    state: &crate::state::AppState,
+    ids: RequestIds<'_>,
+) -> Result<String, crate::errors::UpstreamError> {
+    let test_request = serde_json::json!({
+        "model": state.config.small_model.clone(),
+        "input": "Hello",
+        "max_output_tokens": 5,
+        "stream": false
+    });
+
+    let (response, _) = state
+        .upstream
+        .responses(&test_request, &state.config.small_model, ids)
+        .await?;
+    Ok(response.id().unwrap_or("unknown").to_string())
+}
+
+#[derive(Debug, Serialize)]
+struct HealthCheckResponse {
+    status: String,
+    timestamp: String,
+    openai_api_configured: bool,
+    api_key_valid: bool,
+    client_api_key_validation: bool,
+    upstream_connection_warm: bool,
+    upstreams: Vec<UpstreamProbe>,
+    ttft_ms: TtftPercentiles,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ConnectionTestFailureResponse {
+    status: String,
+    error_type: String,
+    message: String,
+    timestamp: String,
+    suggestions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ConnectionTestSuccessResponse {
+    status: String,
+    message: String,
+    model_used: String,
+    timestamp: String,
+    response_id: String,
+}
@@ -0,0 +1,110 @@
+use salvo::prelude::*;
+use serde::Serialize;
+
+use super::auth::parse_bearer_token;
+use super::error_response::{not_found, unauthorized};
+use crate::config::{Config, WireApi};
+use crate::metrics::metrics;
+use crate::state::app_state;
+
+#[handler]
+pub async fn metrics_endpoint(req: &mut Request, res: &mut Response) {
+    let config = &app_state().config;
+    if !config.metrics_enabled {
+        not_found(res, "metrics endpoint is disabled");
+        return;
+    }
+
+    if let Some(expected) = config.metrics_token.as_deref() {
+        let provided = req
+            .headers()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_bearer_token);
+        if provided != Some(expected) {
+            unauthorized(res, "invalid or missing metrics bearer token");
+            return;
+        }
+    }
+
+    let _ = res.add_header(
+        "Content-Type",
+        "text/plain; version=0.0.4; charset=utf-8",
+        true,
+    );
+    res.render(metrics().render());
+}
+
+#[handler]
+pub async fn root(res: &mut Response) {
+    let config = &app_state().config;
+    res.render(Json(RootResponse {
+        message: "Claude-to-OpenAI API Proxy (Rust/Salvo)".to_string(),
+        status: "running".to_string(),
+        config: RootConfig {
+            openai_base_url: config.openai_base_url.clone(),
+            api_key_configured: !config.openai_api_key.is_empty(),
+            client_api_key_validation: config.anthropic_api_key.is_some(),
+            wire_api: wire_api_name(&config.wire_api),
+            big_model: config.big_model.clone(),
+            middle_model: config.middle_model.clone(),
+            small_model: config.small_model.clone(),
+            model_aliases: model_alias_names(config),
+        },
+        endpoints: RootEndpoints {
+            messages: "/v1/messages".to_string(),
+            models: "/v1/models".to_string(),
+            usage: "/v1/usage".to_string(),
+            batches: "/v1/messages/batches".to_string(),
+            count_tokens: "/v1/messages/count_tokens".to_string(),
+            health: "/health".to_string(),
+            metrics: "/metrics".to_string(),
+            test_connection: "/test-connection".to_string(),
+        },
+    }));
+}
+
+fn model_alias_names(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = config.model_aliases.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+pub(super) fn wire_api_name(wire_api: &WireApi) -> String {
+    match wire_api {
+        WireApi::Chat => "chat".to_string(),
+        WireApi::Responses => "responses".to_string(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RootResponse {
+    message: String,
+    status: String,
+    config: RootConfig,
+    endpoints: RootEndpoints,
+}
+
+#[derive(Debug, Serialize)]
+struct RootConfig {
+    openai_base_url: String,
+    api_key_configured: bool,
+    client_api_key_validation: bool,
+    wire_api: String,
+    big_model: String,
+    middle_model: String,
+    small_model: String,
+    model_aliases: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RootEndpoints {
+    messages: String,
+    models: String,
+    usage: String,
+    batches: String,
+    count_tokens: String,
+    health: String,
+    metrics: String,
+    test_connection: String,
+}
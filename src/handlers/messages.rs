@@ -0,0 +1,281 @@
+use salvo::prelude::*;
+use tracing::field::Empty;
+use tracing::{Instrument, Span, debug, info, info_span, trace};
+
+use super::admission::admit_message;
+use super::auth::{ClientAuth, build_identity_key, validate_client_api_key_header};
+use super::error_response::{bad_request, unauthorized};
+use super::info::wire_api_name;
+use super::render::{render_completion, render_dry_run};
+use super::request_body::parse_messages_request;
+use super::streaming::{handle_chat_streaming_request, handle_responses_streaming_request};
+use crate::anthropic_version::{AnthropicVersion, resolve_anthropic_version};
+use crate::config::{Config, WireApi};
+use crate::constants::{ANTHROPIC_VERSION_HEADER, BETA_INTERLEAVED_THINKING_PREFIX};
+use crate::conversion::request::{
+    check_tool_schemas, convert_claude_to_openai, convert_claude_to_responses,
+    is_thinking_requested, model_tier, route_claude_request, seeded_thinking, session_user,
+};
+use crate::conversion::response::inferred_stop_sequence;
+use crate::conversion::stream::StreamOptions;
+use crate::metrics::metrics;
+use crate::middleware::access_log::AccessLogHandle;
+use crate::middleware::session_id::{SESSION_ID_HEADER, parse_client_session_id};
+use crate::models::ClaudeMessagesRequest;
+use crate::state::app_state;
+use crate::stateful_responses::continue_previous_response;
+use crate::upstream::RequestIds;
+
+#[handler]
+pub async fn create_message(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let state = app_state();
+    let access_log = AccessLogHandle::from_depot(depot);
+    let client_auth = match validate_client_api_key_header(req) {
+        Ok(value) => value,
+        Err(message) => {
+            unauthorized(res, &message);
+            return;
+        }
+    };
+    let anthropic_version = match negotiate_anthropic_version(req, depot) {
+        Ok(value) => value,
+        Err(message) => {
+            bad_request(res, &message);
+            return;
+        }
+    };
+
+    let request = match parse_messages_request(req, res).await {
+        Some(value) => value,
+        None => return,
+    };
+    if let Err(message) = check_tool_schemas(&request, &state.config) {
+        bad_request(res, &message);
+        return;
+    }
+
+    let span = info_span!(
+        "messages",
+        model = %request.model,
+        stream = request.stream.unwrap_or(false),
+        wire_api = %wire_api_name(state.config.wire_api_for(&request.model)),
+        session_id = Empty,
+        request_id = %access_log.request_id(),
+    );
+    dispatch_message(
+        req,
+        res,
+        request,
+        client_auth,
+        access_log,
+        anthropic_version,
+    )
+    .instrument(span)
+    .await;
+}
+
+/// The negotiated version is injected for the response middleware to echo.
+pub(crate) fn negotiate_anthropic_version(
+    req: &Request,
+    depot: &mut Depot,
+) -> Result<Option<AnthropicVersion>, String> {
+    let client_version = req
+        .headers()
+        .get(ANTHROPIC_VERSION_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let anthropic_version = resolve_anthropic_version(client_version, &app_state().config)?;
+    if let Some(version) = &anthropic_version {
+        depot.inject(version.clone());
+    }
+    Ok(anthropic_version)
+}
+
+fn log_downstream_request(
+    request: &ClaudeMessagesRequest,
+    client_auth: &ClientAuth,
+    access_log: &AccessLogHandle,
+    anthropic_version: Option<&AnthropicVersion>,
+) {
+    trace!(
+        phase = "downstream_request_full",
+        claude_request = %serde_json::to_string(request).unwrap_or_default(),
+        "Received downstream request (full)"
+    );
+
+    debug!(
+        phase = "downstream_request_summary",
+        request_id = %access_log.request_id(),
+        claude_model = %request.model,
+        stream = request.stream.unwrap_or(false),
+        max_tokens = request.max_tokens,
+        messages_len = request.messages.len(),
+        has_system = request.system.is_some(),
+        has_tools = request.tools.as_ref().map(|v| !v.is_empty()).unwrap_or(false),
+        has_tool_choice = request.tool_choice.is_some(),
+        has_device_tag = client_auth.device_tag.is_some(),
+        anthropic_version = anthropic_version.map(AnthropicVersion::as_str),
+        "Received downstream request (summary)"
+    );
+}
+
+async fn dispatch_message(
+    req: &Request,
+    res: &mut Response,
+    request: ClaudeMessagesRequest,
+    client_auth: ClientAuth,
+    access_log: AccessLogHandle,
+    anthropic_version: Option<AnthropicVersion>,
+) {
+    let state = app_state();
+    log_downstream_request(
+        &request,
+        &client_auth,
+        &access_log,
+        anthropic_version.as_ref(),
+    );
+    let stream = request.stream.unwrap_or(false);
+    let wire_api = state.config.wire_api_for(&request.model);
+    let tier = model_tier(&request.model).as_str();
+    metrics().record_messages_request(tier, stream, &wire_api_name(wire_api));
+    let identity_key = build_identity_key(req, &client_auth);
+    access_log.record_identity(&identity_key, client_auth.device_tag.as_deref());
+    if !admit_message(res, &identity_key).await {
+        return;
+    }
+    let route = route_claude_request(&request, &state.config);
+    access_log.record_models(&request.model, &route, stream);
+
+    let context = MessageContext {
+        session_id: resolve_message_session_id(req, &identity_key).await,
+        request_id: access_log.request_id(),
+        identity_key,
+        thinking_requested: is_thinking_requested(request.thinking.as_ref()),
+        interleaved_thinking: request.has_beta_prefix(BETA_INTERLEAVED_THINKING_PREFIX),
+        thinking_seed: thinking_seed(&request, &state.config),
+        inferred_stop_sequence: inferred_stop_sequence(&request, state.config.infer_stop_sequence),
+        access_log,
+    };
+    Span::current().record("session_id", context.session_id.as_str());
+
+    match wire_api {
+        WireApi::Chat => handle_chat_message(res, request, &context).await,
+        WireApi::Responses => handle_responses_message(res, request, &context).await,
+    }
+}
+
+/// A valid `X-Session-ID` pins the session when `allow_client_session_id` is
+/// on; the identity key still drives rate limiting and usage accounting.
+async fn resolve_message_session_id(req: &Request, identity_key: &str) -> String {
+    let state = app_state();
+    let client_session_id = state
+        .config
+        .allow_client_session_id
+        .then(|| {
+            parse_client_session_id(
+                req.headers()
+                    .get(SESSION_ID_HEADER)
+                    .and_then(|value| value.to_str().ok()),
+            )
+        })
+        .flatten();
+    match client_session_id {
+        Some(session_id) => {
+            info!(
+                phase = "client_session_id",
+                session_id = %session_id,
+                "Using client-provided session id"
+            );
+            session_id
+        }
+        None => state.sessions.resolve_session_id(identity_key).await,
+    }
+}
+
+/// Seeded prefill reasoning to replay in the streamed thinking block when
+/// `thinking_continuation_mode` is on and the request asks for thinking.
+fn thinking_seed(request: &ClaudeMessagesRequest, config: &Config) -> Option<String> {
+    if !config.thinking_continuation_mode || !is_thinking_requested(request.thinking.as_ref()) {
+        return None;
+    }
+    let seed = seeded_thinking(&request.messages)?;
+    debug!(
+        phase = "thinking_continuation",
+        seed_len = seed.len(),
+        "Continuing seeded thinking from the assistant prefill"
+    );
+    Some(seed)
+}
+
+pub(super) struct MessageContext {
+    pub(super) identity_key: String,
+    pub(super) session_id: String,
+    pub(super) request_id: String,
+    thinking_requested: bool,
+    interleaved_thinking: bool,
+    thinking_seed: Option<String>,
+    inferred_stop_sequence: Option<String>,
+    pub(super) access_log: AccessLogHandle,
+}
+
+impl MessageContext {
+    pub(super) fn upstream_ids(&self) -> RequestIds<'_> {
+        RequestIds {
+            session_id: &self.session_id,
+            request_id: &self.request_id,
+        }
+    }
+
+    pub(super) fn stream_options(&self) -> StreamOptions {
+        let config = &app_state().config;
+        StreamOptions {
+            thinking_requested: self.thinking_requested,
+            interleaved_thinking: self.interleaved_thinking,
+            strict_json_validation: config.buffers_tool_json(),
+            max_consecutive_send_errors: config.max_consecutive_send_errors,
+            content_filter_text: config.content_filter_text().map(str::to_string),
+            thinking_seed: self.thinking_seed.clone(),
+            inferred_stop_sequence: self.inferred_stop_sequence.clone(),
+        }
+    }
+}
+
+async fn handle_chat_message(
+    res: &mut Response,
+    request: ClaudeMessagesRequest,
+    context: &MessageContext,
+) {
+    let config = &app_state().config;
+    if !config.dry_run && !request.stream.unwrap_or(false) {
+        render_completion(res, &request, context).await;
+        return;
+    }
+
+    let mut openai_request = convert_claude_to_openai(&request, config);
+    openai_request.user = session_user(config, &context.session_id);
+    if config.dry_run {
+        render_dry_run(res, &request, &openai_request, context);
+        return;
+    }
+    handle_chat_streaming_request(res, request, &mut openai_request, context).await;
+}
+
+async fn handle_responses_message(
+    res: &mut Response,
+    request: ClaudeMessagesRequest,
+    context: &MessageContext,
+) {
+    let config = &app_state().config;
+    if !config.dry_run && !request.stream.unwrap_or(false) {
+        render_completion(res, &request, context).await;
+        return;
+    }
+
+    let mut responses_request = convert_claude_to_responses(&request, config);
+    responses_request.user = session_user(config, &context.session_id);
+    let prefix = continue_previous_response(&mut responses_request, &context.identity_key).await;
+    if config.dry_run {
+        render_dry_run(res, &request, &responses_request, context);
+        return;
+    }
+    handle_responses_streaming_request(res, request, &mut responses_request, prefix, context).await;
+}
@@ -0,0 +1,105 @@
+use salvo::prelude::*;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::conversion::request::map_claude_model_to_openai;
+use crate::state::app_state;
+use crate::utils::now_unix_secs;
+
+const CLAUDE_MODEL_ALIASES: [(&str, &str); 4] = [
+    ("claude-3-5-sonnet-20241022", "Claude 3.5 Sonnet"),
+    ("claude-3-5-haiku-20241022", "Claude 3.5 Haiku"),
+    ("claude-3-haiku-20240307", "Claude 3 Haiku"),
+    ("claude-3-opus-20240229", "Claude 3 Opus"),
+];
+
+/// Unauthenticated, as on OpenAI, so clients can discover models before
+/// they have a key configured.
+#[handler]
+pub async fn list_models(res: &mut Response) {
+    res.render(Json(model_list(&app_state().config, now_unix_secs())));
+}
+
+/// Serves both the Anthropic list shape (`type`, `display_name`, paging ids)
+/// and the OpenAI one (`object`, `created`, `owned_by`), so either kind of
+/// client can discover the mapped models.
+fn model_list(config: &Config, created: u64) -> ModelsListResponse {
+    let mut data: Vec<ModelInfo> = CLAUDE_MODEL_ALIASES
+        .iter()
+        .map(|(id, name)| ModelInfo {
+            model_type: "model",
+            object: "model",
+            id: id.to_string(),
+            display_name: format!("{name} ({})", map_claude_model_to_openai(id, config)),
+            created,
+            owned_by: "anthropic",
+        })
+        .collect();
+    for upstream_model in [&config.big_model, &config.middle_model, &config.small_model] {
+        if data.iter().any(|model| &model.id == upstream_model) {
+            continue;
+        }
+        data.push(ModelInfo {
+            model_type: "model",
+            object: "model",
+            id: upstream_model.clone(),
+            display_name: upstream_model.clone(),
+            created,
+            owned_by: "upstream",
+        });
+    }
+
+    ModelsListResponse {
+        object: "list",
+        first_id: data.first().map(|model| model.id.clone()),
+        last_id: data.last().map(|model| model.id.clone()),
+        has_more: false,
+        data,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ModelsListResponse {
+    object: &'static str,
+    data: Vec<ModelInfo>,
+    has_more: bool,
+    first_id: Option<String>,
+    last_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ModelInfo {
+    #[serde(rename = "type")]
+    model_type: &'static str,
+    object: &'static str,
+    id: String,
+    display_name: String,
+    created: u64,
+    owned_by: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::model_list;
+
+    #[test]
+    fn model_list_serializes_anthropic_and_openai_fields() {
+        let config = crate::upstream::test_support::test_config();
+        let payload =
+            serde_json::to_value(model_list(&config, 1_700_000_000)).expect("serialize models");
+
+        assert_eq!(payload["object"], "list");
+        assert_eq!(payload["has_more"], false);
+        let data = payload["data"].as_array().expect("model entries");
+        // big_model and middle_model are both gpt-4o and listed once.
+        assert_eq!(data.len(), 4 + 2);
+        assert_eq!(data[0]["id"], "claude-3-5-sonnet-20241022");
+        assert_eq!(data[0]["object"], "model");
+        assert_eq!(data[0]["type"], "model");
+        assert_eq!(data[0]["created"], 1_700_000_000);
+        assert_eq!(data[0]["owned_by"], "anthropic");
+        assert_eq!(data[4]["id"], serde_json::json!(config.big_model));
+        assert_eq!(data[4]["owned_by"], "upstream");
+        assert_eq!(payload["last_id"], serde_json::json!(config.small_model));
+    }
+}
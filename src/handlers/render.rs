@@ -0,0 +1,111 @@
+use salvo::prelude::*;
+use serde::Serialize;
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::error_response::{internal_error, upstream_failed};
+use super::messages::MessageContext;
+use super::streaming::set_sse_headers;
+use crate::capture::capture_conversion;
+use crate::completion::{CompletionError, complete_message};
+use crate::conversion::response::build_dry_run_response;
+use crate::conversion::stream::stream_dry_run_sse;
+use crate::metrics::metrics;
+use crate::models::ClaudeMessagesRequest;
+use crate::state::app_state;
+
+pub(super) fn render_dry_run<T: Serialize>(
+    res: &mut Response,
+    request: &ClaudeMessagesRequest,
+    converted_request: &T,
+    context: &MessageContext,
+) {
+    capture_conversion(
+        request,
+        converted_request,
+        &context.session_id,
+        &context.request_id,
+    );
+    info!(
+        phase = "dry_run_conversion",
+        request_id = %context.request_id,
+        claude_model = %request.model,
+        converted_request = %serde_json::to_string(converted_request).unwrap_or_default(),
+        "Dry run: skipped upstream call for converted request"
+    );
+
+    if !request.stream.unwrap_or(false) {
+        res.render(Json(build_dry_run_response(&request.model)));
+        return;
+    }
+
+    set_sse_headers(res);
+    let sender = res.channel();
+    tokio::spawn(stream_dry_run_sse(sender, request.model.clone()));
+}
+
+pub(super) async fn render_completion(
+    res: &mut Response,
+    request: &ClaudeMessagesRequest,
+    context: &MessageContext,
+) {
+    let upstream_started = Instant::now();
+    let result = complete_message(request, &context.identity_key, context.upstream_ids()).await;
+    context
+        .access_log
+        .record_upstream_latency(upstream_started.elapsed());
+
+    match result {
+        Ok((value, metadata)) => {
+            metadata.apply_to(res);
+            let usage = value.usage();
+            log_token_estimate_drift(
+                &context.request_id,
+                drift_estimate(request),
+                usage.input_tokens,
+            );
+            context
+                .access_log
+                .record_usage(usage.input_tokens, usage.output_tokens);
+            metrics().record_tokens(usage.input_tokens, usage.output_tokens);
+            res.render(Json(value))
+        }
+        Err(CompletionError::Upstream(error)) => {
+            metrics().record_upstream_error(error.status.as_u16(), error.error_type());
+            error.metadata.apply_to(res);
+            upstream_failed(res, error.status, &error.message)
+        }
+        Err(CompletionError::Conversion(message)) => {
+            context.access_log.record_error_type("conversion_error");
+            internal_error(res, &message)
+        }
+    }
+}
+
+/// Tokenizing the prompt again is only worth it when the drift log is visible.
+pub(super) fn drift_estimate(request: &ClaudeMessagesRequest) -> Option<u64> {
+    if !tracing::enabled!(tracing::Level::DEBUG) {
+        return None;
+    }
+    let estimated = app_state()
+        .tokenizer
+        .estimate_input_tokens(request.system.as_ref(), &request.messages);
+    Some(estimated as u64)
+}
+
+pub(super) fn log_token_estimate_drift(request_id: &str, estimated: Option<u64>, reported: u64) {
+    let Some(estimated) = estimated else {
+        return;
+    };
+    if reported == 0 {
+        return;
+    }
+    debug!(
+        phase = "token_estimate_drift",
+        request_id,
+        estimated_input_tokens = estimated,
+        reported_input_tokens = reported,
+        drift = reported as i64 - estimated as i64,
+        "Compared count_tokens estimate with upstream usage"
+    );
+}
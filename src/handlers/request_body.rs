@@ -0,0 +1,195 @@
+use salvo::prelude::*;
+use serde::Deserialize;
+
+use super::{bad_request, extract_anthropic_beta, payload_too_large};
+use crate::config::Config;
+use crate::conversion::request::check_tool_schemas;
+use crate::models::ClaudeMessagesRequest;
+use crate::multipart_request::multipart_to_messages_json;
+use crate::state::app_state;
+
+pub(super) async fn parse_messages_request(
+    req: &mut Request,
+    res: &mut Response,
+) -> Option<ClaudeMessagesRequest> {
+    let config = &app_state().config;
+    let read_limit = config.largest_body_max_size();
+    // The size limit can depend on the model and `max_tokens`, so read just
+    // those first and reject oversized bodies before the full parse.
+    if let Ok(payload) = req.payload_with_max_size(read_limit).await
+        && let Ok(hint) = serde_json::from_slice::<BodySizeHint>(payload)
+        && let Some(message) =
+            body_size_violation(config, &hint.model, hint.max_tokens, payload.len())
+    {
+        payload_too_large(res, &message);
+        return None;
+    }
+
+    let mut request = if is_multipart_form(req) {
+        parse_multipart_messages_request(req, res, read_limit).await?
+    } else {
+        match req
+            .parse_json_with_max_size::<ClaudeMessagesRequest>(read_limit)
+            .await
+        {
+            Ok(value) => value,
+            Err(error) => {
+                bad_request(res, &format!("invalid request body: {error}"));
+                return None;
+            }
+        }
+    };
+
+    request.anthropic_beta = extract_anthropic_beta(req);
+    Some(request)
+}
+
+fn is_multipart_form(req: &Request) -> bool {
+    req.content_type().is_some_and(|mime| {
+        mime.type_() == salvo::http::mime::MULTIPART
+            && mime.subtype() == salvo::http::mime::FORM_DATA
+    })
+}
+
+/// Browser uploads arrive as `multipart/form-data`; see
+/// [`multipart_to_messages_json`] for how the form maps onto a request.
+async fn parse_multipart_messages_request(
+    req: &mut Request,
+    res: &mut Response,
+    read_limit: usize,
+) -> Option<ClaudeMessagesRequest> {
+    let content_type = req
+        .content_type()
+        .map(|mime| mime.to_string())
+        .unwrap_or_default();
+    let payload = match req.payload_with_max_size(read_limit).await {
+        Ok(payload) => payload.clone(),
+        Err(error) => {
+            bad_request(res, &format!("invalid request body: {error}"));
+            return None;
+        }
+    };
+    let body_len = payload.len();
+    let request = multipart_to_messages_json(payload, &content_type)
+        .await
+        .and_then(|value| {
+            serde_json::from_value::<ClaudeMessagesRequest>(value)
+                .map_err(|error| format!("invalid request body: {error}"))
+        });
+    let request = match request {
+        Ok(request) => request,
+        Err(message) => {
+            bad_request(res, &message);
+            return None;
+        }
+    };
+    let config = &app_state().config;
+    if let Some(message) = body_size_violation(config, &request.model, request.max_tokens, body_len)
+    {
+        payload_too_large(res, &message);
+        return None;
+    }
+    Some(request)
+}
+
+/// Batch items never pass through the raw-body path, so each is sized by its
+/// serialized JSON against the same per-model limit and its tools are checked
+/// as on `/v1/messages`.
+pub(crate) fn check_batch_item(
+    config: &Config,
+    request: &ClaudeMessagesRequest,
+) -> Result<(), String> {
+    let body_len = serde_json::to_vec(request).map_or(0, |body| body.len());
+    if let Some(message) = body_size_violation(config, &request.model, request.max_tokens, body_len)
+    {
+        return Err(message);
+    }
+    check_tool_schemas(request, config)
+}
+
+#[derive(Deserialize)]
+struct BodySizeHint {
+    model: String,
+    max_tokens: u32,
+}
+
+fn body_size_violation(
+    config: &Config,
+    model: &str,
+    max_tokens: u32,
+    body_len: usize,
+) -> Option<String> {
+    let limit = config.body_max_size_for(model, max_tokens);
+    (body_len > limit).then(|| {
+        format!("request body is {body_len} bytes; the limit for model {model} is {limit} bytes")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{body_size_violation, check_batch_item, payload_too_large};
+    use crate::config::REQUEST_BODY_BASE_OVERHEAD;
+    use salvo::http::StatusCode;
+    use salvo::prelude::Response;
+    use std::collections::HashMap;
+
+    #[test]
+    fn rejects_oversized_bodies_for_models_with_smaller_limits() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.request_body_max_size = 1_000;
+        config.model_body_max_sizes = HashMap::from([
+            ("claude-3-opus".to_string(), 5_000),
+            ("claude-3-haiku".to_string(), 100),
+        ]);
+        assert_eq!(config.largest_body_max_size(), 5_000);
+
+        assert!(body_size_violation(&config, "claude-3-opus-20240229", 1024, 4_000).is_none());
+        assert!(body_size_violation(&config, "claude-3-5-sonnet", 1024, 4_000).is_some());
+        let message = body_size_violation(&config, "Claude-3-Haiku-20240307", 1024, 101)
+            .expect("haiku limit exceeded");
+        assert!(message.contains("limit for model Claude-3-Haiku-20240307 is 100 bytes"));
+
+        let mut res = Response::new();
+        payload_too_large(&mut res, &message);
+        assert_eq!(res.status_code, Some(StatusCode::PAYLOAD_TOO_LARGE));
+    }
+
+    #[test]
+    fn scales_body_limit_with_max_tokens_when_configured() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.request_body_max_size = 4 * 1024 * 1024;
+        config.request_body_max_size_per_token = 100.0;
+        let small_request_limit = REQUEST_BODY_BASE_OVERHEAD + 100 * 1024;
+
+        assert_eq!(
+            config.body_max_size_for("claude-3-5-sonnet", 1024),
+            small_request_limit
+        );
+        assert!(
+            body_size_violation(&config, "claude-3-5-sonnet", 1024, small_request_limit + 1)
+                .is_some()
+        );
+        assert_eq!(
+            config.body_max_size_for("claude-3-5-sonnet", 64_000),
+            4 * 1024 * 1024
+        );
+    }
+
+    #[test]
+    fn batch_items_get_the_per_model_body_limit() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.model_body_max_sizes = HashMap::from([("claude-3-haiku".to_string(), 100)]);
+        let item = |model: &str| {
+            serde_json::from_value(serde_json::json!({
+                "model": model,
+                "max_tokens": 16,
+                "messages": [{"role": "user", "content": "x".repeat(200)}]
+            }))
+            .expect("request")
+        };
+
+        assert!(check_batch_item(&config, &item("claude-3-5-sonnet")).is_ok());
+        let message = check_batch_item(&config, &item("claude-3-haiku")).expect_err("too large");
+        assert!(message.contains("limit for model claude-3-haiku is 100 bytes"));
+    }
+}
@@ -0,0 +1,205 @@
+use salvo::http::StatusCode;
+use salvo::http::body::BodySender;
+use salvo::prelude::*;
+use serde::Serialize;
+use std::future::Future;
+use std::time::Instant;
+use tracing::{Instrument, error, info_span};
+
+use super::messages::MessageContext;
+use super::render::{drift_estimate, log_token_estimate_drift};
+use crate::capture::capture_conversion;
+use crate::conversion::request::{OpenAiChatRequest, OpenAiResponsesRequest, ResponsesInputPrefix};
+use crate::conversion::stream::{
+    StreamOptions, StreamUsage, stream_openai_responses_to_claude_sse, stream_openai_to_claude_sse,
+    stream_text_sse,
+};
+use crate::errors::UpstreamError;
+use crate::metrics::metrics;
+use crate::middleware::access_log::AccessLogHandle;
+use crate::models::ClaudeMessagesRequest;
+use crate::state::app_state;
+use crate::stateful_responses::record_response;
+use crate::upstream_metadata::UpstreamMetadata;
+
+fn record_stream_usage(access_log: &AccessLogHandle, usage: &StreamUsage) {
+    access_log.record_usage(usage.input_tokens, usage.output_tokens);
+    metrics().record_tokens(usage.input_tokens, usage.output_tokens);
+    if let Some(ttft) = usage.ttft {
+        access_log.record_ttft(ttft);
+        metrics().record_ttft(ttft);
+    }
+}
+
+pub(super) async fn handle_chat_streaming_request(
+    res: &mut Response,
+    request: ClaudeMessagesRequest,
+    openai_request: &mut OpenAiChatRequest,
+    context: &MessageContext,
+) {
+    openai_request.enable_stream_usage();
+    capture_conversion(
+        &request,
+        openai_request,
+        &context.session_id,
+        &context.request_id,
+    );
+    let upstream_started = Instant::now();
+    let upstream_result = app_state()
+        .upstream
+        .chat_completion_stream(
+            openai_request,
+            &openai_request.model,
+            context.upstream_ids(),
+        )
+        .await;
+    context
+        .access_log
+        .record_upstream_latency(upstream_started.elapsed());
+    match upstream_result {
+        Ok(upstream_response) => spawn_downstream_stream(
+            res,
+            &request,
+            context,
+            upstream_response,
+            stream_openai_to_claude_sse,
+        ),
+        Err(error) => render_streaming_upstream_error(res, error, &request.model),
+    }
+}
+
+pub(super) async fn handle_responses_streaming_request(
+    res: &mut Response,
+    request: ClaudeMessagesRequest,
+    responses_request: &mut OpenAiResponsesRequest,
+    prefix: Option<ResponsesInputPrefix>,
+    context: &MessageContext,
+) {
+    responses_request.enable_stream();
+    capture_conversion(
+        &request,
+        responses_request,
+        &context.session_id,
+        &context.request_id,
+    );
+    let upstream_started = Instant::now();
+    let upstream_result = app_state()
+        .upstream
+        .responses_stream(
+            responses_request,
+            &responses_request.model,
+            context.upstream_ids(),
+        )
+        .await;
+    context
+        .access_log
+        .record_upstream_latency(upstream_started.elapsed());
+    let upstream_response = match upstream_result {
+        Ok(value) => value,
+        Err(error) => {
+            record_response(&context.identity_key, prefix, None).await;
+            render_streaming_upstream_error(res, error, &request.model);
+            return;
+        }
+    };
+
+    let identity_key = context.identity_key.clone();
+    let convert = |upstream_response, sender, model, options| async move {
+        let usage =
+            stream_openai_responses_to_claude_sse(upstream_response, sender, model, options).await;
+        record_response(&identity_key, prefix, usage.response_id.as_deref()).await;
+        usage
+    };
+    spawn_downstream_stream(res, &request, context, upstream_response, convert);
+}
+
+/// Opens the SSE channel and runs `convert` in the background; session usage,
+/// token drift and the access log are settled once the stream ends.
+fn spawn_downstream_stream<F, Fut>(
+    res: &mut Response,
+    request: &ClaudeMessagesRequest,
+    context: &MessageContext,
+    upstream_response: reqwest::Response,
+    convert: F,
+) where
+    F: FnOnce(reqwest::Response, BodySender, String, StreamOptions) -> Fut + Send + 'static,
+    Fut: Future<Output = StreamUsage> + Send + 'static,
+{
+    UpstreamMetadata::from_headers(
+        upstream_response.headers(),
+        &app_state().config.echo_upstream_headers,
+    )
+    .apply_to(res);
+    set_sse_headers(res);
+    let sender = res.channel();
+    let model = request.model.clone();
+    let options = context.stream_options();
+    let sessions = app_state().sessions.clone();
+    let identity_key = context.identity_key.clone();
+    let access_log = context.access_log.clone();
+    access_log.defer();
+    let estimated_input_tokens = drift_estimate(request);
+    let request_id = context.request_id.clone();
+    let span = info_span!("downstream_stream", request_id = %context.request_id);
+    tokio::spawn(
+        async move {
+            let usage = convert(upstream_response, sender, model, options).await;
+            sessions
+                .add_usage(&identity_key, usage.input_tokens, usage.output_tokens)
+                .await;
+            log_token_estimate_drift(&request_id, estimated_input_tokens, usage.input_tokens);
+            record_stream_usage(&access_log, &usage);
+            access_log.finish();
+        }
+        .instrument(span),
+    );
+}
+
+/// A content-filter `400` becomes a normal stream carrying the replacement
+/// text unless `content_filter_mode = "error"`.
+fn render_streaming_upstream_error(res: &mut Response, error: UpstreamError, model: &str) {
+    metrics().record_upstream_error(error.status.as_u16(), error.error_type());
+    error.metadata.apply_to(res);
+    if error.is_content_filtered()
+        && let Some(text) = app_state().config.content_filter_text()
+    {
+        set_sse_headers(res);
+        let sender = res.channel();
+        tokio::spawn(stream_text_sse(sender, model.to_string(), text.to_string()));
+        return;
+    }
+    render_streaming_error(res, error.status, error.message);
+}
+
+fn render_streaming_error(res: &mut Response, status: StatusCode, message: String) {
+    error!("Streaming upstream error: {}", message);
+    res.status_code(status);
+    res.render(Json(StreamingErrorResponse {
+        response_type: "error".to_string(),
+        error: ErrorDetail {
+            error_type: "api_error".to_string(),
+            message,
+        },
+    }));
+}
+
+pub(super) fn set_sse_headers(res: &mut Response) {
+    res.status_code(StatusCode::OK);
+    let _ = res.add_header("Cache-Control", "no-cache", true);
+    let _ = res.add_header("Connection", "keep-alive", true);
+    let _ = res.add_header("Content-Type", "text/event-stream; charset=utf-8", true);
+}
+
+#[derive(Debug, Serialize)]
+struct StreamingErrorResponse {
+    #[serde(rename = "type")]
+    response_type: String,
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorDetail {
+    #[serde(rename = "type")]
+    error_type: String,
+    message: String,
+}
@@ -0,0 +1,14 @@
+use salvo::prelude::*;
+
+use super::{unauthorized, validate_client_api_key_header};
+use crate::state::app_state;
+
+#[handler]
+pub async fn usage_stats(req: &mut Request, res: &mut Response) {
+    if let Err(message) = validate_client_api_key_header(req) {
+        unauthorized(res, &message);
+        return;
+    }
+
+    res.render(Json(app_state().sessions.usage_stats().await));
+}
@@ -1,13 +1,32 @@
+mod admin;
+mod anthropic_version;
 mod app;
+mod batches;
+mod capture;
+mod completion;
 mod config;
+mod config_file;
 mod constants;
 mod conversion;
 mod errors;
 mod handlers;
+mod metrics;
+mod middleware;
+mod model_routes;
 mod models;
+mod multipart_request;
+mod rate_limit;
 mod state;
+mod stateful_responses;
+mod telemetry;
+mod token_count;
+mod ttft;
 mod upstream;
+mod upstream_breaker;
+mod upstream_metadata;
 mod upstream_parse;
+mod upstream_proxy;
+mod upstream_retry;
 mod utils;
 
 #[tokio::main]
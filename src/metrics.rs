@@ -0,0 +1,248 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+
+use crate::ttft::{TtftPercentiles, TtftWindow};
+
+mod endpoint;
+
+use endpoint::endpoint_label;
+
+const UPSTREAM_LATENCY_BUCKETS: [f64; 12] = [
+    0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+const TTFT_BUCKETS: [f64; 11] = [0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.5, 5.0, 10.0, 30.0, 60.0];
+/// Prefix for every exported metric name, e.g. `bridge_requests_total`.
+const METRICS_NAMESPACE: &str = "bridge";
+
+pub struct Metrics {
+    registry: Registry,
+    requests_total: IntCounterVec,
+    messages_requests_total: IntCounterVec,
+    upstream_latency_seconds: HistogramVec,
+    stream_ttft_seconds: Histogram,
+    ttft_window: TtftWindow,
+    streaming_chunks_total: IntCounter,
+    upstream_errors_total: IntCounterVec,
+    tokens_total: IntCounterVec,
+    active_sessions: IntGauge,
+    session_cleanup_removed_total: IntCounter,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some(METRICS_NAMESPACE.to_string()), None)
+            .expect("metrics namespace should be valid");
+        let requests_total = IntCounterVec::new(
+            Opts::new("requests_total", "HTTP requests served, by final status"),
+            &["endpoint", "status"],
+        )
+        .expect("requests_total definition should be valid");
+        let messages_requests_total = IntCounterVec::new(
+            Opts::new("messages_requests_total", "Messages requests received"),
+            &["model_tier", "stream", "wire_api"],
+        )
+        .expect("messages_requests_total definition should be valid");
+        let upstream_latency_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "upstream_latency_seconds",
+                "Time until upstream response headers arrive",
+            )
+            .buckets(UPSTREAM_LATENCY_BUCKETS.to_vec()),
+            &["model", "kind"],
+        )
+        .expect("upstream_latency_seconds definition should be valid");
+        let stream_ttft_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "stream_ttft_seconds",
+                "Time from stream start until the first text delta",
+            )
+            .buckets(TTFT_BUCKETS.to_vec()),
+        )
+        .expect("stream_ttft_seconds definition should be valid");
+        let streaming_chunks_total = IntCounter::new(
+            "streaming_chunks_total",
+            "Body chunks read from upstream streams",
+        )
+        .expect("streaming_chunks_total definition should be valid");
+        let upstream_errors_total = IntCounterVec::new(
+            Opts::new(
+                "upstream_errors_total",
+                "Upstream errors returned to clients",
+            ),
+            &["status", "classified_type"],
+        )
+        .expect("upstream_errors_total definition should be valid");
+        let tokens_total = IntCounterVec::new(
+            Opts::new("tokens_total", "Tokens reported by upstream usage"),
+            &["direction"],
+        )
+        .expect("tokens_total definition should be valid");
+        let active_sessions = IntGauge::new("active_sessions", "Sessions currently tracked")
+            .expect("active_sessions definition should be valid");
+        let session_cleanup_removed_total = IntCounter::new(
+            "session_cleanup_removed_total",
+            "Sessions removed by expiry cleanup",
+        )
+        .expect("session_cleanup_removed_total definition should be valid");
+
+        let metrics = Self {
+            registry,
+            requests_total,
+            messages_requests_total,
+            upstream_latency_seconds,
+            stream_ttft_seconds,
+            ttft_window: TtftWindow::default(),
+            streaming_chunks_total,
+            upstream_errors_total,
+            tokens_total,
+            active_sessions,
+            session_cleanup_removed_total,
+        };
+        metrics.register_all();
+        metrics
+    }
+
+    fn register_all(&self) {
+        let collectors: [Box<dyn prometheus::core::Collector>; 9] = [
+            Box::new(self.requests_total.clone()),
+            Box::new(self.messages_requests_total.clone()),
+            Box::new(self.upstream_latency_seconds.clone()),
+            Box::new(self.stream_ttft_seconds.clone()),
+            Box::new(self.streaming_chunks_total.clone()),
+            Box::new(self.upstream_errors_total.clone()),
+            Box::new(self.tokens_total.clone()),
+            Box::new(self.active_sessions.clone()),
+            Box::new(self.session_cleanup_removed_total.clone()),
+        ];
+        for collector in collectors {
+            self.registry
+                .register(collector)
+                .expect("metric names should be unique");
+        }
+    }
+
+    /// Counted once per request when its access log entry is finished, so
+    /// streams report the status they ended with.
+    pub fn record_http_request(&self, path: &str, status: u16) {
+        self.requests_total
+            .with_label_values(&[endpoint_label(path), status.to_string().as_str()])
+            .inc();
+    }
+
+    /// Labelled by model tier rather than the client's model name, which
+    /// clients choose freely and would create a series per name.
+    pub fn record_messages_request(&self, model_tier: &str, stream: bool, wire_api: &str) {
+        let stream = if stream { "true" } else { "false" };
+        self.messages_requests_total
+            .with_label_values(&[model_tier, stream, wire_api])
+            .inc();
+    }
+
+    pub fn observe_upstream_latency(&self, model: &str, kind: &str, elapsed: Duration) {
+        self.upstream_latency_seconds
+            .with_label_values(&[model, kind])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_ttft(&self, ttft: Duration) {
+        self.stream_ttft_seconds.observe(ttft.as_secs_f64());
+        self.ttft_window.record(ttft);
+    }
+
+    pub fn record_stream_chunk(&self) {
+        self.streaming_chunks_total.inc();
+    }
+
+    pub fn ttft_percentiles(&self) -> TtftPercentiles {
+        self.ttft_window.percentiles()
+    }
+
+    pub fn record_upstream_error(&self, status: u16, classified_type: &str) {
+        self.upstream_errors_total
+            .with_label_values(&[status.to_string().as_str(), classified_type])
+            .inc();
+    }
+
+    pub fn record_tokens(&self, input_tokens: u64, output_tokens: u64) {
+        self.tokens_total
+            .with_label_values(&["input"])
+            .inc_by(input_tokens);
+        self.tokens_total
+            .with_label_values(&["output"])
+            .inc_by(output_tokens);
+    }
+
+    pub fn set_active_sessions(&self, count: usize) {
+        self.active_sessions.set(count as i64);
+    }
+
+    pub fn record_session_cleanup(&self, removed: usize) {
+        self.session_cleanup_removed_total.inc_by(removed as u64);
+    }
+
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        let _ = TextEncoder::new().encode(&self.registry.gather(), &mut buffer);
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Metrics;
+
+    #[test]
+    fn renders_recorded_metrics_in_prometheus_format() {
+        let metrics = Metrics::new();
+        metrics.record_http_request("/v1/messages", 200);
+        metrics.record_http_request("/v1/messages/batches/msgbatch_1/results", 404);
+        metrics.record_messages_request("middle", true, "chat");
+        metrics.observe_upstream_latency("gpt-4o", "stream", Duration::from_millis(300));
+        metrics.record_upstream_error(429, "rate_limit_error");
+        metrics.record_tokens(12, 34);
+        metrics.record_ttft(Duration::from_millis(400));
+        metrics.record_stream_chunk();
+        metrics.set_active_sessions(3);
+        metrics.record_session_cleanup(2);
+
+        let output = metrics.render();
+        assert!(
+            output.contains(r#"bridge_requests_total{endpoint="/v1/messages",status="200"} 1"#)
+        );
+        assert!(
+            output.contains(
+                r#"bridge_requests_total{endpoint="/v1/messages/batches",status="404"} 1"#
+            )
+        );
+        assert!(output.contains(
+            r#"bridge_messages_requests_total{model_tier="middle",stream="true",wire_api="chat"} 1"#
+        ));
+        assert!(
+            output.contains(
+                r#"bridge_upstream_latency_seconds_count{kind="stream",model="gpt-4o"} 1"#
+            )
+        );
+        assert!(output.contains(
+            r#"bridge_upstream_errors_total{classified_type="rate_limit_error",status="429"} 1"#
+        ));
+        assert!(output.contains(r#"bridge_tokens_total{direction="input"} 12"#));
+        assert!(output.contains(r#"bridge_tokens_total{direction="output"} 34"#));
+        assert!(output.contains("bridge_stream_ttft_seconds_count 1"));
+        assert!(output.contains("bridge_streaming_chunks_total 1"));
+        assert!(output.contains("bridge_active_sessions 3"));
+        assert!(output.contains("bridge_session_cleanup_removed_total 2"));
+    }
+}
@@ -0,0 +1,24 @@
+/// Routed paths reported as-is in the `endpoint` label; batch paths collapse
+/// to their collection so ids don't create a series each.
+const KNOWN_ENDPOINTS: [&str; 9] = [
+    "/",
+    "/health",
+    "/test-connection",
+    "/metrics",
+    "/v1/models",
+    "/v1/usage",
+    "/v1/messages",
+    "/v1/messages/count_tokens",
+    "/admin/sessions",
+];
+const BATCHES_ENDPOINT: &str = "/v1/messages/batches";
+
+pub(super) fn endpoint_label(path: &str) -> &'static str {
+    if let Some(known) = KNOWN_ENDPOINTS.iter().find(|endpoint| **endpoint == path) {
+        return known;
+    }
+    if path.starts_with(BATCHES_ENDPOINT) {
+        return BATCHES_ENDPOINT;
+    }
+    "other"
+}
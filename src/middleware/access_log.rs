@@ -0,0 +1,277 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use salvo::http::StatusCode;
+use salvo::http::header::HeaderValue;
+use salvo::prelude::*;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::errors::error_type_for_status;
+use crate::metrics::metrics;
+use crate::middleware::request_id::{REQUEST_ID_HEADER, resolve_request_id};
+
+pub struct AccessLog;
+
+#[handler]
+impl AccessLog {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        let incoming = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok());
+        let request_id = resolve_request_id(incoming);
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            res.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+        let handle =
+            AccessLogHandle::with_request_id(request_id, req.method().as_str(), req.uri().path());
+        depot.inject(handle.clone());
+        ctrl.call_next(req, depot, res).await;
+
+        let status = res.status_code.unwrap_or(StatusCode::OK);
+        handle.record_status(status);
+        if !handle.is_deferred() {
+            handle.finish();
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessLogHandle {
+    inner: Arc<Mutex<AccessLogEntry>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessLogEntry {
+    pub request_id: String,
+    pub method: String,
+    pub path: String,
+    pub identity_hash: Option<String>,
+    pub device_tag: Option<String>,
+    pub claude_model: Option<String>,
+    pub upstream_model: Option<String>,
+    pub stream: bool,
+    pub status_code: u16,
+    pub upstream_latency_ms: Option<u64>,
+    pub total_latency_ms: u64,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub ttft_ms: Option<u64>,
+    pub error_type: Option<String>,
+    started: Instant,
+    deferred: bool,
+    emitted: bool,
+}
+
+impl AccessLogHandle {
+    pub fn new(method: &str, path: &str) -> Self {
+        Self::with_request_id(Uuid::new_v4().to_string(), method, path)
+    }
+
+    pub fn with_request_id(request_id: String, method: &str, path: &str) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(AccessLogEntry {
+                request_id,
+                method: method.to_string(),
+                path: path.to_string(),
+                identity_hash: None,
+                device_tag: None,
+                claude_model: None,
+                upstream_model: None,
+                stream: false,
+                status_code: StatusCode::OK.as_u16(),
+                upstream_latency_ms: None,
+                total_latency_ms: 0,
+                input_tokens: None,
+                output_tokens: None,
+                ttft_ms: None,
+                error_type: None,
+                started: Instant::now(),
+                deferred: false,
+                emitted: false,
+            })),
+        }
+    }
+
+    pub fn from_depot(depot: &Depot) -> Self {
+        depot
+            .obtain::<Self>()
+            .cloned()
+            .unwrap_or_else(|_| Self::new("-", "-"))
+    }
+
+    pub fn request_id(&self) -> String {
+        self.with_entry(|entry| entry.request_id.clone())
+    }
+
+    pub fn record_identity(&self, identity_hash: &str, device_tag: Option<&str>) {
+        self.with_entry(|entry| {
+            entry.identity_hash = Some(identity_hash.to_string());
+            entry.device_tag = device_tag.map(ToOwned::to_owned);
+        });
+    }
+
+    pub fn record_models(&self, claude_model: &str, upstream_model: &str, stream: bool) {
+        self.with_entry(|entry| {
+            entry.claude_model = Some(claude_model.to_string());
+            entry.upstream_model = Some(upstream_model.to_string());
+            entry.stream = stream;
+        });
+    }
+
+    pub fn record_upstream_latency(&self, elapsed: Duration) {
+        self.with_entry(|entry| entry.upstream_latency_ms = Some(elapsed.as_millis() as u64));
+    }
+
+    pub fn record_usage(&self, input_tokens: u64, output_tokens: u64) {
+        self.with_entry(|entry| {
+            entry.input_tokens = Some(input_tokens);
+            entry.output_tokens = Some(output_tokens);
+        });
+    }
+
+    pub fn record_ttft(&self, ttft: Duration) {
+        self.with_entry(|entry| entry.ttft_ms = Some(ttft.as_millis() as u64));
+    }
+
+    pub fn record_error_type(&self, error_type: &str) {
+        self.with_entry(|entry| entry.error_type = Some(error_type.to_string()));
+    }
+
+    pub fn record_status(&self, status: StatusCode) {
+        self.with_entry(|entry| {
+            entry.status_code = status.as_u16();
+            if entry.error_type.is_none() {
+                entry.error_type = error_type_for_status(status).map(ToOwned::to_owned);
+            }
+        });
+    }
+
+    pub fn defer(&self) {
+        self.with_entry(|entry| entry.deferred = true);
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.with_entry(|entry| entry.deferred)
+    }
+
+    pub fn finish(&self) -> Option<AccessLogEntry> {
+        let entry = self.with_entry(|entry| {
+            if entry.emitted {
+                return None;
+            }
+            entry.emitted = true;
+            entry.total_latency_ms = entry.started.elapsed().as_millis() as u64;
+            Some(entry.clone())
+        })?;
+        metrics().record_http_request(&entry.path, entry.status_code);
+        emit(&entry);
+        Some(entry)
+    }
+
+    fn with_entry<R>(&self, f: impl FnOnce(&mut AccessLogEntry) -> R) -> R {
+        let mut entry = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut entry)
+    }
+}
+
+fn emit(entry: &AccessLogEntry) {
+    info!(
+        phase = "access_log",
+        request_id = %entry.request_id,
+        method = %entry.method,
+        path = %entry.path,
+        identity_hash = entry.identity_hash.as_deref().unwrap_or("-"),
+        device_tag = entry.device_tag.as_deref().unwrap_or("-"),
+        claude_model = entry.claude_model.as_deref().unwrap_or("-"),
+        upstream_model = entry.upstream_model.as_deref().unwrap_or("-"),
+        stream = entry.stream,
+        status_code = entry.status_code,
+        upstream_latency_ms = ?entry.upstream_latency_ms,
+        total_latency_ms = entry.total_latency_ms,
+        input_tokens = ?entry.input_tokens,
+        output_tokens = ?entry.output_tokens,
+        ttft_ms = ?entry.ttft_ms,
+        error_type = entry.error_type.as_deref().unwrap_or("-"),
+        "Request completed"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccessLogHandle;
+    use salvo::http::StatusCode;
+    use std::time::Duration;
+
+    fn populated_handle(stream: bool) -> AccessLogHandle {
+        let handle = AccessLogHandle::new("POST", "/v1/messages");
+        handle.record_identity("identity-hash", Some("device_001"));
+        handle.record_models("claude-3-5-sonnet-20241022", "gpt-4o", stream);
+        handle.record_upstream_latency(Duration::from_millis(25));
+        handle
+    }
+
+    #[test]
+    fn non_streaming_entry_has_all_fields() {
+        let handle = populated_handle(false);
+        handle.record_usage(10, 20);
+        handle.record_status(StatusCode::OK);
+
+        let entry = handle.finish().expect("entry should be emitted");
+        assert!(!entry.request_id.is_empty());
+        assert_eq!(entry.identity_hash.as_deref(), Some("identity-hash"));
+        assert_eq!(entry.device_tag.as_deref(), Some("device_001"));
+        assert_eq!(
+            entry.claude_model.as_deref(),
+            Some("claude-3-5-sonnet-20241022")
+        );
+        assert_eq!(entry.upstream_model.as_deref(), Some("gpt-4o"));
+        assert!(!entry.stream);
+        assert_eq!(entry.status_code, 200);
+        assert_eq!(entry.upstream_latency_ms, Some(25));
+        assert_eq!(entry.input_tokens, Some(10));
+        assert_eq!(entry.output_tokens, Some(20));
+        assert!(entry.error_type.is_none());
+    }
+
+    #[test]
+    fn streaming_entry_is_emitted_once_after_usage_is_known() {
+        let handle = populated_handle(true);
+        handle.defer();
+        handle.record_status(StatusCode::OK);
+        assert!(handle.is_deferred());
+
+        handle.record_usage(5, 7);
+        handle.record_ttft(Duration::from_millis(120));
+        let entry = handle.finish().expect("entry should be emitted");
+        assert!(entry.stream);
+        assert_eq!(entry.ttft_ms, Some(120));
+        assert_eq!(entry.input_tokens, Some(5));
+        assert_eq!(entry.output_tokens, Some(7));
+        assert!(handle.finish().is_none());
+    }
+
+    #[test]
+    fn derives_error_type_from_status_unless_recorded() {
+        let handle = AccessLogHandle::new("POST", "/v1/messages");
+        handle.record_status(StatusCode::UNAUTHORIZED);
+        let entry = handle.finish().expect("entry");
+        assert_eq!(entry.error_type.as_deref(), Some("authentication_error"));
+
+        let handle = AccessLogHandle::new("POST", "/v1/messages");
+        handle.record_error_type("overloaded_error");
+        handle.record_status(StatusCode::SERVICE_UNAVAILABLE);
+        let entry = handle.finish().expect("entry");
+        assert_eq!(entry.error_type.as_deref(), Some("overloaded_error"));
+    }
+}
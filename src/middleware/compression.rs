@@ -0,0 +1,73 @@
+use salvo::compression::{Compression, CompressionLevel};
+use salvo::http::mime;
+
+/// Compresses JSON bodies with gzip or brotli as the client's
+/// `Accept-Encoding` allows. Only `application/json` qualifies, and SSE
+/// bodies are channels the middleware never touches, so event streams
+/// always reach the client uncompressed.
+pub fn compression_handler(min_size_bytes: usize) -> Compression {
+    Compression::new()
+        .disable_all()
+        .enable_brotli(CompressionLevel::Default)
+        .enable_gzip(CompressionLevel::Default)
+        .content_types(&[mime::APPLICATION_JSON])
+        .min_length(min_size_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo::http::ResBody;
+    use salvo::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
+    use salvo::prelude::*;
+    use salvo::test::TestClient;
+    use serde_json::json;
+
+    use super::compression_handler;
+
+    const MIN_SIZE_BYTES: usize = 64;
+
+    #[handler]
+    async fn large_json(res: &mut Response) {
+        res.render(Json(json!({ "text": "x".repeat(MIN_SIZE_BYTES * 4) })));
+    }
+
+    #[handler]
+    async fn event_stream(res: &mut Response) {
+        let (mut sender, body) = ResBody::channel();
+        let _ = res.add_header("Content-Type", "text/event-stream; charset=utf-8", true);
+        res.body(body);
+        tokio::spawn(async move {
+            let frame = format!("event: ping\ndata: {}\n\n", "x".repeat(MIN_SIZE_BYTES * 4));
+            let _ = sender.send_data(frame).await;
+        });
+    }
+
+    fn service() -> Service {
+        let router = Router::new()
+            .hoop(compression_handler(MIN_SIZE_BYTES))
+            .push(Router::with_path("json").get(large_json))
+            .push(Router::with_path("sse").get(event_stream));
+        Service::new(router)
+    }
+
+    async fn content_encoding(path: &str) -> Option<String> {
+        let res = TestClient::get(format!("http://127.0.0.1:5800/{path}"))
+            .add_header(ACCEPT_ENCODING, "gzip", true)
+            .send(&service())
+            .await;
+        res.headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    }
+
+    #[tokio::test]
+    async fn gzips_json_bodies_over_the_minimum_size() {
+        assert_eq!(content_encoding("json").await.as_deref(), Some("gzip"));
+    }
+
+    #[tokio::test]
+    async fn leaves_event_streams_uncompressed() {
+        assert_eq!(content_encoding("sse").await, None);
+    }
+}
@@ -0,0 +1,54 @@
+use salvo::cors::{AllowOrigin, Cors, CorsHandler};
+use salvo::http::header::HeaderValue;
+use salvo::http::{HeaderName, Method};
+
+use crate::constants::{ANTHROPIC_BETA_HEADER, ANTHROPIC_VERSION_HEADER};
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+use crate::middleware::session_id::SESSION_ID_HEADER;
+use crate::upstream_metadata::echoed_header_name;
+
+/// Browsers let preflight results be cached for at most this long.
+const PREFLIGHT_MAX_AGE_SECS: usize = 86_400;
+
+/// Headers are listed explicitly because a `*` wildcard never covers
+/// `Authorization` in browsers.
+const ALLOWED_REQUEST_HEADERS: [&str; 7] = [
+    "content-type",
+    "x-api-key",
+    "authorization",
+    ANTHROPIC_VERSION_HEADER,
+    ANTHROPIC_BETA_HEADER,
+    REQUEST_ID_HEADER,
+    SESSION_ID_HEADER,
+];
+
+/// `*` anywhere in the list allows every origin; otherwise only exact
+/// matches are echoed back. Preflight `OPTIONS` requests get a 204 for every
+/// route, including `/v1/messages`, before routing happens. Headers copied
+/// via `echo_upstream_headers` are exposed so browser clients can read them.
+pub fn cors_handler(allowed_origins: &[String], echo_headers: &[String]) -> CorsHandler {
+    let mut exposed = vec![HeaderName::from_static(REQUEST_ID_HEADER)];
+    exposed.extend(
+        echo_headers
+            .iter()
+            .filter_map(|name| echoed_header_name(name)),
+    );
+    Cors::new()
+        .allow_origin(allow_origin(allowed_origins))
+        .allow_methods(vec![Method::GET, Method::POST, Method::OPTIONS])
+        .allow_headers(ALLOWED_REQUEST_HEADERS.to_vec())
+        .expose_headers(exposed)
+        .max_age(PREFLIGHT_MAX_AGE_SECS)
+        .into_handler()
+}
+
+fn allow_origin(allowed_origins: &[String]) -> AllowOrigin {
+    if allowed_origins.iter().any(|origin| origin == "*") {
+        return AllowOrigin::any();
+    }
+    AllowOrigin::list(
+        allowed_origins
+            .iter()
+            .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+    )
+}
@@ -0,0 +1,5 @@
+pub mod access_log;
+pub mod compression;
+pub mod cors;
+pub mod request_id;
+pub mod session_id;
@@ -0,0 +1,53 @@
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Reuses a client-supplied `X-Request-ID` when it is short and limited to
+/// `[A-Za-z0-9._:-]`; anything else is replaced with a fresh UUID so the id
+/// stays safe to log and forward.
+pub fn resolve_request_id(incoming: Option<&str>) -> String {
+    incoming
+        .map(str::trim)
+        .filter(|value| is_valid_request_id(value))
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+fn is_valid_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_request_id;
+    use uuid::Uuid;
+
+    #[test]
+    fn keeps_well_formed_incoming_id() {
+        assert_eq!(
+            resolve_request_id(Some(" req_abc-123.4:5 ")),
+            "req_abc-123.4:5"
+        );
+    }
+
+    #[test]
+    fn replaces_missing_or_invalid_ids_with_uuid() {
+        let too_long = "a".repeat(129);
+        for incoming in [
+            None,
+            Some(""),
+            Some("has space"),
+            Some("bad\"quote"),
+            Some(too_long.as_str()),
+        ] {
+            let resolved = resolve_request_id(incoming);
+            assert!(Uuid::parse_str(&resolved).is_ok(), "{incoming:?}");
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use uuid::Uuid;
+
+pub const SESSION_ID_HEADER: &str = "x-session-id";
+
+const MAX_SESSION_ID_LEN: usize = 128;
+
+/// Accepts a client-supplied `X-Session-ID` only when it parses as a UUID;
+/// anything else falls back to the identity-derived session.
+pub fn parse_client_session_id(incoming: Option<&str>) -> Option<String> {
+    incoming
+        .map(str::trim)
+        .filter(|value| value.len() <= MAX_SESSION_ID_LEN && Uuid::try_parse(value).is_ok())
+        .map(ToOwned::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_client_session_id;
+
+    #[test]
+    fn accepts_only_uuid_session_ids() {
+        assert_eq!(
+            parse_client_session_id(Some(" 6f1c2a4e-0b7d-4c3f-9a8e-2d5b7c9e1f03 ")).as_deref(),
+            Some("6f1c2a4e-0b7d-4c3f-9a8e-2d5b7c9e1f03")
+        );
+        assert_eq!(parse_client_session_id(Some("my-session")), None);
+        assert_eq!(parse_client_session_id(Some("")), None);
+        assert_eq!(parse_client_session_id(None), None);
+    }
+}
@@ -0,0 +1,186 @@
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::config::{WireApi, parse_wire_api};
+
+/// One `[[model_routing]]` entry as written in the config file.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RawModelRoute {
+    pub pattern: String,
+    pub target: String,
+    #[serde(default)]
+    pub wire_api_override: Option<String>,
+}
+
+/// A `[[model_routing]]` entry with its pattern compiled at startup. The
+/// pattern is matched against the downstream (Claude) model name and is not
+/// anchored, so `^o3-` and `o3-` differ.
+#[derive(Clone, Debug)]
+pub struct ModelRoute {
+    pub pattern: Regex,
+    pub target: String,
+    pub wire_api_override: Option<WireApi>,
+}
+
+pub fn compile_model_routes(raw_routes: Vec<RawModelRoute>) -> Result<Vec<ModelRoute>, String> {
+    raw_routes
+        .into_iter()
+        .enumerate()
+        .map(|(index, raw)| compile_model_route(index, raw))
+        .collect()
+}
+
+fn compile_model_route(index: usize, raw: RawModelRoute) -> Result<ModelRoute, String> {
+    let pattern = Regex::new(&raw.pattern).map_err(|error| {
+        format!(
+            "Invalid model_routing[{index}].pattern '{}': {error}",
+            raw.pattern
+        )
+    })?;
+    let target = raw.target.trim().to_string();
+    if target.is_empty() {
+        return Err(format!("model_routing[{index}].target must not be empty"));
+    }
+    let wire_api_override = raw
+        .wire_api_override
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| parse_wire_api(Some(value)))
+        .transpose()
+        .map_err(|error| format!("model_routing[{index}].wire_api_override: {error}"))?;
+
+    Ok(ModelRoute {
+        pattern,
+        target,
+        wire_api_override,
+    })
+}
+
+/// Routes from numbered `MODEL_ROUTE_<n>_PATTERN` / `MODEL_ROUTE_<n>_TARGET`
+/// pairs (plus optional `MODEL_ROUTE_<n>_WIRE_API_OVERRIDE`), counting up
+/// from 0 until the first missing pattern. `read` looks up one variable.
+pub fn env_model_routes(read: impl Fn(&str) -> Option<String>) -> Vec<RawModelRoute> {
+    (0..)
+        .map_while(|index| {
+            let var = |suffix: &str| read(&format!("MODEL_ROUTE_{index}_{suffix}"));
+            Some(RawModelRoute {
+                pattern: var("PATTERN")?,
+                target: var("TARGET").unwrap_or_default(),
+                wire_api_override: var("WIRE_API_OVERRIDE"),
+            })
+        })
+        .collect()
+}
+
+/// The upstream model an alias or route picked for a Claude model name
+/// (`None` falls back to name-based mapping) and the wire API to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModelTarget<'a> {
+    pub model: Option<&'a str>,
+    pub wire_api: &'a WireApi,
+}
+
+/// Entries are tried in file order; the first matching pattern wins.
+pub fn find_model_route<'a>(
+    routes: &'a [ModelRoute],
+    claude_model: &str,
+) -> Option<&'a ModelRoute> {
+    routes
+        .iter()
+        .find(|route| route.pattern.is_match(claude_model))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{RawModelRoute, compile_model_routes, env_model_routes, find_model_route};
+    use crate::config::WireApi;
+
+    fn raw(pattern: &str, target: &str, wire_api_override: Option<&str>) -> RawModelRoute {
+        RawModelRoute {
+            pattern: pattern.to_string(),
+            target: target.to_string(),
+            wire_api_override: wire_api_override.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn first_matching_pattern_wins() {
+        let routes = compile_model_routes(vec![
+            raw("^o3-", "o3", Some("responses")),
+            raw("(?i)opus", "gpt-4.1", None),
+            raw(".*", "fallback", None),
+        ])
+        .expect("valid routes");
+
+        let route = find_model_route(&routes, "o3-mini").expect("o3 route");
+        assert_eq!(route.target, "o3");
+        assert_eq!(route.wire_api_override, Some(WireApi::Responses));
+        assert_eq!(
+            find_model_route(&routes, "claude-3-OPUS").map(|route| route.target.as_str()),
+            Some("gpt-4.1")
+        );
+        assert_eq!(
+            find_model_route(&routes, "my-o3-model").map(|route| route.target.as_str()),
+            Some("fallback")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_pattern_target_and_wire_api() {
+        assert!(compile_model_routes(vec![raw("(", "x", None)]).is_err());
+        assert!(compile_model_routes(vec![raw("x", " ", None)]).is_err());
+        let error = compile_model_routes(vec![raw("x", "y", Some("grpc"))]).expect_err("bad wire");
+        assert!(error.contains("model_routing[0].wire_api_override"));
+    }
+
+    #[test]
+    fn route_override_selects_wire_api_per_model() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.model_routes =
+            compile_model_routes(vec![raw("^o3", "o3", Some("responses"))]).expect("valid route");
+
+        assert_eq!(config.wire_api_for("o3-mini"), &WireApi::Responses);
+        assert_eq!(config.wire_api_for("claude-3-5-sonnet"), &WireApi::Chat);
+    }
+
+    #[test]
+    fn alias_keeps_global_wire_api_over_a_matching_route() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.model_routes =
+            compile_model_routes(vec![raw("^o3", "o3", Some("responses"))]).expect("valid route");
+        config.model_aliases = HashMap::from([("o3-mini".to_string(), "gpt-4o".to_string())]);
+
+        let target = config.model_target_for("o3-mini");
+        assert_eq!(target.model, Some("gpt-4o"));
+        assert_eq!(target.wire_api, &WireApi::Chat);
+        assert_eq!(config.wire_api_for("o3-mini"), &WireApi::Chat);
+        assert_eq!(config.model_target_for("o3").wire_api, &WireApi::Responses);
+    }
+
+    #[test]
+    fn reads_numbered_env_routes_until_the_first_gap() {
+        let vars: HashMap<&str, &str> = HashMap::from([
+            ("MODEL_ROUTE_0_PATTERN", "^claude-3-opus-20240229$"),
+            ("MODEL_ROUTE_0_TARGET", "gpt-4.1"),
+            ("MODEL_ROUTE_1_PATTERN", "(?i)sonnet-4"),
+            ("MODEL_ROUTE_1_TARGET", "o3"),
+            ("MODEL_ROUTE_1_WIRE_API_OVERRIDE", "responses"),
+            ("MODEL_ROUTE_3_PATTERN", "ignored"),
+            ("MODEL_ROUTE_3_TARGET", "ignored"),
+        ]);
+        let raw_routes = env_model_routes(|name| vars.get(name).map(|value| value.to_string()));
+        assert_eq!(raw_routes.len(), 2);
+        let routes = compile_model_routes(raw_routes).expect("valid routes");
+
+        let exact = find_model_route(&routes, "claude-3-opus-20240229").expect("exact route");
+        assert_eq!(exact.target, "gpt-4.1");
+        assert!(find_model_route(&routes, "claude-3-opus-latest").is_none());
+
+        let regex = find_model_route(&routes, "claude-SONNET-4-5").expect("regex route");
+        assert_eq!(regex.target, "o3");
+        assert_eq!(regex.wire_api_override, Some(WireApi::Responses));
+    }
+}
@@ -8,6 +8,9 @@ pub struct ClaudeMessage {
     pub role: String,
     #[serde(default)]
     pub content: Option<ClaudeContent>,
+    /// Only sent on legacy OpenAI `function` role messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -33,6 +36,18 @@ pub enum ClaudeContentBlock {
         #[serde(flatten)]
         extra: BTreeMap<String, Value>,
     },
+    #[serde(rename = "document")]
+    Document {
+        source: Option<ClaudeDocumentSource>,
+        #[serde(flatten)]
+        extra: BTreeMap<String, Value>,
+    },
+    #[serde(rename = "video")]
+    Video {
+        source: Option<ClaudeVideoSource>,
+        #[serde(flatten)]
+        extra: BTreeMap<String, Value>,
+    },
     #[serde(rename = "tool_use")]
     ToolUse {
         id: Option<String>,
@@ -45,6 +60,16 @@ pub enum ClaudeContentBlock {
     ToolResult {
         tool_use_id: Option<String>,
         content: Option<Value>,
+        is_error: Option<bool>,
+        #[serde(flatten)]
+        extra: BTreeMap<String, Value>,
+    },
+    #[serde(rename = "thinking")]
+    Thinking {
+        #[serde(default)]
+        thinking: String,
+        #[serde(default)]
+        signature: Option<String>,
         #[serde(flatten)]
         extra: BTreeMap<String, Value>,
     },
@@ -58,6 +83,28 @@ pub struct ClaudeImageSource {
     pub source_type: Option<String>,
     pub media_type: Option<String>,
     pub data: Option<String>,
+    pub url: Option<String>,
+    /// OpenAI's `low` / `high` / `auto` resolution hint; not part of
+    /// Claude's schema but passed through when a client sends it.
+    pub detail: Option<String>,
+}
+
+/// Only `url` sources are forwarded; the bridge never fetches the video.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClaudeVideoSource {
+    #[serde(rename = "type")]
+    pub source_type: Option<String>,
+    pub url: Option<String>,
+}
+
+/// `base64` sources carry encoded bytes (typically PDF); `text` sources carry
+/// the document as plain text in `data`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClaudeDocumentSource {
+    #[serde(rename = "type")]
+    pub source_type: Option<String>,
+    pub media_type: Option<String>,
+    pub data: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -83,6 +130,9 @@ pub enum ClaudeSystemBlock {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ClaudeToolDefinition {
+    /// Set for Anthropic built-in tools (`computer_20250124`, ...).
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub tool_type: Option<String>,
     pub name: Option<String>,
     #[serde(default)]
     pub description: Option<String>,
@@ -105,6 +155,8 @@ pub struct ClaudeNamedToolChoice {
     #[serde(rename = "type")]
     pub choice_type: Option<String>,
     pub name: Option<String>,
+    #[serde(default)]
+    pub disable_parallel_tool_use: Option<bool>,
     #[serde(flatten)]
     pub extra: BTreeMap<String, Value>,
 }
@@ -127,9 +179,40 @@ pub struct ClaudeMessagesRequest {
     #[serde(default)]
     pub top_p: Option<f64>,
     #[serde(default)]
+    pub top_k: Option<u32>,
+    #[serde(default)]
     pub tools: Option<Vec<ClaudeToolDefinition>>,
     #[serde(default)]
     pub tool_choice: Option<ClaudeToolChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Not part of the Anthropic API; accepted for OpenAI-compatible backends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    /// OpenAI-style switch; `tool_choice.disable_parallel_tool_use` wins
+    /// when both are given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<Value>,
+    /// Taken from the `anthropic-beta` request header, never from the body.
+    #[serde(skip)]
+    pub anthropic_beta: Option<Vec<String>>,
+}
+
+impl ClaudeMessagesRequest {
+    pub fn has_beta_prefix(&self, prefix: &str) -> bool {
+        self.anthropic_beta
+            .as_ref()
+            .is_some_and(|flags| flags.iter().any(|flag| flag.starts_with(prefix)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClaudeBatchRequest {
+    pub requests: Vec<ClaudeMessagesRequest>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -153,6 +236,9 @@ pub struct StreamingToolCallState {
     pub id: Option<String>,
     pub name: Option<String>,
     pub args_buffer: String,
+    /// Bytes of `args_buffer` already emitted as `input_json_delta`.
+    pub args_sent_len: usize,
+    /// Set once the upstream finished the call; later argument deltas are dropped.
     pub json_sent: bool,
     pub claude_index: Option<usize>,
     pub started: bool,
@@ -0,0 +1,161 @@
+use std::convert::Infallible;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use futures_util::stream;
+use multer::Multipart;
+use multer::bytes::Bytes;
+use serde_json::{Map, Value, json};
+
+/// Form fields copied onto the request as-is; `max_tokens` is parsed as a
+/// number and `stream` as a boolean.
+const REQUEST_FIELDS: [&str; 4] = ["model", "max_tokens", "system", "stream"];
+
+/// Builds a Messages request body from a `multipart/form-data` upload so
+/// browser clients can attach images without base64-encoding them. Image
+/// file parts become base64 `image` blocks and every other field becomes a
+/// `text` block, both in form order, inside a single user message.
+pub async fn multipart_to_messages_json(body: Bytes, content_type: &str) -> Result<Value, String> {
+    let boundary = multer::parse_boundary(content_type)
+        .map_err(|error| format!("invalid multipart content type: {error}"))?;
+    let mut multipart = Multipart::new(
+        stream::once(async move { Ok::<_, Infallible>(body) }),
+        boundary,
+    );
+
+    let mut request = Map::new();
+    let mut content = Vec::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|error| format!("invalid multipart body: {error}"))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        let media_type = field.content_type().map(ToString::to_string);
+        let is_file = field.file_name().is_some();
+        let data = field
+            .bytes()
+            .await
+            .map_err(|error| format!("invalid multipart field '{name}': {error}"))?;
+
+        if is_file {
+            content.push(image_block(&name, media_type.as_deref(), &data)?);
+            continue;
+        }
+        let text = String::from_utf8(data.to_vec())
+            .map_err(|_| format!("multipart field '{name}' is not valid UTF-8"))?;
+        if REQUEST_FIELDS.contains(&name.as_str()) {
+            request.insert(name.clone(), request_field(&name, &text)?);
+        } else if !text.trim().is_empty() {
+            content.push(json!({"type": "text", "text": text}));
+        }
+    }
+
+    if content.is_empty() {
+        return Err("multipart body has no text or image parts".to_string());
+    }
+    request.insert(
+        "messages".to_string(),
+        json!([{"role": "user", "content": content}]),
+    );
+    Ok(Value::Object(request))
+}
+
+fn image_block(name: &str, media_type: Option<&str>, data: &[u8]) -> Result<Value, String> {
+    let media_type = media_type
+        .filter(|media_type| media_type.starts_with("image/"))
+        .ok_or_else(|| format!("multipart file '{name}' must have an image/* content type"))?;
+    Ok(json!({
+        "type": "image",
+        "source": {"type": "base64", "media_type": media_type, "data": STANDARD.encode(data)}
+    }))
+}
+
+fn request_field(name: &str, text: &str) -> Result<Value, String> {
+    let text = text.trim();
+    match name {
+        "max_tokens" => text
+            .parse::<u32>()
+            .map(Value::from)
+            .map_err(|_| format!("multipart field 'max_tokens' must be an integer, got '{text}'")),
+        "stream" => text
+            .parse::<bool>()
+            .map(Value::from)
+            .map_err(|_| format!("multipart field 'stream' must be true or false, got '{text}'")),
+        _ => Ok(Value::from(text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::multipart_to_messages_json;
+    use multer::bytes::Bytes;
+    use serde_json::json;
+
+    const BOUNDARY: &str = "bridge-boundary";
+
+    /// Field name, optional `(file name, content type)`, and body.
+    type Part<'a> = (&'a str, Option<(&'a str, &'a str)>, &'a [u8]);
+
+    fn form(parts: &[Part]) -> Bytes {
+        let mut body = Vec::new();
+        for (name, file, data) in parts {
+            body.extend_from_slice(format!("--{BOUNDARY}\r\n").as_bytes());
+            let disposition = match file {
+                Some((file_name, media_type)) => format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\nContent-Type: {media_type}\r\n"
+                ),
+                None => format!("Content-Disposition: form-data; name=\"{name}\"\r\n"),
+            };
+            body.extend_from_slice(disposition.as_bytes());
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+        Bytes::from(body)
+    }
+
+    #[tokio::test]
+    async fn converts_image_uploads_and_text_fields_to_a_user_message() {
+        let body = form(&[
+            ("model", None, b"claude-3-5-sonnet-20241022"),
+            ("max_tokens", None, b"256"),
+            ("prompt", None, b"What is in this picture?"),
+            ("image", Some(("cat.png", "image/png")), b"\x89PNG"),
+        ]);
+        let content_type = format!("multipart/form-data; boundary={BOUNDARY}");
+
+        let request = multipart_to_messages_json(body, &content_type)
+            .await
+            .expect("valid form");
+        assert_eq!(request["model"], json!("claude-3-5-sonnet-20241022"));
+        assert_eq!(request["max_tokens"], json!(256));
+        assert_eq!(
+            request["messages"][0]["content"],
+            json!([
+                {"type": "text", "text": "What is in this picture?"},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw=="}}
+            ])
+        );
+        serde_json::from_value::<crate::models::ClaudeMessagesRequest>(request)
+            .expect("deserializes as a Messages request");
+    }
+
+    #[tokio::test]
+    async fn rejects_non_image_files_and_bad_numbers() {
+        let content_type = format!("multipart/form-data; boundary={BOUNDARY}");
+        let pdf = form(&[("doc", Some(("a.pdf", "application/pdf")), b"%PDF")]);
+        let error = multipart_to_messages_json(pdf, &content_type)
+            .await
+            .expect_err("pdf rejected");
+        assert!(error.contains("image/*"));
+
+        let bad = form(&[("max_tokens", None, b"lots"), ("prompt", None, b"hi")]);
+        assert!(
+            multipart_to_messages_json(bad, &content_type)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+/// Per-identity token buckets that refill continuously at `rpm / 60` tokens
+/// per second up to `burst`. A zero `rpm` disables limiting entirely.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    buckets: Arc<RwLock<HashMap<String, TokenBucket>>>,
+    refill_per_sec: f64,
+    capacity: f64,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, now: Instant, refill_per_sec: f64, capacity: f64) {
+        let elapsed = now
+            .checked_duration_since(self.last_refill)
+            .unwrap_or_default();
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * refill_per_sec).min(capacity);
+        self.last_refill = now;
+    }
+}
+
+impl RateLimiter {
+    pub fn new(rpm: u32, burst: u32) -> Self {
+        Self {
+            buckets: Arc::new(RwLock::new(HashMap::new())),
+            refill_per_sec: f64::from(rpm) / 60.0,
+            capacity: f64::from(burst.max(1)),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.refill_per_sec > 0.0
+    }
+
+    /// Takes one token for `key`, or returns how long until one is available.
+    pub async fn check(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let mut buckets = self.buckets.write().await;
+        let bucket = buckets.entry(key.to_string()).or_insert(TokenBucket {
+            tokens: self.capacity,
+            last_refill: now,
+        });
+        bucket.refill(now, self.refill_per_sec, self.capacity);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        let missing = 1.0 - bucket.tokens;
+        Err(Duration::from_secs_f64(missing / self.refill_per_sec))
+    }
+
+    /// Drops buckets that have refilled completely; they are
+    /// indistinguishable from a fresh bucket.
+    pub async fn cleanup_idle(&self, now: Instant) -> usize {
+        let mut buckets = self.buckets.write().await;
+        let before = buckets.len();
+        buckets.retain(|_, bucket| {
+            bucket.refill(now, self.refill_per_sec, self.capacity);
+            bucket.tokens < self.capacity
+        });
+        before.saturating_sub(buckets.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimiter;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn allows_burst_then_reports_retry_after() {
+        let limiter = RateLimiter::new(60, 2);
+        let now = Instant::now();
+        assert!(limiter.check("a", now).await.is_ok());
+        assert!(limiter.check("a", now).await.is_ok());
+
+        let retry_after = limiter.check("a", now).await.expect_err("bucket empty");
+        assert_eq!(retry_after, Duration::from_secs(1));
+        assert!(limiter.check("b", now).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn refills_continuously_over_time() {
+        let limiter = RateLimiter::new(120, 1);
+        let now = Instant::now();
+        assert!(limiter.check("a", now).await.is_ok());
+        assert!(limiter.check("a", now).await.is_err());
+        assert!(
+            limiter
+                .check("a", now + Duration::from_millis(500))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn zero_rpm_disables_limiting() {
+        let limiter = RateLimiter::new(0, 1);
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert!(limiter.check("a", now).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn cleanup_drops_only_fully_refilled_buckets() {
+        let limiter = RateLimiter::new(60, 5);
+        let now = Instant::now();
+        limiter.check("idle", now).await.expect("allowed");
+        limiter
+            .check("busy", now + Duration::from_secs(10))
+            .await
+            .expect("allowed");
+
+        let removed = limiter.cleanup_idle(now + Duration::from_secs(10)).await;
+        assert_eq!(removed, 1);
+    }
+}
@@ -5,9 +5,21 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::batches::BatchStore;
+use crate::capture::RequestCapture;
 use crate::config::Config;
+use crate::conversion::request::ResponsesAnchor;
+use crate::metrics::metrics;
+use crate::rate_limit::RateLimiter;
+use crate::token_count::TokenCounter;
 use crate::upstream::UpstreamClient;
 
+mod session_stats;
+mod session_usage;
+
+pub use session_stats::{HistogramBucket, IdentityUsage, RequestSummary};
+use session_usage::TokenTotals;
+
 const SESSION_TTL_TOKEN_K: f64 = 50_000.0;
 
 #[derive(Clone, Debug)]
@@ -15,6 +27,10 @@ pub struct AppState {
     pub config: Config,
     pub upstream: UpstreamClient,
     pub sessions: SessionManager,
+    pub batches: BatchStore,
+    pub tokenizer: TokenCounter,
+    pub rate_limiter: RateLimiter,
+    pub capture: Option<RequestCapture>,
 }
 
 #[derive(Clone, Debug)]
@@ -34,8 +50,28 @@ struct SessionStore {
 #[derive(Debug)]
 struct SessionEntry {
     session_id: String,
+    created_at: Instant,
     last_seen: Instant,
-    total_tokens: u64,
+    tokens: TokenTotals,
+    request_count: u64,
+    /// Requests let through by `max_requests_per_session`, counted on
+    /// admission so concurrent requests cannot overshoot the cap.
+    admitted_requests: u64,
+    last_responses_id: Option<ResponsesAnchor>,
+}
+
+impl SessionEntry {
+    fn new(now: Instant, tokens: TokenTotals, request_count: u64) -> Self {
+        Self {
+            session_id: Uuid::new_v4().to_string(),
+            created_at: now,
+            last_seen: now,
+            tokens,
+            request_count,
+            admitted_requests: 0,
+            last_responses_id: None,
+        }
+    }
 }
 
 impl SessionManager {
@@ -62,15 +98,10 @@ impl SessionManager {
             return entry.session_id.clone();
         }
 
-        let session_id = Uuid::new_v4().to_string();
-        store.sessions.insert(
-            identity_key.to_string(),
-            SessionEntry {
-                session_id: session_id.clone(),
-                last_seen: now,
-                total_tokens: 0,
-            },
-        );
+        let entry = SessionEntry::new(now, TokenTotals::default(), 0);
+        let session_id = entry.session_id.clone();
+        store.sessions.insert(identity_key.to_string(), entry);
+        metrics().set_active_sessions(store.sessions.len());
         session_id
     }
 
@@ -83,23 +114,54 @@ impl SessionManager {
         }
     }
 
-    pub async fn add_usage(&self, identity_key: &str, tokens: u64) {
+    pub async fn add_usage(&self, identity_key: &str, input_tokens: u64, output_tokens: u64) {
         let now = Instant::now();
         let mut store = self.inner.write().await;
         if let Some(entry) = store.sessions.get_mut(identity_key) {
-            entry.total_tokens = entry.total_tokens.saturating_add(tokens);
+            entry.tokens.add(input_tokens, output_tokens);
+            entry.request_count = entry.request_count.saturating_add(1);
             entry.last_seen = now;
             return;
         }
 
-        store.sessions.insert(
-            identity_key.to_string(),
-            SessionEntry {
-                session_id: Uuid::new_v4().to_string(),
-                last_seen: now,
-                total_tokens: tokens,
-            },
-        );
+        let tokens = TokenTotals::new(input_tokens, output_tokens);
+        store
+            .sessions
+            .insert(identity_key.to_string(), SessionEntry::new(now, tokens, 1));
+        metrics().set_active_sessions(store.sessions.len());
+    }
+
+    /// Checks and counts one request against `max_requests` under the same
+    /// lock; `false` once the identity's current session has used them all.
+    pub async fn try_admit_request(&self, identity_key: &str, max_requests: u64) -> bool {
+        let now = Instant::now();
+        let mut store = self.inner.write().await;
+        let entry = store
+            .sessions
+            .entry(identity_key.to_string())
+            .or_insert_with(|| SessionEntry::new(now, TokenTotals::default(), 0));
+        entry.last_seen = now;
+        if entry.admitted_requests >= max_requests {
+            return false;
+        }
+        entry.admitted_requests += 1;
+        metrics().set_active_sessions(store.sessions.len());
+        true
+    }
+
+    /// The stored upstream response the identity's next Responses API request
+    /// can continue from, when `use_stateful_responses` is on.
+    pub async fn responses_anchor(&self, identity_key: &str) -> Option<ResponsesAnchor> {
+        let store = self.inner.read().await;
+        store.sessions.get(identity_key)?.last_responses_id.clone()
+    }
+
+    /// `None` forgets the stored response, e.g. after the upstream rejected it.
+    pub async fn update_responses_id(&self, identity_key: &str, anchor: Option<ResponsesAnchor>) {
+        let mut store = self.inner.write().await;
+        if let Some(entry) = store.sessions.get_mut(identity_key) {
+            entry.last_responses_id = anchor;
+        }
     }
 
     pub async fn cleanup_expired(&self, now: Instant) -> usize {
@@ -126,11 +188,14 @@ impl SessionManager {
         store
             .sessions
             .retain(|_, entry| !self.is_expired(entry, now));
-        before.saturating_sub(store.sessions.len())
+        let removed = before.saturating_sub(store.sessions.len());
+        metrics().record_session_cleanup(removed);
+        metrics().set_active_sessions(store.sessions.len());
+        removed
     }
 
     fn is_expired(&self, entry: &SessionEntry, now: Instant) -> bool {
-        let ttl = self.dynamic_ttl(entry.total_tokens);
+        let ttl = self.dynamic_ttl(entry.tokens.total());
         now.checked_duration_since(entry.last_seen)
             .unwrap_or_default()
             > ttl
@@ -167,7 +232,7 @@ pub fn app_state() -> &'static AppState {
 
 #[cfg(test)]
 mod tests {
-    use super::{SessionEntry, SessionManager};
+    use super::{SessionEntry, SessionManager, TokenTotals};
     use std::time::{Duration, Instant};
 
     #[tokio::test]
@@ -212,16 +277,24 @@ mod tests {
                 "expired".to_string(),
                 SessionEntry {
                     session_id: "s1".to_string(),
+                    created_at: now - Duration::from_secs(120),
                     last_seen: now - Duration::from_secs(120),
-                    total_tokens: 0,
+                    tokens: TokenTotals::default(),
+                    request_count: 0,
+                    admitted_requests: 0,
+                    last_responses_id: None,
                 },
             );
             store.sessions.insert(
                 "active".to_string(),
                 SessionEntry {
                     session_id: "s2".to_string(),
+                    created_at: now - Duration::from_secs(30),
                     last_seen: now - Duration::from_secs(30),
-                    total_tokens: 0,
+                    tokens: TokenTotals::default(),
+                    request_count: 0,
+                    admitted_requests: 0,
+                    last_responses_id: None,
                 },
             );
         }
@@ -233,4 +306,25 @@ mod tests {
         assert!(!store.sessions.contains_key("expired"));
         assert!(store.sessions.contains_key("active"));
     }
+
+    #[tokio::test]
+    async fn concurrent_requests_cannot_exceed_the_session_cap() {
+        let manager = SessionManager::new(10, 100, 60);
+        let attempts: Vec<_> = (0..20)
+            .map(|_| {
+                let manager = manager.clone();
+                tokio::spawn(async move { manager.try_admit_request("identity-a", 5).await })
+            })
+            .collect();
+
+        let mut admitted = 0;
+        for attempt in attempts {
+            if attempt.await.expect("admission task") {
+                admitted += 1;
+            }
+        }
+        assert_eq!(admitted, 5);
+        assert!(!manager.try_admit_request("identity-a", 5).await);
+        assert!(manager.try_admit_request("identity-b", 5).await);
+    }
 }
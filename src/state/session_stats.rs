@@ -0,0 +1,223 @@
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::{SessionEntry, SessionManager};
+
+const TOKEN_BUCKET_BOUNDS: [u64; 4] = [1_000, 10_000, 100_000, 1_000_000];
+const AGE_BUCKET_BOUNDS_SECS: [u64; 4] = [300, 3_600, 21_600, 86_400];
+/// Identity keys are already SHA-256 digests; a prefix is enough to tell
+/// them apart without publishing the full fingerprint.
+pub(super) const IDENTITY_PREFIX_LEN: usize = 12;
+
+/// Non-cumulative bucket; `upper_bound: None` collects everything above the
+/// last bound.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct HistogramBucket {
+    pub upper_bound: Option<u64>,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct IdentityUsage {
+    pub identity: String,
+    pub total_tokens: u64,
+    pub request_count: u64,
+}
+
+/// Per-session request counts, for spotting clients that send many small
+/// requests.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RequestSummary {
+    pub total_requests: u64,
+    pub average_requests_per_session: f64,
+    pub sessions_over_threshold: usize,
+    /// The busiest of those sessions, capped at the requested limit.
+    pub top_over_threshold: Vec<IdentityUsage>,
+}
+
+impl SessionManager {
+    pub async fn session_count(&self) -> usize {
+        self.inner.read().await.sessions.len()
+    }
+
+    pub async fn total_token_usage(&self) -> u64 {
+        let store = self.inner.read().await;
+        store
+            .sessions
+            .values()
+            .fold(0, |total, entry| total.saturating_add(entry.tokens.total()))
+    }
+
+    /// Sessions bucketed by accumulated tokens.
+    pub async fn usage_histogram(&self) -> Vec<HistogramBucket> {
+        let store = self.inner.read().await;
+        bucketize(
+            store.sessions.values().map(|entry| entry.tokens.total()),
+            &TOKEN_BUCKET_BOUNDS,
+        )
+    }
+
+    /// Sessions bucketed by seconds since they were created.
+    pub async fn age_histogram(&self, now: Instant) -> Vec<HistogramBucket> {
+        let store = self.inner.read().await;
+        let ages = store.sessions.values().map(|entry| {
+            now.checked_duration_since(entry.created_at)
+                .unwrap_or_default()
+                .as_secs()
+        });
+        bucketize(ages, &AGE_BUCKET_BOUNDS_SECS)
+    }
+
+    pub async fn top_identities(&self, limit: usize) -> Vec<IdentityUsage> {
+        let store = self.inner.read().await;
+        let mut usage: Vec<IdentityUsage> = store
+            .sessions
+            .iter()
+            .map(|(identity_key, entry)| identity_usage(identity_key, entry))
+            .collect();
+        usage.sort_by(|a, b| {
+            b.total_tokens
+                .cmp(&a.total_tokens)
+                .then_with(|| a.identity.cmp(&b.identity))
+        });
+        usage.truncate(limit);
+        usage
+    }
+
+    pub async fn request_summary(&self, threshold: u64, limit: usize) -> RequestSummary {
+        let store = self.inner.read().await;
+        let total_requests = store.sessions.values().fold(0u64, |total, entry| {
+            total.saturating_add(entry.request_count)
+        });
+        let average_requests_per_session = if store.sessions.is_empty() {
+            0.0
+        } else {
+            total_requests as f64 / store.sessions.len() as f64
+        };
+        let mut over_threshold: Vec<IdentityUsage> = store
+            .sessions
+            .iter()
+            .filter(|(_, entry)| entry.request_count > threshold)
+            .map(|(identity_key, entry)| identity_usage(identity_key, entry))
+            .collect();
+        over_threshold.sort_by(|a, b| {
+            b.request_count
+                .cmp(&a.request_count)
+                .then_with(|| a.identity.cmp(&b.identity))
+        });
+        let sessions_over_threshold = over_threshold.len();
+        over_threshold.truncate(limit);
+        RequestSummary {
+            total_requests,
+            average_requests_per_session,
+            sessions_over_threshold,
+            top_over_threshold: over_threshold,
+        }
+    }
+}
+
+fn identity_usage(identity_key: &str, entry: &SessionEntry) -> IdentityUsage {
+    IdentityUsage {
+        identity: identity_key.chars().take(IDENTITY_PREFIX_LEN).collect(),
+        total_tokens: entry.tokens.total(),
+        request_count: entry.request_count,
+    }
+}
+
+fn bucketize(values: impl Iterator<Item = u64>, bounds: &[u64]) -> Vec<HistogramBucket> {
+    let mut counts = vec![0; bounds.len() + 1];
+    for value in values {
+        let index = bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(bounds.len());
+        counts[index] += 1;
+    }
+
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(index, count)| HistogramBucket {
+            upper_bound: bounds.get(index).copied(),
+            count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HistogramBucket;
+    use crate::state::SessionManager;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn reports_counts_totals_and_usage_buckets() {
+        let manager = SessionManager::new(10, 100, 60);
+        manager.add_usage("aaaaaaaaaaaaaaaa", 500, 0).await;
+        manager.add_usage("bbbbbbbbbbbbbbbb", 50_000, 0).await;
+        manager.add_usage("cccccccccccccccc", 2_000_000, 0).await;
+
+        assert_eq!(manager.session_count().await, 3);
+        assert_eq!(manager.total_token_usage().await, 2_050_500);
+
+        let counts: Vec<usize> = manager
+            .usage_histogram()
+            .await
+            .into_iter()
+            .map(|bucket| bucket.count)
+            .collect();
+        assert_eq!(counts, vec![1, 0, 1, 0, 1]);
+    }
+
+    #[tokio::test]
+    async fn top_identities_are_truncated_and_sorted_by_usage() {
+        let manager = SessionManager::new(10, 100, 60);
+        manager.add_usage("aaaaaaaaaaaaaaaa", 10, 0).await;
+        manager.add_usage("bbbbbbbbbbbbbbbb", 30, 0).await;
+        manager.add_usage("cccccccccccccccc", 20, 0).await;
+
+        let top = manager.top_identities(2).await;
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].identity, "bbbbbbbbbbbb");
+        assert_eq!(top[0].total_tokens, 30);
+        assert_eq!(top[1].total_tokens, 20);
+    }
+
+    #[tokio::test]
+    async fn summarizes_request_counts_and_flags_busy_sessions() {
+        let manager = SessionManager::new(10, 100, 60);
+        for _ in 0..5 {
+            manager.add_usage("aaaaaaaaaaaaaaaa", 1, 0).await;
+        }
+        manager.add_usage("bbbbbbbbbbbbbbbb", 1, 0).await;
+        manager.resolve_session_id("cccccccccccccccc").await;
+
+        let summary = manager.request_summary(3, 10).await;
+        assert_eq!(summary.total_requests, 6);
+        assert_eq!(summary.average_requests_per_session, 2.0);
+        assert_eq!(summary.sessions_over_threshold, 1);
+        assert_eq!(summary.top_over_threshold[0].identity, "aaaaaaaaaaaa");
+        assert_eq!(summary.top_over_threshold[0].request_count, 5);
+    }
+
+    #[tokio::test]
+    async fn age_histogram_uses_creation_time() {
+        let manager = SessionManager::new(10, 100, 60);
+        manager.resolve_session_id("fresh").await;
+
+        let later = Instant::now() + Duration::from_secs(7_200);
+        let histogram = manager.age_histogram(later).await;
+        assert_eq!(
+            histogram[2],
+            HistogramBucket {
+                upper_bound: Some(21_600),
+                count: 1
+            }
+        );
+        assert_eq!(
+            histogram.iter().map(|bucket| bucket.count).sum::<usize>(),
+            1
+        );
+    }
+}
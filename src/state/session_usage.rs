@@ -0,0 +1,163 @@
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::session_stats::IDENTITY_PREFIX_LEN;
+use super::{SessionEntry, SessionManager};
+
+const USAGE_TOP_SESSIONS: usize = 10;
+
+/// Tokens a session has consumed, split by direction so billing can price
+/// prompt and completion tokens separately.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct TokenTotals {
+    input: u64,
+    output: u64,
+}
+
+impl TokenTotals {
+    pub(super) fn new(input: u64, output: u64) -> Self {
+        Self { input, output }
+    }
+
+    pub(super) fn add(&mut self, input: u64, output: u64) {
+        self.input = self.input.saturating_add(input);
+        self.output = self.output.saturating_add(output);
+    }
+
+    pub(super) fn total(&self) -> u64 {
+        self.input.saturating_add(self.output)
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct SessionUsageEntry {
+    pub identity: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    pub request_count: u64,
+    pub age_secs: u64,
+}
+
+/// Served by `/v1/usage`; `top_sessions` holds the heaviest sessions by
+/// total tokens.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct SessionUsageStats {
+    pub session_count: usize,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub total_tokens_consumed: u64,
+    /// `None` while no session is live.
+    pub oldest_session_age_secs: Option<u64>,
+    pub top_sessions: Vec<SessionUsageEntry>,
+}
+
+impl SessionManager {
+    pub async fn usage_stats(&self) -> SessionUsageStats {
+        let now = Instant::now();
+        let store = self.inner.read().await;
+        let mut totals = TokenTotals::default();
+        let mut entries: Vec<SessionUsageEntry> = store
+            .sessions
+            .iter()
+            .map(|(identity_key, entry)| {
+                totals.add(entry.tokens.input, entry.tokens.output);
+                usage_entry(identity_key, entry, now)
+            })
+            .collect();
+        let oldest_session_age_secs = entries.iter().map(|entry| entry.age_secs).max();
+        entries.sort_by(|a, b| {
+            b.total_tokens
+                .cmp(&a.total_tokens)
+                .then_with(|| a.identity.cmp(&b.identity))
+        });
+        entries.truncate(USAGE_TOP_SESSIONS);
+
+        SessionUsageStats {
+            session_count: store.sessions.len(),
+            total_input_tokens: totals.input,
+            total_output_tokens: totals.output,
+            total_tokens_consumed: totals.total(),
+            oldest_session_age_secs,
+            top_sessions: entries,
+        }
+    }
+}
+
+fn usage_entry(identity_key: &str, entry: &SessionEntry, now: Instant) -> SessionUsageEntry {
+    SessionUsageEntry {
+        identity: identity_key.chars().take(IDENTITY_PREFIX_LEN).collect(),
+        input_tokens: entry.tokens.input,
+        output_tokens: entry.tokens.output,
+        total_tokens: entry.tokens.total(),
+        request_count: entry.request_count,
+        age_secs: now
+            .checked_duration_since(entry.created_at)
+            .unwrap_or_default()
+            .as_secs(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::USAGE_TOP_SESSIONS;
+    use crate::state::SessionManager;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn reports_split_token_totals_and_heaviest_sessions() {
+        let manager = SessionManager::new(10, 100, 60);
+        manager.add_usage("aaaaaaaaaaaaaaaa", 100, 20).await;
+        manager.add_usage("aaaaaaaaaaaaaaaa", 50, 10).await;
+        manager.add_usage("bbbbbbbbbbbbbbbb", 1_000, 400).await;
+
+        let stats = serde_json::to_value(manager.usage_stats().await).expect("serialize");
+        assert_eq!(
+            stats,
+            json!({
+                "session_count": 2,
+                "total_input_tokens": 1_150,
+                "total_output_tokens": 430,
+                "total_tokens_consumed": 1_580,
+                "oldest_session_age_secs": 0,
+                "top_sessions": [
+                    {
+                        "identity": "bbbbbbbbbbbb",
+                        "input_tokens": 1_000,
+                        "output_tokens": 400,
+                        "total_tokens": 1_400,
+                        "request_count": 1,
+                        "age_secs": 0
+                    },
+                    {
+                        "identity": "aaaaaaaaaaaa",
+                        "input_tokens": 150,
+                        "output_tokens": 30,
+                        "total_tokens": 180,
+                        "request_count": 2,
+                        "age_secs": 0
+                    }
+                ]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn caps_top_sessions_and_reports_no_age_when_empty() {
+        let manager = SessionManager::new(10, 100, 60);
+        let empty = manager.usage_stats().await;
+        assert_eq!(empty.session_count, 0);
+        assert_eq!(empty.oldest_session_age_secs, None);
+
+        for index in 0..USAGE_TOP_SESSIONS + 5 {
+            manager
+                .add_usage(&format!("identity-{index:02}"), index as u64, 1)
+                .await;
+        }
+        let stats = manager.usage_stats().await;
+        assert_eq!(stats.session_count, USAGE_TOP_SESSIONS + 5);
+        assert_eq!(stats.top_sessions.len(), USAGE_TOP_SESSIONS);
+        assert_eq!(stats.top_sessions[0].identity, "identity-14");
+    }
+}
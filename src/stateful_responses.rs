@@ -0,0 +1,50 @@
+use tracing::debug;
+
+use crate::conversion::request::{
+    OpenAiResponsesRequest, ResponsesInputPrefix, apply_previous_response,
+};
+use crate::state::app_state;
+
+/// Points the request at the identity's stored upstream response when
+/// `use_stateful_responses` is on and the history extends it. Returns the
+/// full-history prefix to anchor the next request on, or `None` when the
+/// feature is off.
+pub async fn continue_previous_response(
+    request: &mut OpenAiResponsesRequest,
+    identity_key: &str,
+) -> Option<ResponsesInputPrefix> {
+    let state = app_state();
+    if !state.config.use_stateful_responses {
+        return None;
+    }
+    let prefix = ResponsesInputPrefix::of(request);
+    if let Some(anchor) = state.sessions.responses_anchor(identity_key).await
+        && apply_previous_response(request, &anchor)
+    {
+        debug!(
+            phase = "stateful_responses",
+            previous_response_id = %anchor.response_id,
+            input_items = request.input.len(),
+            "Continuing from stored upstream response"
+        );
+    }
+    Some(prefix)
+}
+
+/// Anchors the session on `response_id`; without one (the request failed or
+/// the upstream sent no id) the stored anchor is dropped so the next request
+/// resends full history.
+pub async fn record_response(
+    identity_key: &str,
+    prefix: Option<ResponsesInputPrefix>,
+    response_id: Option<&str>,
+) {
+    let Some(prefix) = prefix else {
+        return;
+    };
+    let anchor = response_id.map(|id| prefix.into_anchor(id.to_string()));
+    app_state()
+        .sessions
+        .update_responses_id(identity_key, anchor)
+        .await;
+}
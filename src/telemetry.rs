@@ -0,0 +1,135 @@
+use opentelemetry::global;
+use opentelemetry::propagation::Injector;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::config::Config;
+
+const DEFAULT_SERVICE_NAME: &str = "claude-openai-bridge";
+const TRACES_PATH: &str = "/v1/traces";
+
+/// Exports spans over OTLP/HTTP when `otel_exporter_otlp_endpoint` is set.
+/// Also installs the W3C trace-context propagator so upstream requests carry
+/// `traceparent` / `tracestate`; without an endpoint nothing is installed and
+/// injection stays a no-op.
+pub struct Telemetry {
+    provider: SdkTracerProvider,
+}
+
+impl Telemetry {
+    pub fn init(config: &Config) -> Result<Option<Self>, String> {
+        let Some(endpoint) = config.otel_exporter_otlp_endpoint.as_deref() else {
+            return Ok(None);
+        };
+
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(traces_endpoint(endpoint))
+            .build()
+            .map_err(|error| format!("failed to initialize OTLP exporter: {error}"))?;
+        let service_name = config
+            .otel_service_name
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string());
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(service_name).build())
+            .build();
+
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        global::set_tracer_provider(provider.clone());
+        Ok(Some(Self { provider }))
+    }
+
+    pub fn tracer(&self) -> SdkTracer {
+        self.provider.tracer(DEFAULT_SERVICE_NAME)
+    }
+
+    /// Flushes spans still buffered in the batch processor.
+    pub fn shutdown(self) {
+        if let Err(error) = self.provider.shutdown() {
+            eprintln!("Failed to flush OpenTelemetry spans: {error}");
+        }
+    }
+}
+
+/// Follows the OTLP convention that the generic endpoint is a base URL and
+/// traces are posted to `{endpoint}/v1/traces`.
+fn traces_endpoint(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with(TRACES_PATH) {
+        return endpoint.to_string();
+    }
+    format!("{endpoint}{TRACES_PATH}")
+}
+
+/// Writes the current span's trace context into `headers`.
+pub fn inject_trace_context(headers: &mut HeaderMap) {
+    let context = tracing::Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers));
+    });
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HeaderInjector, traces_endpoint};
+    use opentelemetry::Context;
+    use opentelemetry::propagation::TextMapPropagator;
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use reqwest::header::HeaderMap;
+
+    #[test]
+    fn appends_traces_path_to_base_endpoint() {
+        assert_eq!(
+            traces_endpoint("http://collector:4318/"),
+            "http://collector:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_endpoint("http://collector:4318/v1/traces"),
+            "http://collector:4318/v1/traces"
+        );
+    }
+
+    #[test]
+    fn injects_traceparent_into_upstream_headers() {
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").expect("trace id"),
+            SpanId::from_hex("00f067aa0ba902b7").expect("span id"),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let context = Context::new().with_remote_span_context(span_context);
+
+        let mut headers = HeaderMap::new();
+        TraceContextPropagator::new().inject_context(&context, &mut HeaderInjector(&mut headers));
+        assert_eq!(
+            headers
+                .get("traceparent")
+                .and_then(|value| value.to_str().ok()),
+            Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::de::{Deserializer, IgnoredAny};
+use serde_json::Value;
+use tiktoken_rs::CoreBPE;
+
+use crate::models::{
+    ClaudeContent, ClaudeContentBlock, ClaudeMessage, ClaudeSystemBlock, ClaudeSystemContent,
+};
+
+pub const TOKEN_COUNT_APPROXIMATION_NOTE: &str =
+    "count_tokens uses the cl100k_base tokenizer as an approximation of Claude's tokenizer";
+
+/// Shared `cl100k_base` encoder; built once at startup because loading the
+/// BPE ranks is far more expensive than encoding a prompt.
+#[derive(Clone)]
+pub struct TokenCounter {
+    bpe: Arc<CoreBPE>,
+}
+
+impl fmt::Debug for TokenCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TokenCounter(cl100k_base)")
+    }
+}
+
+impl TokenCounter {
+    pub fn cl100k() -> Result<Self, String> {
+        let bpe = tiktoken_rs::cl100k_base()
+            .map_err(|error| format!("failed to load cl100k_base tokenizer: {error}"))?;
+        Ok(Self { bpe: Arc::new(bpe) })
+    }
+
+    pub fn count_text(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+
+    pub fn estimate_input_tokens(
+        &self,
+        system: Option<&ClaudeSystemContent>,
+        messages: &[ClaudeMessage],
+    ) -> usize {
+        let mut texts = Vec::new();
+        if let Some(system) = system {
+            collect_system_texts(system, &mut texts);
+        }
+        for message in messages {
+            if let Some(content) = &message.content {
+                collect_message_texts(content, &mut texts);
+            }
+        }
+        let total: usize = texts.iter().map(|text| self.count_text(text)).sum();
+        total.max(1)
+    }
+}
+
+fn collect_system_texts(system: &ClaudeSystemContent, texts: &mut Vec<String>) {
+    match system {
+        ClaudeSystemContent::Text(text) => texts.push(text.clone()),
+        ClaudeSystemContent::Blocks(blocks) => {
+            for block in blocks {
+                if let ClaudeSystemBlock::Text { text, .. } = block {
+                    texts.push(text.clone());
+                }
+            }
+        }
+        ClaudeSystemContent::Other(value) => collect_value_texts(value, texts),
+    }
+}
+
+fn collect_message_texts(content: &ClaudeContent, texts: &mut Vec<String>) {
+    match content {
+        ClaudeContent::Text(text) => texts.push(text.clone()),
+        ClaudeContent::Blocks(blocks) => {
+            for block in blocks {
+                collect_block_texts(block, texts);
+            }
+        }
+        ClaudeContent::Other(value) => collect_value_texts(value, texts),
+    }
+}
+
+fn collect_block_texts(block: &ClaudeContentBlock, texts: &mut Vec<String>) {
+    match block {
+        ClaudeContentBlock::Text { text, .. } => texts.push(text.clone()),
+        ClaudeContentBlock::Thinking { thinking, .. } => texts.push(thinking.clone()),
+        _ => {
+            if let Ok(value) = serde_json::to_value(block) {
+                collect_value_texts(&value, texts);
+            }
+        }
+    }
+}
+
+fn collect_value_texts(value: &Value, texts: &mut Vec<String>) {
+    match value {
+        Value::String(text) => texts.push(text.clone()),
+        Value::Array(items) => {
+            for item in items {
+                collect_value_texts(item, texts);
+            }
+        }
+        Value::Object(object) => {
+            match serde_json::from_value::<LooseTextCarrier>(value.clone())
+                .ok()
+                .and_then(|payload| payload.text)
+            {
+                Some(text) => texts.push(text),
+                None => {
+                    for item in object.values() {
+                        collect_value_texts(item, texts);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LooseTextCarrier {
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LooseString {
+    String(String),
+    Other(IgnoredAny),
+}
+
+impl LooseString {
+    fn into_string(self) -> Option<String> {
+        match self {
+            Self::String(value) => Some(value),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+fn deserialize_optional_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<LooseString>::deserialize(deserializer)?;
+    Ok(value.and_then(LooseString::into_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenCounter;
+    use crate::models::{ClaudeContent, ClaudeMessage, ClaudeSystemContent};
+
+    fn message(role: &str, text: &str) -> ClaudeMessage {
+        ClaudeMessage {
+            role: role.to_string(),
+            content: Some(ClaudeContent::Text(text.to_string())),
+            name: None,
+        }
+    }
+
+    #[test]
+    fn counts_cl100k_tokens_for_system_and_messages() {
+        let counter = TokenCounter::cl100k().expect("tokenizer should load");
+        assert_eq!(counter.count_text("hello world"), 2);
+
+        let system = ClaudeSystemContent::Text("hello world".to_string());
+        let messages = vec![message("user", "hello world"), message("assistant", "")];
+        assert_eq!(counter.estimate_input_tokens(Some(&system), &messages), 4);
+    }
+
+    #[test]
+    fn empty_request_counts_as_one_token() {
+        let counter = TokenCounter::cl100k().expect("tokenizer should load");
+        assert_eq!(counter.estimate_input_tokens(None, &[]), 1);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Percentiles are taken over this many of the most recent streams, so
+/// `/health` reflects current upstream behaviour rather than all-time data.
+const TTFT_WINDOW_SIZE: usize = 1024;
+
+/// Time-to-first-token percentiles over the recent window, in milliseconds;
+/// `None` until a stream has produced text.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TtftPercentiles {
+    pub samples: usize,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct TtftWindow {
+    samples: Mutex<VecDeque<u64>>,
+}
+
+impl TtftWindow {
+    pub fn record(&self, ttft: Duration) {
+        let mut samples = self
+            .samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if samples.len() == TTFT_WINDOW_SIZE {
+            samples.pop_front();
+        }
+        samples.push_back(ttft.as_millis() as u64);
+    }
+
+    pub fn percentiles(&self) -> TtftPercentiles {
+        let mut sorted: Vec<u64> = self
+            .samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .copied()
+            .collect();
+        sorted.sort_unstable();
+        TtftPercentiles {
+            samples: sorted.len(),
+            p50_ms: nearest_rank(&sorted, 50),
+            p95_ms: nearest_rank(&sorted, 95),
+            p99_ms: nearest_rank(&sorted, 99),
+        }
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn nearest_rank(sorted: &[u64], percentile: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TTFT_WINDOW_SIZE, TtftWindow};
+    use std::time::Duration;
+
+    #[test]
+    fn reports_nearest_rank_percentiles_over_the_recent_window() {
+        let window = TtftWindow::default();
+        assert_eq!(window.percentiles().p50_ms, None);
+
+        for millis in (1..=100).rev() {
+            window.record(Duration::from_millis(millis));
+        }
+        let percentiles = window.percentiles();
+        assert_eq!(percentiles.samples, 100);
+        assert_eq!(percentiles.p50_ms, Some(50));
+        assert_eq!(percentiles.p95_ms, Some(95));
+        assert_eq!(percentiles.p99_ms, Some(99));
+
+        for _ in 0..TTFT_WINDOW_SIZE {
+            window.record(Duration::from_millis(7));
+        }
+        let percentiles = window.percentiles();
+        assert_eq!(percentiles.samples, TTFT_WINDOW_SIZE);
+        assert_eq!(percentiles.p99_ms, Some(7));
+    }
+}
@@ -1,68 +1,128 @@
+mod body;
+mod http_error;
+mod probe;
+mod send;
+#[cfg(test)]
+pub(crate) mod test_support;
+
 use reqwest::Client;
 use reqwest::header::{
     ACCEPT_ENCODING, AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, USER_AGENT,
 };
 use serde::Serialize;
-use serde::de::DeserializeOwned;
-use std::borrow::Cow;
-use std::time::{Duration, Instant};
-use tracing::{debug, error, warn};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tracing::warn;
 
 use crate::config::Config;
 use crate::conversion::response::{OpenAiChatResponse, OpenAiResponsesResponse};
-use crate::errors::{UpstreamError, classify_openai_error, extract_error_message_from_body};
+use crate::errors::{UpstreamError, UpstreamErrorKind, classify_openai_error};
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+use crate::upstream_breaker::CircuitBreaker;
+use crate::upstream_metadata::UpstreamMetadata;
 use crate::upstream_parse::parse_responses_body;
-use crate::utils::to_salvo_status;
+use crate::upstream_proxy::build_http_client;
+use crate::upstream_retry::RequestKind;
+
+use body::{parse_success_json_response, parse_success_text_response};
+use probe::CachedProbes;
+
+pub use probe::UpstreamProbe;
 
 #[derive(Clone, Debug)]
 pub struct UpstreamClient {
     client: Client,
     config: Config,
+    breaker: Arc<RwLock<CircuitBreaker>>,
+    next_url: Arc<AtomicUsize>,
+    warm: Arc<AtomicBool>,
+    probe_cache: Arc<Mutex<Option<CachedProbes>>>,
+}
+
+/// Identifiers forwarded upstream as headers and attached to upstream logs.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestIds<'a> {
+    pub session_id: &'a str,
+    pub request_id: &'a str,
 }
 
 impl UpstreamClient {
     pub fn new(config: Config) -> Result<Self, String> {
-        let client = Client::builder()
-            .build()
-            .map_err(|error| format!("failed to initialize upstream HTTP client: {error}"))?;
-        Ok(Self { client, config })
+        let client = build_http_client(&config)?;
+        let breaker = Arc::new(RwLock::new(CircuitBreaker::from_config(&config)));
+        Ok(Self {
+            client,
+            config,
+            breaker,
+            next_url: Arc::new(AtomicUsize::new(0)),
+            warm: Arc::new(AtomicBool::new(false)),
+            probe_cache: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// True once any upstream request has received a response, meaning the
+    /// pool holds an established connection the next request can reuse.
+    pub fn is_warm(&self) -> bool {
+        self.warm.load(Ordering::Relaxed)
+    }
+
+    fn mark_warm(&self) {
+        self.warm.store(true, Ordering::Relaxed);
+    }
+
+    fn next_base_url(&self) -> &str {
+        let urls = &self.config.openai_base_urls;
+        let index = self.next_url.fetch_add(1, Ordering::Relaxed) % urls.len();
+        &urls[index]
     }
 
     pub async fn chat_completion<T: Serialize + ?Sized>(
         &self,
         body: &T,
-        session_id: &str,
-    ) -> Result<OpenAiChatResponse, UpstreamError> {
+        model: &str,
+        ids: RequestIds<'_>,
+    ) -> Result<(OpenAiChatResponse, UpstreamMetadata), UpstreamError> {
         let response = self
             .send_request(
                 "/chat/completions",
                 body,
-                session_id,
-                Some(Duration::from_secs(self.config.request_timeout)),
-                "non_stream",
+                model,
+                ids,
+                Some(Duration::from_secs(self.config.request_timeout_for(model))),
+                RequestKind::NonStream,
             )
             .await?;
-        parse_success_json_response::<OpenAiChatResponse>(
+        let metadata =
+            UpstreamMetadata::from_headers(response.headers(), &self.config.echo_upstream_headers);
+        let parsed = parse_success_json_response::<OpenAiChatResponse>(
             response,
-            "non_stream",
+            RequestKind::NonStream.as_str(),
             "/chat/completions",
-            session_id,
+            ids,
         )
-        .await
+        .await?;
+        Ok((parsed, metadata))
     }
 
     pub async fn chat_completion_stream<T: Serialize + ?Sized>(
         &self,
         body: &T,
-        session_id: &str,
+        model: &str,
+        ids: RequestIds<'_>,
     ) -> Result<reqwest::Response, UpstreamError> {
-        let stream_timeout = self.config.stream_request_timeout.map(Duration::from_secs);
+        let stream_timeout = self
+            .config
+            .stream_request_timeout_for(model)
+            .map(Duration::from_secs);
         self.send_request(
             "/chat/completions",
             body,
-            session_id,
+            model,
+            ids,
             stream_timeout,
-            "stream",
+            RequestKind::Stream,
         )
         .await
     }
@@ -70,524 +130,64 @@ impl UpstreamClient {
     pub async fn responses<T: Serialize + ?Sized>(
         &self,
         body: &T,
-        session_id: &str,
-    ) -> Result<OpenAiResponsesResponse, UpstreamError> {
+        model: &str,
+        ids: RequestIds<'_>,
+    ) -> Result<(OpenAiResponsesResponse, UpstreamMetadata), UpstreamError> {
         let response = self
             .send_request(
                 "/responses",
                 body,
-                session_id,
-                Some(Duration::from_secs(self.config.request_timeout)),
-                "non_stream",
+                model,
+                ids,
+                Some(Duration::from_secs(self.config.request_timeout_for(model))),
+                RequestKind::NonStream,
             )
             .await?;
-        let (status, content_type, text) =
-            parse_success_text_response(response, "non_stream", "/responses", session_id).await?;
-        parse_responses_body(&text, Some(&content_type)).map_err(|error| UpstreamError {
+        let metadata =
+            UpstreamMetadata::from_headers(response.headers(), &self.config.echo_upstream_headers);
+        let (status, content_type, text) = parse_success_text_response(
+            response,
+            RequestKind::NonStream.as_str(),
+            "/responses",
+            ids,
+        )
+        .await?;
+        let parsed = parse_responses_body(&text, Some(&content_type)).map_err(|error| UpstreamError {
             status: salvo::http::StatusCode::BAD_GATEWAY,
             message: classify_openai_error(&format!(
                 "failed to parse upstream JSON response (status: {status}, content-type: {}, body-preview: {}): {error}",
                 content_type,
                 text.chars().take(1200).collect::<String>()
             )),
-        })
+            kind: UpstreamErrorKind::Other,
+            metadata: UpstreamMetadata::default(),
+        })?;
+        Ok((parsed, metadata))
     }
 
     pub async fn responses_stream<T: Serialize + ?Sized>(
         &self,
         body: &T,
-        session_id: &str,
-    ) -> Result<reqwest::Response, UpstreamError> {
-        let stream_timeout = self.config.stream_request_timeout.map(Duration::from_secs);
-        self.send_request("/responses", body, session_id, stream_timeout, "stream")
-            .await
-    }
-
-    async fn send_request<T: Serialize + ?Sized>(
-        &self,
-        path: &str,
-        body: &T,
-        session_id: &str,
-        timeout: Option<Duration>,
-        request_kind: &'static str,
+        model: &str,
+        ids: RequestIds<'_>,
     ) -> Result<reqwest::Response, UpstreamError> {
-        let url = format!(
-            "{}{}",
-            self.config.openai_base_url.trim_end_matches('/'),
-            path
-        );
-
-        let mut request_builder = self
-            .client
-            .post(&url)
-            .headers(build_upstream_headers(&self.config, session_id))
-            .json(body);
-
-        if let Some(api_version) = self.config.azure_api_version.as_deref() {
-            request_builder = request_builder.query(&[("api-version", api_version)]);
-        }
-
-        if let Some(duration) = timeout {
-            request_builder = request_builder.timeout(duration);
-        }
-
-        let timeout_secs = timeout.map(|value| value.as_secs());
-        debug!(
-            phase = "upstream_request_start",
-            request_kind,
-            path,
-            session_id,
-            url = %url,
-            timeout_secs = ?timeout_secs,
-            "Sending upstream request"
-        );
-        let request_started = Instant::now();
-        let response = request_builder.send().await.map_err(|error| {
-            build_send_error(
-                error,
-                timeout,
-                request_kind,
-                path,
-                session_id,
-                request_started.elapsed(),
-            )
-        })?;
-
-        log_response_headers(
-            &response,
-            request_kind,
-            path,
-            session_id,
-            timeout_secs,
-            request_started.elapsed(),
-        );
-
-        if response.status().is_success() {
-            return Ok(response);
-        }
-
-        handle_http_error_response(response, request_kind, path, session_id).await
-    }
-}
-
-const BODY_PREVIEW_LIMIT: usize = 1024;
-
-async fn handle_http_error_response(
-    response: reqwest::Response,
-    request_kind: &str,
-    path: &str,
-    session_id: &str,
-) -> Result<reqwest::Response, UpstreamError> {
-    let upstream_status = response.status();
-    let status = to_salvo_status(upstream_status);
-    let content_type = response_content_type(&response);
-    let content_length = response.content_length();
-    debug!(
-        phase = "upstream_http_error_body_read_start",
-        request_kind,
-        path,
-        session_id,
-        upstream_status = %upstream_status,
-        content_type = %content_type,
-        content_length = ?content_length,
-        "Reading upstream error response body"
-    );
-
-    let body_read_started = Instant::now();
-    let read_context = BodyReadContext::new(
-        request_kind,
-        path,
-        session_id,
-        upstream_status,
-        &content_type,
-        content_length,
-    );
-    let text = match response.text().await {
-        Ok(value) => {
-            debug!(
-                phase = "upstream_http_error_body_read_done",
-                request_kind,
-                path,
-                session_id,
-                upstream_status = %upstream_status,
-                body_bytes = value.len(),
-                elapsed_ms = body_read_started.elapsed().as_millis() as u64,
-                "Read upstream error response body"
-            );
-            value
-        }
-        Err(error) => {
-            log_error_body_read_failure(&error, &read_context, body_read_started.elapsed());
-            String::new()
-        }
-    };
-
-    let body_preview = preview_text(&text, BODY_PREVIEW_LIMIT);
-    let raw_message = extract_error_message_from_body(&text);
-
-    warn!(
-        phase = "upstream_http_error",
-        request_kind,
-        path,
-        session_id,
-        status = %status,
-        upstream_status = %upstream_status,
-        content_type = %content_type,
-        content_length = ?content_length,
-        body_bytes = text.len(),
-        body_preview = %body_preview,
-        "Upstream returned non-success status"
-    );
-
-    Err(UpstreamError {
-        status,
-        message: classify_openai_error(&raw_message),
-    })
-}
-
-fn log_error_body_read_failure(
-    error: &reqwest::Error,
-    context: &BodyReadContext<'_>,
-    elapsed: Duration,
-) {
-    if error.is_timeout() {
-        warn!(
-            phase = "upstream_http_error_body_timeout",
-            request_kind = context.request_kind,
-            path = context.path,
-            session_id = context.session_id,
-            status = %context.status,
-            content_type = %context.content_type,
-            content_length = ?context.content_length,
-            elapsed_ms = elapsed.as_millis() as u64,
-            "Timed out while reading upstream error response body: {error}"
-        );
-        return;
-    }
-
-    warn!(
-        phase = "upstream_error_body_read_failed",
-        request_kind = context.request_kind,
-        path = context.path,
-        session_id = context.session_id,
-        status = %context.status,
-        content_type = %context.content_type,
-        content_length = ?context.content_length,
-        elapsed_ms = elapsed.as_millis() as u64,
-        "Failed to read upstream error response body: {error}"
-    );
-}
-
-fn log_response_headers(
-    response: &reqwest::Response,
-    request_kind: &str,
-    path: &str,
-    session_id: &str,
-    timeout_secs: Option<u64>,
-    elapsed: Duration,
-) {
-    debug!(
-        phase = "upstream_response_headers",
-        request_kind,
-        path,
-        session_id,
-        timeout_secs = ?timeout_secs,
-        status = %response.status(),
-        content_type = %response_content_type(response),
-        content_length = ?response.content_length(),
-        transfer_encoding = %response_header_value(response, "transfer-encoding"),
-        elapsed_ms = elapsed.as_millis() as u64,
-        "Received upstream response headers"
-    );
-}
-
-fn response_content_type(response: &reqwest::Response) -> String {
-    response
-        .headers()
-        .get(CONTENT_TYPE)
-        .and_then(|value| value.to_str().ok())
-        .map(ToOwned::to_owned)
-        .unwrap_or_else(|| "<missing>".to_string())
-}
-
-fn response_header_value(response: &reqwest::Response, header_name: &str) -> String {
-    response
-        .headers()
-        .get(header_name)
-        .and_then(|value| value.to_str().ok())
-        .map(ToOwned::to_owned)
-        .unwrap_or_else(|| "<missing>".to_string())
-}
-
-async fn parse_success_text_response(
-    response: reqwest::Response,
-    request_kind: &str,
-    path: &str,
-    session_id: &str,
-) -> Result<(reqwest::StatusCode, String, String), UpstreamError> {
-    let status = response.status();
-    let content_type = response_content_type(&response);
-    let content_length = response.content_length();
-    debug!(
-        phase = "upstream_success_body_read_start",
-        request_kind,
-        path,
-        session_id,
-        status = %status,
-        content_type = %content_type,
-        content_length = ?content_length,
-        "Reading upstream success response body"
-    );
-
-    let body_read_started = Instant::now();
-    let read_context = BodyReadContext::new(
-        request_kind,
-        path,
-        session_id,
-        status,
-        &content_type,
-        content_length,
-    );
-    let text = response.text().await.map_err(|error| {
-        build_body_read_error(error, &read_context, body_read_started.elapsed())
-    })?;
-
-    debug!(
-        phase = "upstream_success_body_read_done",
-        request_kind,
-        path,
-        session_id,
-        status = %status,
-        body_bytes = text.len(),
-        elapsed_ms = body_read_started.elapsed().as_millis() as u64,
-        "Read upstream success response body"
-    );
-
-    Ok((status, content_type, text))
-}
-
-async fn parse_success_json_response<T: DeserializeOwned>(
-    response: reqwest::Response,
-    request_kind: &str,
-    path: &str,
-    session_id: &str,
-) -> Result<T, UpstreamError> {
-    let status = response.status();
-    let content_type = response_content_type(&response);
-    let content_length = response.content_length();
-    debug!(
-        phase = "upstream_success_body_read_start",
-        request_kind,
-        path,
-        session_id,
-        status = %status,
-        content_type = %content_type,
-        content_length = ?content_length,
-        "Reading upstream success response body"
-    );
-
-    let body_read_started = Instant::now();
-    let read_context = BodyReadContext::new(
-        request_kind,
-        path,
-        session_id,
-        status,
-        &content_type,
-        content_length,
-    );
-    let body = response.bytes().await.map_err(|error| {
-        build_body_read_error(error, &read_context, body_read_started.elapsed())
-    })?;
-    debug!(
-        phase = "upstream_success_body_read_done",
-        request_kind,
-        path,
-        session_id,
-        status = %status,
-        body_bytes = body.len(),
-        elapsed_ms = body_read_started.elapsed().as_millis() as u64,
-        "Read upstream success response body"
-    );
-
-    decode_json_body::<T>(status, &content_type, &body)
-}
-
-fn build_body_read_error(
-    error: reqwest::Error,
-    context: &BodyReadContext<'_>,
-    elapsed: Duration,
-) -> UpstreamError {
-    if error.is_timeout() {
-        error!(
-            phase = "upstream_body_read_timeout",
-            request_kind = context.request_kind,
-            path = context.path,
-            session_id = context.session_id,
-            status = %context.status,
-            content_type = %context.content_type,
-            content_length = ?context.content_length,
-            elapsed_ms = elapsed.as_millis() as u64,
-            "Timed out while reading upstream response body: {error}"
-        );
-    } else {
-        error!(
-            phase = "upstream_body_read_failed",
-            request_kind = context.request_kind,
-            path = context.path,
-            session_id = context.session_id,
-            status = %context.status,
-            content_type = %context.content_type,
-            content_length = ?context.content_length,
-            elapsed_ms = elapsed.as_millis() as u64,
-            "Failed to read upstream response body: {error}"
-        );
-    }
-
-    UpstreamError {
-        status: salvo::http::StatusCode::BAD_GATEWAY,
-        message: classify_openai_error(&format!(
-            "failed to read upstream response body (status: {}, content-type: {}): {error}",
-            context.status, context.content_type
-        )),
-    }
-}
-
-struct BodyReadContext<'a> {
-    request_kind: &'a str,
-    path: &'a str,
-    session_id: &'a str,
-    status: reqwest::StatusCode,
-    content_type: &'a str,
-    content_length: Option<u64>,
-}
-
-impl<'a> BodyReadContext<'a> {
-    fn new(
-        request_kind: &'a str,
-        path: &'a str,
-        session_id: &'a str,
-        status: reqwest::StatusCode,
-        content_type: &'a str,
-        content_length: Option<u64>,
-    ) -> Self {
-        Self {
-            request_kind,
-            path,
-            session_id,
-            status,
-            content_type,
-            content_length,
-        }
-    }
-}
-
-fn decode_json_body<T: DeserializeOwned>(
-    status: reqwest::StatusCode,
-    content_type: &str,
-    body: &[u8],
-) -> Result<T, UpstreamError> {
-    serde_json::from_slice::<T>(body).map_err(|error| {
-        let body_preview = preview_bytes(body, BODY_PREVIEW_LIMIT);
-        UpstreamError {
-            status: salvo::http::StatusCode::BAD_GATEWAY,
-            message: classify_openai_error(&format!(
-                "failed to parse upstream JSON response (status: {status}, content-type: {content_type}, body-preview: {body_preview}): {error}"
-            )),
-        }
-    })
-}
-
-fn preview_bytes(body: &[u8], limit: usize) -> String {
-    match std::str::from_utf8(body) {
-        Ok(text) => preview_text(text, limit).into_owned(),
-        Err(_) => {
-            let len = body.len().min(limit);
-            let mut preview = String::with_capacity(len * 2 + 32);
-            for byte in &body[..len] {
-                use std::fmt::Write;
-                let _ = write!(&mut preview, "{byte:02x}");
-            }
-            if body.len() > limit {
-                preview.push_str("...(truncated)");
-            }
-            format!("<non-utf8 hex: {preview}>")
-        }
-    }
-}
-
-fn preview_text(text: &str, limit: usize) -> Cow<'_, str> {
-    let mut iterator = text.chars();
-    let preview: String = iterator.by_ref().take(limit).collect();
-    if iterator.next().is_none() {
-        Cow::Borrowed(text)
-    } else {
-        Cow::Owned(format!("{preview}...(truncated)"))
-    }
-}
-
-fn build_send_error(
-    error: reqwest::Error,
-    timeout: Option<Duration>,
-    request_kind: &'static str,
-    path: &str,
-    session_id: &str,
-    elapsed: Duration,
-) -> UpstreamError {
-    log_send_stage_error(&error, timeout, request_kind, path, session_id, elapsed);
-    UpstreamError {
-        status: salvo::http::StatusCode::BAD_GATEWAY,
-        message: classify_openai_error(&format!("upstream request failed: {error}")),
-    }
-}
-
-fn log_send_stage_error(
-    error: &reqwest::Error,
-    timeout: Option<Duration>,
-    request_kind: &str,
-    path: &str,
-    session_id: &str,
-    elapsed: Duration,
-) {
-    let timeout_secs = timeout.map(|value| value.as_secs());
-
-    if error.is_timeout() {
-        error!(
-            phase = "upstream_connect_timeout",
-            request_kind,
-            path,
-            session_id,
-            timeout_secs = ?timeout_secs,
-            elapsed_ms = elapsed.as_millis() as u64,
-            "Upstream timeout before response headers"
-        );
-        return;
-    }
-
-    if error.is_connect() {
-        error!(
-            phase = "upstream_connect_error",
-            request_kind,
-            path,
-            session_id,
-            timeout_secs = ?timeout_secs,
-            elapsed_ms = elapsed.as_millis() as u64,
-            "Upstream connection failed before response headers: {error}"
-        );
-        return;
+        let stream_timeout = self
+            .config
+            .stream_request_timeout_for(model)
+            .map(Duration::from_secs);
+        self.send_request(
+            "/responses",
+            body,
+            model,
+            ids,
+            stream_timeout,
+            RequestKind::Stream,
+        )
+        .await
     }
-
-    error!(
-        phase = "upstream_request_error",
-        request_kind,
-        path,
-        session_id,
-        timeout_secs = ?timeout_secs,
-        elapsed_ms = elapsed.as_millis() as u64,
-        "Upstream request failed before response headers: {error}"
-    );
 }
 
-fn build_upstream_headers(config: &Config, session_id: &str) -> HeaderMap {
+fn build_upstream_headers(config: &Config, ids: RequestIds<'_>) -> HeaderMap {
     let mut headers = HeaderMap::new();
     headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
     headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("identity"));
@@ -612,51 +212,198 @@ fn build_upstream_headers(config: &Config, session_id: &str) -> HeaderMap {
         headers.insert(name, value);
     }
 
-    if let Ok(value) = HeaderValue::from_str(session_id) {
+    if let Ok(value) = HeaderValue::from_str(ids.session_id) {
         headers.insert("session_id", value);
     }
+    if let Ok(value) = HeaderValue::from_str(ids.request_id) {
+        headers.insert(REQUEST_ID_HEADER, value);
+    }
 
     headers
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{build_upstream_headers, decode_json_body, preview_bytes, preview_text};
-    use crate::config::{Config, WireApi};
-    use reqwest::StatusCode;
-    use serde::Deserialize;
-    use std::collections::HashMap;
+    use super::test_support::test_config;
+    use super::{RequestIds, UpstreamClient, build_upstream_headers};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use uuid::Uuid;
 
-    fn test_config() -> Config {
-        Config {
-            openai_api_key: "sk-test".to_string(),
-            anthropic_api_key: None,
-            openai_base_url: "https://api.openai.com/v1".to_string(),
-            azure_api_version: None,
-            host: "0.0.0.0".to_string(),
-            port: 8082,
-            log_level: "INFO".to_string(),
-            request_timeout: 90,
-            stream_request_timeout: None,
-            request_body_max_size: 16 * 1024 * 1024,
-            session_ttl_min_secs: 1800,
-            session_ttl_max_secs: 86400,
-            session_cleanup_interval_secs: 60,
-            debug_tool_id_matching: false,
-            wire_api: WireApi::Chat,
-            big_model: "gpt-4o".to_string(),
-            middle_model: "gpt-4o".to_string(),
-            small_model: "gpt-4o-mini".to_string(),
-            min_thinking_level: None,
-            custom_headers: HashMap::new(),
-        }
+    #[test]
+    fn base_urls_are_selected_round_robin() {
+        let mut config = test_config();
+        config.openai_base_urls = vec!["http://a/v1".to_string(), "http://b/v1".to_string()];
+        let client = UpstreamClient::new(config).expect("client should build");
+
+        let picked: Vec<&str> = (0..4).map(|_| client.next_base_url()).collect();
+        assert_eq!(
+            picked,
+            ["http://a/v1", "http://b/v1", "http://a/v1", "http://b/v1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_response_marks_client_warm() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let address = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.expect("accept");
+            let mut buffer = vec![0; 4096];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buffer).await;
+            let _ = tokio::io::AsyncWriteExt::write_all(
+                &mut socket,
+                b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n",
+            )
+            .await;
+        });
+
+        let mut config = test_config();
+        config.openai_base_urls = vec![format!("http://{address}/v1")];
+        let client = UpstreamClient::new(config).expect("client should build");
+        assert!(!client.is_warm());
+
+        let probes = client.probe_base_urls().await;
+        assert!(probes[0].reachable);
+        assert!(client.is_warm());
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limited_requests_until_success() {
+        const RATE_LIMITED: &[u8] =
+            b"HTTP/1.1 429 Too Many Requests\r\nconnection: close\r\ncontent-length: 2\r\n\r\n{}";
+        const COMPLETED: &str = r#"{"id":"chatcmpl-1","choices":[{"index":0,"message":{"role":"assistant","content":"ok"},"finish_reason":"stop"}]}"#;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let address = listener.local_addr().expect("local addr");
+        let server = tokio::spawn(async move {
+            for attempt in 0..4 {
+                let (mut socket, _) = listener.accept().await.expect("accept");
+                let mut buffer = vec![0; 8192];
+                let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buffer).await;
+                let reply = if attempt < 3 {
+                    RATE_LIMITED.to_vec()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{COMPLETED}",
+                        COMPLETED.len()
+                    )
+                    .into_bytes()
+                };
+                let _ = tokio::io::AsyncWriteExt::write_all(&mut socket, &reply).await;
+            }
+        });
+
+        let mut config = test_config();
+        config.openai_base_urls = vec![format!("http://{address}/v1")];
+        config.max_retries = 3;
+        config.retry_base_delay_ms = 1;
+        config.retry_jitter_factor = 0.0;
+        let client = UpstreamClient::new(config).expect("client should build");
+
+        let ids = RequestIds {
+            session_id: "session-1",
+            request_id: "req-1",
+        };
+        let (response, _) = client
+            .chat_completion(&serde_json::json!({"model": "gpt-4o"}), "gpt-4o", ids)
+            .await
+            .expect("fourth attempt should succeed");
+        assert_eq!(response.id.as_deref(), Some("chatcmpl-1"));
+        server.await.expect("server should serve four attempts");
+    }
+
+    #[tokio::test]
+    async fn open_breaker_fails_fast_without_contacting_upstream() {
+        const BAD_GATEWAY: &[u8] =
+            b"HTTP/1.1 502 Bad Gateway\r\nconnection: close\r\ncontent-length: 2\r\n\r\n{}";
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let address = listener.local_addr().expect("local addr");
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = Arc::clone(&connections);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let mut buffer = vec![0; 8192];
+                let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buffer).await;
+                let _ = tokio::io::AsyncWriteExt::write_all(&mut socket, BAD_GATEWAY).await;
+            }
+        });
+
+        let mut config = test_config();
+        config.openai_base_urls = vec![format!("http://{address}/v1")];
+        config.circuit_breaker_failure_threshold = 1;
+        let client = UpstreamClient::new(config).expect("client should build");
+        let ids = RequestIds {
+            session_id: "session-1",
+            request_id: "req-1",
+        };
+        let body = serde_json::json!({"model": "gpt-4o"});
+
+        let first = client.chat_completion(&body, "gpt-4o", ids).await;
+        assert_eq!(first.err().map(|error| error.status.as_u16()), Some(502));
+        let second = client.chat_completion(&body, "gpt-4o", ids).await;
+        assert_eq!(second.err().map(|error| error.status.as_u16()), Some(503));
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn stream_requests_are_not_retried() {
+        const RATE_LIMITED: &[u8] =
+            b"HTTP/1.1 429 Too Many Requests\r\nconnection: close\r\ncontent-length: 2\r\n\r\n{}";
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let address = listener.local_addr().expect("local addr");
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = Arc::clone(&connections);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let mut buffer = vec![0; 8192];
+                let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buffer).await;
+                let _ = tokio::io::AsyncWriteExt::write_all(&mut socket, RATE_LIMITED).await;
+            }
+        });
+
+        let mut config = test_config();
+        config.openai_base_urls = vec![format!("http://{address}/v1")];
+        config.max_retries = 3;
+        config.retry_base_delay_ms = 1;
+        config.retry_jitter_factor = 0.0;
+        let client = UpstreamClient::new(config).expect("client should build");
+        let ids = RequestIds {
+            session_id: "session-1",
+            request_id: "req-1",
+        };
+        let body = serde_json::json!({"model": "gpt-4o", "stream": true});
+
+        let chat = client.chat_completion_stream(&body, "gpt-4o", ids).await;
+        assert_eq!(chat.err().map(|error| error.status.as_u16()), Some(429));
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+        let responses = client.responses_stream(&body, "gpt-4o", ids).await;
+        assert_eq!(
+            responses.err().map(|error| error.status.as_u16()),
+            Some(429)
+        );
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
     }
 
     #[test]
     fn adds_session_id_header() {
         let session_id = Uuid::new_v4().to_string();
-        let headers = build_upstream_headers(&test_config(), &session_id);
+        let headers = build_upstream_headers(
+            &test_config(),
+            RequestIds {
+                session_id: &session_id,
+                request_id: "req-1",
+            },
+        );
 
         let value = headers
             .get("session_id")
@@ -669,7 +416,13 @@ mod tests {
     #[test]
     fn session_id_header_contains_valid_uuid() {
         let session_id = Uuid::new_v4().to_string();
-        let headers = build_upstream_headers(&test_config(), &session_id);
+        let headers = build_upstream_headers(
+            &test_config(),
+            RequestIds {
+                session_id: &session_id,
+                request_id: "req-1",
+            },
+        );
 
         let value = headers
             .get("session_id")
@@ -679,51 +432,21 @@ mod tests {
         assert!(Uuid::parse_str(value).is_ok());
     }
 
-    #[derive(Debug, Deserialize)]
-    struct TestPayload {
-        value: String,
-    }
-
     #[test]
-    fn decodes_valid_json_payload() {
-        let payload = decode_json_body::<TestPayload>(
-            StatusCode::OK,
-            "application/json",
-            br#"{"value":"ok"}"#,
-        )
-        .expect("json should decode");
-
-        assert_eq!(payload.value, "ok");
-    }
-
-    #[test]
-    fn parse_error_includes_status_content_type_and_preview() {
-        let error = decode_json_body::<TestPayload>(
-            StatusCode::OK,
-            "text/html",
-            b"<html><body>upstream gateway failed</body></html>",
-        )
-        .expect_err("json should fail");
-
-        assert_eq!(error.status, salvo::http::StatusCode::BAD_GATEWAY);
-        assert!(error.message.contains("status: 200 OK"));
-        assert!(error.message.contains("content-type: text/html"));
-        assert!(
-            error
-                .message
-                .contains("body-preview: <html><body>upstream gateway failed</body></html>")
+    fn forwards_request_id_header() {
+        let headers = build_upstream_headers(
+            &test_config(),
+            RequestIds {
+                session_id: "session",
+                request_id: "req-abc",
+            },
         );
-    }
 
-    #[test]
-    fn preview_text_truncates_long_text() {
-        let preview = preview_text("abcdef", 3);
-        assert_eq!(preview, "abc...(truncated)");
-    }
-
-    #[test]
-    fn preview_bytes_formats_non_utf8_as_hex() {
-        let preview = preview_bytes(&[0xff, 0x00, 0x7f], 8);
-        assert_eq!(preview, "<non-utf8 hex: ff007f>");
+        assert_eq!(
+            headers
+                .get("x-request-id")
+                .and_then(|raw| raw.to_str().ok()),
+            Some("req-abc")
+        );
     }
 }
@@ -0,0 +1,327 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+use reqwest::header::CONTENT_TYPE;
+use serde::de::DeserializeOwned;
+use tracing::{debug, error};
+
+use super::RequestIds;
+use crate::errors::{UpstreamError, UpstreamErrorKind, classify_openai_error};
+use crate::upstream_metadata::UpstreamMetadata;
+
+pub(super) const BODY_PREVIEW_LIMIT: usize = 1024;
+
+pub(super) fn log_response_headers(
+    response: &reqwest::Response,
+    request_kind: &str,
+    path: &str,
+    ids: RequestIds<'_>,
+    timeout_secs: Option<u64>,
+    elapsed: Duration,
+) {
+    debug!(
+        phase = "upstream_response_headers",
+        request_kind,
+        path,
+        session_id = ids.session_id,
+        request_id = ids.request_id,
+        timeout_secs = ?timeout_secs,
+        status = %response.status(),
+        content_type = %response_content_type(response),
+        content_length = ?response.content_length(),
+        transfer_encoding = %response_header_value(response, "transfer-encoding"),
+        elapsed_ms = elapsed.as_millis() as u64,
+        "Received upstream response headers"
+    );
+}
+
+pub(super) fn response_content_type(response: &reqwest::Response) -> String {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| "<missing>".to_string())
+}
+
+fn response_header_value(response: &reqwest::Response, header_name: &str) -> String {
+    response
+        .headers()
+        .get(header_name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| "<missing>".to_string())
+}
+
+pub(super) async fn parse_success_text_response(
+    response: reqwest::Response,
+    request_kind: &str,
+    path: &str,
+    ids: RequestIds<'_>,
+) -> Result<(reqwest::StatusCode, String, String), UpstreamError> {
+    let status = response.status();
+    let content_type = response_content_type(&response);
+    let content_length = response.content_length();
+    debug!(
+        phase = "upstream_success_body_read_start",
+        request_kind,
+        path,
+        session_id = ids.session_id,
+        request_id = ids.request_id,
+        status = %status,
+        content_type = %content_type,
+        content_length = ?content_length,
+        "Reading upstream success response body"
+    );
+
+    let body_read_started = Instant::now();
+    let read_context = BodyReadContext::new(
+        request_kind,
+        path,
+        ids,
+        status,
+        &content_type,
+        content_length,
+    );
+    let text = response.text().await.map_err(|error| {
+        build_body_read_error(error, &read_context, body_read_started.elapsed())
+    })?;
+
+    debug!(
+        phase = "upstream_success_body_read_done",
+        request_kind,
+        path,
+        session_id = ids.session_id,
+        request_id = ids.request_id,
+        status = %status,
+        body_bytes = text.len(),
+        elapsed_ms = body_read_started.elapsed().as_millis() as u64,
+        "Read upstream success response body"
+    );
+
+    Ok((status, content_type, text))
+}
+
+pub(super) async fn parse_success_json_response<T: DeserializeOwned>(
+    response: reqwest::Response,
+    request_kind: &str,
+    path: &str,
+    ids: RequestIds<'_>,
+) -> Result<T, UpstreamError> {
+    let status = response.status();
+    let content_type = response_content_type(&response);
+    let content_length = response.content_length();
+    debug!(
+        phase = "upstream_success_body_read_start",
+        request_kind,
+        path,
+        session_id = ids.session_id,
+        request_id = ids.request_id,
+        status = %status,
+        content_type = %content_type,
+        content_length = ?content_length,
+        "Reading upstream success response body"
+    );
+
+    let body_read_started = Instant::now();
+    let read_context = BodyReadContext::new(
+        request_kind,
+        path,
+        ids,
+        status,
+        &content_type,
+        content_length,
+    );
+    let body = response.bytes().await.map_err(|error| {
+        build_body_read_error(error, &read_context, body_read_started.elapsed())
+    })?;
+    debug!(
+        phase = "upstream_success_body_read_done",
+        request_kind,
+        path,
+        session_id = ids.session_id,
+        request_id = ids.request_id,
+        status = %status,
+        body_bytes = body.len(),
+        elapsed_ms = body_read_started.elapsed().as_millis() as u64,
+        "Read upstream success response body"
+    );
+
+    decode_json_body::<T>(status, &content_type, &body)
+}
+
+fn build_body_read_error(
+    error: reqwest::Error,
+    context: &BodyReadContext<'_>,
+    elapsed: Duration,
+) -> UpstreamError {
+    if error.is_timeout() {
+        error!(
+            phase = "upstream_body_read_timeout",
+            request_kind = context.request_kind,
+            path = context.path,
+            session_id = context.session_id,
+            request_id = context.request_id,
+            status = %context.status,
+            content_type = %context.content_type,
+            content_length = ?context.content_length,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Timed out while reading upstream response body: {error}"
+        );
+    } else {
+        error!(
+            phase = "upstream_body_read_failed",
+            request_kind = context.request_kind,
+            path = context.path,
+            session_id = context.session_id,
+            request_id = context.request_id,
+            status = %context.status,
+            content_type = %context.content_type,
+            content_length = ?context.content_length,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Failed to read upstream response body: {error}"
+        );
+    }
+
+    UpstreamError {
+        status: salvo::http::StatusCode::BAD_GATEWAY,
+        message: classify_openai_error(&format!(
+            "failed to read upstream response body (status: {}, content-type: {}): {error}",
+            context.status, context.content_type
+        )),
+        kind: UpstreamErrorKind::Other,
+        metadata: UpstreamMetadata::default(),
+    }
+}
+
+pub(super) struct BodyReadContext<'a> {
+    pub(super) request_kind: &'a str,
+    pub(super) path: &'a str,
+    pub(super) session_id: &'a str,
+    pub(super) request_id: &'a str,
+    pub(super) status: reqwest::StatusCode,
+    pub(super) content_type: &'a str,
+    pub(super) content_length: Option<u64>,
+}
+
+impl<'a> BodyReadContext<'a> {
+    pub(super) fn new(
+        request_kind: &'a str,
+        path: &'a str,
+        ids: RequestIds<'a>,
+        status: reqwest::StatusCode,
+        content_type: &'a str,
+        content_length: Option<u64>,
+    ) -> Self {
+        Self {
+            request_kind,
+            path,
+            session_id: ids.session_id,
+            request_id: ids.request_id,
+            status,
+            content_type,
+            content_length,
+        }
+    }
+}
+
+fn decode_json_body<T: DeserializeOwned>(
+    status: reqwest::StatusCode,
+    content_type: &str,
+    body: &[u8],
+) -> Result<T, UpstreamError> {
+    serde_json::from_slice::<T>(body).map_err(|error| {
+        let body_preview = preview_bytes(body, BODY_PREVIEW_LIMIT);
+        UpstreamError {
+            status: salvo::http::StatusCode::BAD_GATEWAY,
+            message: classify_openai_error(&format!(
+                "failed to parse upstream JSON response (status: {status}, content-type: {content_type}, body-preview: {body_preview}): {error}"
+            )),
+            kind: UpstreamErrorKind::Other,
+            metadata: UpstreamMetadata::default(),
+        }
+    })
+}
+
+fn preview_bytes(body: &[u8], limit: usize) -> String {
+    match std::str::from_utf8(body) {
+        Ok(text) => preview_text(text, limit).into_owned(),
+        Err(_) => {
+            let len = body.len().min(limit);
+            let mut preview = String::with_capacity(len * 2 + 32);
+            for byte in &body[..len] {
+                use std::fmt::Write;
+                let _ = write!(&mut preview, "{byte:02x}");
+            }
+            if body.len() > limit {
+                preview.push_str("...(truncated)");
+            }
+            format!("<non-utf8 hex: {preview}>")
+        }
+    }
+}
+
+pub(super) fn preview_text(text: &str, limit: usize) -> Cow<'_, str> {
+    let mut iterator = text.chars();
+    let preview: String = iterator.by_ref().take(limit).collect();
+    if iterator.next().is_none() {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("{preview}...(truncated)"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_json_body, preview_bytes, preview_text};
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct TestPayload {
+        value: String,
+    }
+
+    #[test]
+    fn decodes_valid_json_payload() {
+        let payload = decode_json_body::<TestPayload>(
+            StatusCode::OK,
+            "application/json",
+            br#"{"value":"ok"}"#,
+        )
+        .expect("json should decode");
+
+        assert_eq!(payload.value, "ok");
+    }
+
+    #[test]
+    fn parse_error_includes_status_content_type_and_preview() {
+        let error = decode_json_body::<TestPayload>(
+            StatusCode::OK,
+            "text/html",
+            b"<html><body>upstream gateway failed</body></html>",
+        )
+        .expect_err("json should fail");
+
+        assert_eq!(error.status, salvo::http::StatusCode::BAD_GATEWAY);
+        assert!(error.message.contains("status: 200 OK"));
+        assert!(error.message.contains("content-type: text/html"));
+        assert!(
+            error
+                .message
+                .contains("body-preview: <html><body>upstream gateway failed</body></html>")
+        );
+    }
+
+    #[test]
+    fn preview_text_truncates_long_text() {
+        let preview = preview_text("abcdef", 3);
+        assert_eq!(preview, "abc...(truncated)");
+    }
+
+    #[test]
+    fn preview_bytes_formats_non_utf8_as_hex() {
+        let preview = preview_bytes(&[0xff, 0x00, 0x7f], 8);
+        assert_eq!(preview, "<non-utf8 hex: ff007f>");
+    }
+}
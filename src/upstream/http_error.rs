@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+use super::RequestIds;
+use super::body::{BODY_PREVIEW_LIMIT, BodyReadContext, preview_text, response_content_type};
+use crate::errors::UpstreamError;
+use crate::upstream_metadata::UpstreamMetadata;
+use crate::utils::to_salvo_status;
+
+pub(super) async fn handle_http_error_response(
+    response: reqwest::Response,
+    request_kind: &str,
+    path: &str,
+    ids: RequestIds<'_>,
+    echo_headers: &[String],
+) -> UpstreamError {
+    let upstream_status = response.status();
+    let status = to_salvo_status(upstream_status);
+    let metadata = UpstreamMetadata::from_headers(response.headers(), echo_headers);
+    let content_type = response_content_type(&response);
+    let content_length = response.content_length();
+    debug!(
+        phase = "upstream_http_error_body_read_start",
+        request_kind,
+        path,
+        session_id = ids.session_id,
+        request_id = ids.request_id,
+        upstream_status = %upstream_status,
+        content_type = %content_type,
+        content_length = ?content_length,
+        "Reading upstream error response body"
+    );
+
+    let body_read_started = Instant::now();
+    let read_context = BodyReadContext::new(
+        request_kind,
+        path,
+        ids,
+        upstream_status,
+        &content_type,
+        content_length,
+    );
+    let text = match response.text().await {
+        Ok(value) => {
+            debug!(
+                phase = "upstream_http_error_body_read_done",
+                request_kind,
+                path,
+                session_id = ids.session_id,
+                request_id = ids.request_id,
+                upstream_status = %upstream_status,
+                body_bytes = value.len(),
+                elapsed_ms = body_read_started.elapsed().as_millis() as u64,
+                "Read upstream error response body"
+            );
+            value
+        }
+        Err(error) => {
+            log_error_body_read_failure(&error, &read_context, body_read_started.elapsed());
+            String::new()
+        }
+    };
+
+    let body_preview = preview_text(&text, BODY_PREVIEW_LIMIT);
+
+    warn!(
+        phase = "upstream_http_error",
+        request_kind,
+        path,
+        session_id = ids.session_id,
+        request_id = ids.request_id,
+        status = %status,
+        upstream_status = %upstream_status,
+        content_type = %content_type,
+        content_length = ?content_length,
+        body_bytes = text.len(),
+        body_preview = %body_preview,
+        "Upstream returned non-success status"
+    );
+
+    UpstreamError::from_error_body(status, &text, metadata)
+}
+
+fn log_error_body_read_failure(
+    error: &reqwest::Error,
+    context: &BodyReadContext<'_>,
+    elapsed: Duration,
+) {
+    if error.is_timeout() {
+        warn!(
+            phase = "upstream_http_error_body_timeout",
+            request_kind = context.request_kind,
+            path = context.path,
+            session_id = context.session_id,
+            request_id = context.request_id,
+            status = %context.status,
+            content_type = %context.content_type,
+            content_length = ?context.content_length,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Timed out while reading upstream error response body: {error}"
+        );
+        return;
+    }
+
+    warn!(
+        phase = "upstream_error_body_read_failed",
+        request_kind = context.request_kind,
+        path = context.path,
+        session_id = context.session_id,
+        request_id = context.request_id,
+        status = %context.status,
+        content_type = %context.content_type,
+        content_length = ?context.content_length,
+        elapsed_ms = elapsed.as_millis() as u64,
+        "Failed to read upstream error response body: {error}"
+    );
+}
@@ -0,0 +1,129 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use super::{RequestIds, UpstreamClient, build_upstream_headers};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// `/health` may be polled often by load balancers; within this window they
+/// all see the same probe instead of each sending authenticated requests to
+/// every upstream.
+const PROBE_CACHE_TTL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UpstreamProbe {
+    pub url: String,
+    pub reachable: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+pub(super) struct CachedProbes {
+    probed_at: Instant,
+    probes: Vec<UpstreamProbe>,
+}
+
+impl UpstreamClient {
+    /// The last probe if it is younger than `PROBE_CACHE_TTL`, otherwise a
+    /// fresh one; concurrent callers wait for the same probe.
+    pub async fn cached_probes(&self) -> Vec<UpstreamProbe> {
+        let mut cached = self.probe_cache.lock().await;
+        if let Some(entry) = cached.as_ref()
+            && entry.probed_at.elapsed() < PROBE_CACHE_TTL
+        {
+            return entry.probes.clone();
+        }
+        let probes = self.probe_base_urls().await;
+        *cached = Some(CachedProbes {
+            probed_at: Instant::now(),
+            probes: probes.clone(),
+        });
+        probes
+    }
+
+    /// Any HTTP response from `GET {base}/models` counts as reachable; only
+    /// transport failures mark a base URL as down.
+    pub async fn probe_base_urls(&self) -> Vec<UpstreamProbe> {
+        let probes = self
+            .config
+            .openai_base_urls
+            .iter()
+            .map(|base_url| self.probe_base_url(base_url));
+        futures_util::future::join_all(probes).await
+    }
+
+    async fn probe_base_url(&self, base_url: &str) -> UpstreamProbe {
+        let started = Instant::now();
+        let mut request = self
+            .client
+            .get(format!("{base_url}/models"))
+            .headers(build_upstream_headers(
+                &self.config,
+                RequestIds {
+                    session_id: "health-check",
+                    request_id: "health-check",
+                },
+            ))
+            .timeout(PROBE_TIMEOUT);
+        if let Some(api_version) = self.config.azure_api_version.as_deref() {
+            request = request.query(&[("api-version", api_version)]);
+        }
+        let result = request.send().await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        if result.is_ok() {
+            self.mark_warm();
+        }
+        match result {
+            Ok(response) => UpstreamProbe {
+                url: base_url.to_string(),
+                reachable: true,
+                status_code: Some(response.status().as_u16()),
+                latency_ms,
+                error: None,
+            },
+            Err(error) => UpstreamProbe {
+                url: base_url.to_string(),
+                reachable: false,
+                status_code: None,
+                latency_ms,
+                error: Some(error.to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::upstream::UpstreamClient;
+    use crate::upstream::test_support::test_config;
+
+    #[tokio::test]
+    async fn health_probes_are_reused_within_the_cache_window() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let address = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.expect("accept");
+            let mut buffer = vec![0; 4096];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buffer).await;
+            let _ = tokio::io::AsyncWriteExt::write_all(
+                &mut socket,
+                b"HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 0\r\n\r\n",
+            )
+            .await;
+        });
+
+        let mut config = test_config();
+        config.openai_base_urls = vec![format!("http://{address}/v1")];
+        let client = UpstreamClient::new(config).expect("client should build");
+
+        assert!(client.cached_probes().await[0].reachable);
+        // The listener is gone after one connection; only a cached probe can
+        // still report the upstream as reachable.
+        let again = client.cached_probes().await;
+        assert!(again[0].reachable);
+        assert_eq!(again[0].status_code, Some(200));
+    }
+}
@@ -0,0 +1,291 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::field::Empty;
+use tracing::{Instrument, Span, debug, error, info_span, warn};
+
+use super::body::log_response_headers;
+use super::http_error::handle_http_error_response;
+use super::{RequestIds, UpstreamClient, build_upstream_headers};
+use crate::errors::{UpstreamError, UpstreamErrorKind, classify_openai_error};
+use crate::metrics::metrics;
+use crate::telemetry::inject_trace_context;
+use crate::upstream_breaker::{BreakerState, circuit_open_error};
+use crate::upstream_metadata::UpstreamMetadata;
+use crate::upstream_retry::{
+    RequestKind, is_retryable_http_error, is_retryable_send_error, parse_retry_after,
+};
+
+impl UpstreamClient {
+    pub(super) async fn send_request<T: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &T,
+        model: &str,
+        ids: RequestIds<'_>,
+        timeout: Option<Duration>,
+        kind: RequestKind,
+    ) -> Result<reqwest::Response, UpstreamError> {
+        let request_kind = kind.as_str();
+        if !self.breaker.write().await.try_acquire(Instant::now()) {
+            warn!(
+                phase = "upstream_circuit_open",
+                request_kind,
+                path,
+                session_id = ids.session_id,
+                request_id = ids.request_id,
+                "Failing fast while upstream circuit breaker is open"
+            );
+            return Err(circuit_open_error());
+        }
+
+        let span = info_span!(
+            "upstream_request",
+            request_kind,
+            path,
+            model,
+            url = Empty,
+            status = Empty,
+            elapsed_ms = Empty,
+        );
+        let started = Instant::now();
+        let result = self
+            .send_with_retries(path, body, model, ids, timeout, kind)
+            .instrument(span.clone())
+            .await;
+        let status = match &result {
+            Ok(response) => response.status().as_u16(),
+            Err(error) => error.status.as_u16(),
+        };
+        span.record("status", status);
+        span.record("elapsed_ms", started.elapsed().as_millis() as u64);
+        self.record_breaker_outcome(result.as_ref().map(|_| ()), path)
+            .await;
+        result
+    }
+
+    async fn record_breaker_outcome(&self, outcome: Result<(), &UpstreamError>, path: &str) {
+        let mut breaker = self.breaker.write().await;
+        let previous = breaker.state();
+        breaker.record_outcome(outcome, Instant::now());
+        if breaker.state() == BreakerState::Open && previous != BreakerState::Open {
+            warn!(
+                phase = "upstream_circuit_opened",
+                path,
+                open_duration_secs = self.config.circuit_breaker_open_duration_secs,
+                "Upstream circuit breaker opened after repeated failures"
+            );
+        }
+    }
+
+    async fn send_with_retries<T: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &T,
+        model: &str,
+        ids: RequestIds<'_>,
+        timeout: Option<Duration>,
+        kind: RequestKind,
+    ) -> Result<reqwest::Response, UpstreamError> {
+        let policy = kind.retry_policy(&self.config);
+        let request_kind = kind.as_str();
+        let mut attempt = 0;
+        loop {
+            let failure = match self
+                .send_once(path, body, model, ids, timeout, request_kind)
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(failure) => failure,
+            };
+            if !failure.retryable || attempt >= policy.max_retries {
+                return Err(failure.error);
+            }
+
+            let delay = policy.delay_for(attempt, failure.retry_after);
+            attempt += 1;
+            warn!(
+                phase = "upstream_retry",
+                request_kind,
+                path,
+                session_id = ids.session_id,
+                request_id = ids.request_id,
+                attempt,
+                max_retries = policy.max_retries,
+                status = %failure.error.status,
+                delay_ms = delay.as_millis() as u64,
+                "Retrying upstream request after transient failure"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn send_once<T: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &T,
+        model: &str,
+        ids: RequestIds<'_>,
+        timeout: Option<Duration>,
+        request_kind: &'static str,
+    ) -> Result<reqwest::Response, SendFailure> {
+        let request_builder = self.build_request(path, body, ids, timeout, request_kind);
+        let timeout_secs = timeout.map(|value| value.as_secs());
+        let request_started = Instant::now();
+        let response = match request_builder.send().await {
+            Ok(value) => value,
+            Err(error) => {
+                let retryable = is_retryable_send_error(&error);
+                let elapsed = request_started.elapsed();
+                let error = build_send_error(error, timeout, request_kind, path, ids, elapsed);
+                return Err(SendFailure::new(error, retryable, None));
+            }
+        };
+        self.mark_warm();
+
+        log_response_headers(
+            &response,
+            request_kind,
+            path,
+            ids,
+            timeout_secs,
+            request_started.elapsed(),
+        );
+        metrics().observe_upstream_latency(model, request_kind, request_started.elapsed());
+
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        let retry_after = parse_retry_after(response.headers());
+        let error = handle_http_error_response(
+            response,
+            request_kind,
+            path,
+            ids,
+            &self.config.echo_upstream_headers,
+        )
+        .await;
+        let retryable = is_retryable_http_error(&error);
+        Err(SendFailure::new(error, retryable, retry_after))
+    }
+
+    fn build_request<T: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &T,
+        ids: RequestIds<'_>,
+        timeout: Option<Duration>,
+        request_kind: &'static str,
+    ) -> reqwest::RequestBuilder {
+        let url = format!("{}{}", self.next_base_url(), path);
+        Span::current().record("url", url.as_str());
+        let mut headers = build_upstream_headers(&self.config, ids);
+        inject_trace_context(&mut headers);
+
+        let mut request_builder = self.client.post(&url).headers(headers).json(body);
+
+        if let Some(api_version) = self.config.azure_api_version.as_deref() {
+            request_builder = request_builder.query(&[("api-version", api_version)]);
+        }
+
+        if let Some(duration) = timeout {
+            request_builder = request_builder.timeout(duration);
+        }
+
+        debug!(
+            phase = "upstream_request_start",
+            request_kind,
+            path,
+            session_id = ids.session_id,
+            request_id = ids.request_id,
+            url = %url,
+            timeout_secs = ?timeout.map(|value| value.as_secs()),
+            "Sending upstream request"
+        );
+        request_builder
+    }
+}
+
+struct SendFailure {
+    error: UpstreamError,
+    retryable: bool,
+    retry_after: Option<Duration>,
+}
+
+impl SendFailure {
+    fn new(error: UpstreamError, retryable: bool, retry_after: Option<Duration>) -> Self {
+        Self {
+            error,
+            retryable,
+            retry_after,
+        }
+    }
+}
+
+fn build_send_error(
+    error: reqwest::Error,
+    timeout: Option<Duration>,
+    request_kind: &'static str,
+    path: &str,
+    ids: RequestIds<'_>,
+    elapsed: Duration,
+) -> UpstreamError {
+    log_send_stage_error(&error, timeout, request_kind, path, ids, elapsed);
+    UpstreamError {
+        status: salvo::http::StatusCode::BAD_GATEWAY,
+        message: classify_openai_error(&format!("upstream request failed: {error}")),
+        kind: UpstreamErrorKind::Other,
+        metadata: UpstreamMetadata::default(),
+    }
+}
+
+fn log_send_stage_error(
+    error: &reqwest::Error,
+    timeout: Option<Duration>,
+    request_kind: &str,
+    path: &str,
+    ids: RequestIds<'_>,
+    elapsed: Duration,
+) {
+    let timeout_secs = timeout.map(|value| value.as_secs());
+
+    if error.is_timeout() {
+        error!(
+            phase = "upstream_connect_timeout",
+            request_kind,
+            path,
+            session_id = ids.session_id,
+            request_id = ids.request_id,
+            timeout_secs = ?timeout_secs,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Upstream timeout before response headers"
+        );
+        return;
+    }
+
+    if error.is_connect() {
+        error!(
+            phase = "upstream_connect_error",
+            request_kind,
+            path,
+            session_id = ids.session_id,
+            request_id = ids.request_id,
+            timeout_secs = ?timeout_secs,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Upstream connection failed before response headers: {error}"
+        );
+        return;
+    }
+
+    error!(
+        phase = "upstream_request_error",
+        request_kind,
+        path,
+        session_id = ids.session_id,
+        request_id = ids.request_id,
+        timeout_secs = ?timeout_secs,
+        elapsed_ms = elapsed.as_millis() as u64,
+        "Upstream request failed before response headers: {error}"
+    );
+}
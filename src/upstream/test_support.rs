@@ -0,0 +1,41 @@
+use crate::config::Config;
+
+/// Close to the `Config::load` defaults with a placeholder API key; tests
+/// override the fields they exercise.
+pub(crate) fn test_config() -> Config {
+    Config {
+        openai_api_key: "sk-test".to_string(),
+        openai_base_url: "https://api.openai.com/v1".to_string(),
+        openai_base_urls: vec!["https://api.openai.com/v1".to_string()],
+        host: "0.0.0.0".to_string(),
+        port: 8082,
+        log_level: "INFO".to_string(),
+        request_timeout: 90,
+        request_body_max_size: 16 * 1024 * 1024,
+        session_ttl_min_secs: 1800,
+        session_ttl_max_secs: 86400,
+        session_cleanup_interval_secs: 60,
+        batch_retention_secs: 86400,
+        big_model: "gpt-4o".to_string(),
+        middle_model: "gpt-4o".to_string(),
+        small_model: "gpt-4o-mini".to_string(),
+        min_tokens_thinking_fraction: 0.5,
+        cors_allowed_origins: vec!["*".to_string()],
+        min_compress_size_bytes: 1024,
+        metrics_enabled: true,
+        retry_base_delay_ms: 500,
+        retry_jitter_factor: 0.25,
+        circuit_breaker_failure_threshold: 5,
+        circuit_breaker_success_threshold: 1,
+        circuit_breaker_open_duration_secs: 30,
+        rate_limit_burst: 10,
+        system_block_separator: "\n\n".to_string(),
+        cache_boundary_separator: "\n\n---\n\n".to_string(),
+        preserve_thinking_signatures: true,
+        max_consecutive_send_errors: 3,
+        capture_dir: "captures".to_string(),
+        max_capture_file_size_mb: 100,
+        shutdown_timeout_secs: 30,
+        ..Config::default()
+    }
+}
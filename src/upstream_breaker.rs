@@ -0,0 +1,212 @@
+use std::time::{Duration, Instant};
+
+use salvo::http::StatusCode;
+
+use crate::config::Config;
+use crate::errors::{UpstreamError, UpstreamErrorKind};
+use crate::upstream_metadata::UpstreamMetadata;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// Counts consecutive upstream outages (send failures and 5xx responses);
+/// 4xx responses prove the upstream is reachable and count as successes.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    state: BreakerState,
+    failure_threshold: u32,
+    success_threshold: u32,
+    open_duration: Duration,
+    consecutive_failures: u32,
+    consecutive_successes: u32,
+    opened_at: Option<Instant>,
+    probe_started_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.circuit_breaker_failure_threshold,
+            config.circuit_breaker_success_threshold,
+            Duration::from_secs(config.circuit_breaker_open_duration_secs),
+        )
+    }
+
+    fn new(failure_threshold: u32, success_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            state: BreakerState::Closed,
+            failure_threshold,
+            success_threshold: success_threshold.max(1),
+            open_duration,
+            consecutive_failures: 0,
+            consecutive_successes: 0,
+            opened_at: None,
+            probe_started_at: None,
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    /// Returns whether a request may go upstream. Once the open period has
+    /// elapsed a single probe is let through; a probe that never reports back
+    /// is replaced after another open period.
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        if self.failure_threshold == 0 {
+            return true;
+        }
+        match self.state {
+            BreakerState::Closed => true,
+            BreakerState::Open => {
+                let elapsed = self.opened_at.map_or(self.open_duration, |at| now - at);
+                if elapsed < self.open_duration {
+                    return false;
+                }
+                self.state = BreakerState::HalfOpen;
+                self.consecutive_successes = 0;
+                self.probe_started_at = Some(now);
+                true
+            }
+            BreakerState::HalfOpen => {
+                let probe_stale = self
+                    .probe_started_at
+                    .is_none_or(|at| now - at >= self.open_duration);
+                if probe_stale {
+                    self.probe_started_at = Some(now);
+                }
+                probe_stale
+            }
+        }
+    }
+
+    pub fn record_outcome(&mut self, outcome: Result<(), &UpstreamError>, now: Instant) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+        match outcome {
+            Err(error) if is_outage(error) => self.record_failure(now),
+            _ => self.record_success(),
+        }
+    }
+
+    fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        if self.state != BreakerState::HalfOpen {
+            return;
+        }
+        self.probe_started_at = None;
+        self.consecutive_successes += 1;
+        if self.consecutive_successes >= self.success_threshold {
+            self.state = BreakerState::Closed;
+            self.opened_at = None;
+        }
+    }
+
+    fn record_failure(&mut self, now: Instant) {
+        self.consecutive_successes = 0;
+        self.probe_started_at = None;
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if self.state == BreakerState::HalfOpen
+            || self.consecutive_failures >= self.failure_threshold
+        {
+            self.state = BreakerState::Open;
+            self.opened_at = Some(now);
+        }
+    }
+}
+
+fn is_outage(error: &UpstreamError) -> bool {
+    error.status.is_server_error()
+}
+
+pub fn circuit_open_error() -> UpstreamError {
+    UpstreamError {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        message: "Upstream is unavailable (circuit breaker open); retry later".to_string(),
+        kind: UpstreamErrorKind::Other,
+        metadata: UpstreamMetadata::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BreakerState, CircuitBreaker};
+    use crate::errors::{UpstreamError, UpstreamErrorKind};
+    use crate::upstream_metadata::UpstreamMetadata;
+    use salvo::http::StatusCode;
+    use std::time::{Duration, Instant};
+
+    fn error(status: u16) -> UpstreamError {
+        UpstreamError {
+            status: StatusCode::from_u16(status).expect("valid status"),
+            message: "boom".to_string(),
+            kind: UpstreamErrorKind::Other,
+            metadata: UpstreamMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn opens_after_consecutive_outages_and_fails_fast() {
+        let mut breaker = CircuitBreaker::new(2, 1, Duration::from_secs(30));
+        let now = Instant::now();
+
+        breaker.record_outcome(Err(&error(502)), now);
+        breaker.record_outcome(Err(&error(400)), now);
+        breaker.record_outcome(Err(&error(503)), now);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        breaker.record_outcome(Err(&error(500)), now);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(!breaker.try_acquire(now + Duration::from_secs(29)));
+    }
+
+    #[test]
+    fn half_open_allows_single_probe_and_closes_on_success() {
+        let mut breaker = CircuitBreaker::new(1, 2, Duration::from_secs(10));
+        let now = Instant::now();
+        breaker.record_outcome(Err(&error(502)), now);
+
+        let later = now + Duration::from_secs(10);
+        assert!(breaker.try_acquire(later));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(!breaker.try_acquire(later));
+
+        breaker.record_outcome(Ok(()), later);
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.try_acquire(later));
+        breaker.record_outcome(Ok(()), later);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.try_acquire(later));
+    }
+
+    #[test]
+    fn failed_probe_reopens_breaker() {
+        let mut breaker = CircuitBreaker::new(3, 1, Duration::from_secs(10));
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_outcome(Err(&error(504)), now);
+        }
+
+        let later = now + Duration::from_secs(10);
+        assert!(breaker.try_acquire(later));
+        breaker.record_outcome(Err(&error(502)), later);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(!breaker.try_acquire(later + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn zero_failure_threshold_disables_breaker() {
+        let mut breaker = CircuitBreaker::new(0, 1, Duration::from_secs(10));
+        let now = Instant::now();
+        for _ in 0..10 {
+            breaker.record_outcome(Err(&error(502)), now);
+        }
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.try_acquire(now));
+    }
+}
@@ -0,0 +1,141 @@
+use reqwest::header::HeaderMap;
+use salvo::http::HeaderName;
+use salvo::prelude::Response;
+
+/// Prefix for headers copied verbatim via `echo_upstream_headers`, so they
+/// cannot collide with the bridge's own response headers.
+const ECHO_HEADER_PREFIX: &str = "x-upstream-";
+
+/// OpenAI rate-limit headers and the Anthropic names they are re-emitted as.
+const RATE_LIMIT_HEADER_MAP: [(&str, &str); 4] = [
+    (
+        "x-ratelimit-limit-requests",
+        "x-anthropic-ratelimit-requests-limit",
+    ),
+    (
+        "x-ratelimit-remaining-requests",
+        "x-anthropic-ratelimit-requests-remaining",
+    ),
+    (
+        "x-ratelimit-limit-tokens",
+        "x-anthropic-ratelimit-tokens-limit",
+    ),
+    (
+        "x-ratelimit-remaining-tokens",
+        "x-anthropic-ratelimit-tokens-remaining",
+    ),
+];
+
+/// Response headers worth surfacing to the client, captured from the
+/// upstream response before its body is consumed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UpstreamMetadata {
+    rate_limit_headers: Vec<(&'static str, String)>,
+    echoed_headers: Vec<(HeaderName, String)>,
+}
+
+impl UpstreamMetadata {
+    /// `echo_headers` are lowercase upstream header names from
+    /// `echo_upstream_headers`.
+    pub fn from_headers(headers: &HeaderMap, echo_headers: &[String]) -> Self {
+        let rate_limit_headers = RATE_LIMIT_HEADER_MAP
+            .iter()
+            .filter_map(|(upstream_name, claude_name)| {
+                Some((*claude_name, header_text(headers, upstream_name)?))
+            })
+            .collect();
+        let echoed_headers = echo_headers
+            .iter()
+            .filter_map(|name| {
+                let value = header_text(headers, name)?;
+                Some((echoed_header_name(name)?, value))
+            })
+            .collect();
+        Self {
+            rate_limit_headers,
+            echoed_headers,
+        }
+    }
+
+    pub fn apply_to(&self, res: &mut Response) {
+        for (name, value) in &self.rate_limit_headers {
+            let _ = res.add_header(*name, value, true);
+        }
+        for (name, value) in &self.echoed_headers {
+            let _ = res.add_header(name.clone(), value, true);
+        }
+    }
+}
+
+/// The name an upstream header is echoed back to the client as.
+pub fn echoed_header_name(upstream_name: &str) -> Option<HeaderName> {
+    HeaderName::from_bytes(format!("{ECHO_HEADER_PREFIX}{upstream_name}").as_bytes()).ok()
+}
+
+fn header_text(headers: &HeaderMap, name: &str) -> Option<String> {
+    let value = headers.get(name)?.to_str().ok()?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UpstreamMetadata;
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use salvo::prelude::Response;
+
+    #[test]
+    fn maps_openai_rate_limit_headers_to_anthropic_names() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-ratelimit-limit-requests",
+            HeaderValue::from_static("500"),
+        );
+        headers.insert(
+            "x-ratelimit-remaining-tokens",
+            HeaderValue::from_static("149000"),
+        );
+        headers.insert("x-ratelimit-reset-requests", HeaderValue::from_static("1s"));
+
+        let mut res = Response::new();
+        UpstreamMetadata::from_headers(&headers, &[]).apply_to(&mut res);
+
+        let header = |name: &str| {
+            res.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+        assert_eq!(
+            header("x-anthropic-ratelimit-requests-limit").as_deref(),
+            Some("500")
+        );
+        assert_eq!(
+            header("x-anthropic-ratelimit-tokens-remaining").as_deref(),
+            Some("149000")
+        );
+        assert_eq!(header("x-anthropic-ratelimit-requests-remaining"), None);
+        assert_eq!(res.headers().len(), 2);
+    }
+
+    #[test]
+    fn echoes_configured_headers_with_upstream_prefix() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("req_123"));
+        headers.insert(
+            "x-ms-client-request-id",
+            HeaderValue::from_static("azure-1"),
+        );
+
+        let mut res = Response::new();
+        let echo = ["x-request-id".to_string(), "apim-request-id".to_string()];
+        UpstreamMetadata::from_headers(&headers, &echo).apply_to(&mut res);
+
+        assert_eq!(
+            res.headers()
+                .get("x-upstream-x-request-id")
+                .and_then(|value| value.to_str().ok()),
+            Some("req_123")
+        );
+        assert_eq!(res.headers().len(), 1);
+    }
+}
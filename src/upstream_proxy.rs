@@ -0,0 +1,115 @@
+use reqwest::{Client, NoProxy, Proxy};
+
+use crate::config::Config;
+
+/// An explicit `UPSTREAM_PROXY` replaces reqwest's system proxy detection;
+/// without it the client keeps reqwest's defaults.
+pub fn build_http_client(config: &Config) -> Result<Client, String> {
+    let mut builder = Client::builder();
+    if let Some(proxy) = build_proxy(config)? {
+        builder = builder.proxy(proxy);
+    }
+    builder
+        .build()
+        .map_err(|error| format!("failed to initialize upstream HTTP client: {error}"))
+}
+
+fn build_proxy(config: &Config) -> Result<Option<Proxy>, String> {
+    let Some(proxy_url) = config.upstream_proxy.as_deref() else {
+        return Ok(None);
+    };
+
+    let mut proxy = Proxy::all(proxy_url)
+        .map_err(|error| format!("invalid UPSTREAM_PROXY {proxy_url:?}: {error}"))?;
+    if let Some((username, password)) = config
+        .upstream_proxy_basic_auth
+        .as_deref()
+        .and_then(|value| value.split_once(':'))
+    {
+        proxy = proxy.basic_auth(username, password);
+    }
+    if !config.no_proxy.is_empty() {
+        proxy = proxy.no_proxy(NoProxy::from_string(&config.no_proxy.join(",")));
+    }
+    Ok(Some(proxy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_http_client;
+    use crate::config::Config;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn proxied_config(proxy_url: String) -> Config {
+        let mut config = crate::upstream::test_support::test_config();
+        config.upstream_proxy = Some(proxy_url);
+        config.upstream_proxy_basic_auth = Some("alice:s3cret".to_string());
+        config
+    }
+
+    /// Accepts one connection, records the raw request head and answers 200.
+    async fn spawn_mock_proxy() -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let address = listener.local_addr().expect("local addr");
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.expect("accept");
+            let mut buffer = vec![0; 4096];
+            let read = socket.read(&mut buffer).await.expect("read");
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok")
+                .await
+                .expect("write");
+            String::from_utf8_lossy(&buffer[..read]).into_owned()
+        });
+        (format!("http://{address}"), handle)
+    }
+
+    #[tokio::test]
+    async fn routes_requests_through_proxy_with_basic_auth() {
+        let (proxy_url, handle) = spawn_mock_proxy().await;
+        let client = build_http_client(&proxied_config(proxy_url)).expect("client");
+
+        let response = client
+            .get("http://upstream.invalid/v1/models")
+            .send()
+            .await
+            .expect("proxied response");
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        let request_head = handle.await.expect("proxy task").to_ascii_lowercase();
+        assert!(request_head.starts_with("get http://upstream.invalid/v1/models http/1.1"));
+        // base64("alice:s3cret")
+        assert!(request_head.contains("proxy-authorization: basic ywxpy2u6cznjcmv0"));
+    }
+
+    #[tokio::test]
+    async fn bypasses_proxy_for_no_proxy_hosts() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let upstream_url = format!("http://{}/v1/models", upstream.local_addr().expect("addr"));
+        let served = tokio::spawn(async move {
+            let (mut socket, _) = upstream.accept().await.expect("accept");
+            let mut buffer = vec![0; 4096];
+            let _ = socket.read(&mut buffer).await;
+            socket
+                .write_all(b"HTTP/1.1 204 No Content\r\nconnection: close\r\n\r\n")
+                .await
+                .expect("write");
+        });
+
+        let mut config = proxied_config("http://127.0.0.1:9".to_string());
+        config.no_proxy = vec!["127.0.0.1".to_string()];
+        let client = build_http_client(&config).expect("client");
+
+        let response = client.get(upstream_url).send().await.expect("direct");
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+        served.await.expect("upstream task");
+    }
+
+    #[test]
+    fn rejects_invalid_proxy_url() {
+        let error = build_http_client(&proxied_config("::not a url".to_string()))
+            .expect_err("invalid proxy");
+        assert!(error.contains("UPSTREAM_PROXY"));
+    }
+}
@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use salvo::http::StatusCode;
+
+use crate::config::Config;
+use crate::errors::UpstreamError;
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Which upstream call is being sent; also the `request_kind` log field and
+/// latency metric label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestKind {
+    NonStream,
+    Stream,
+}
+
+impl RequestKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NonStream => "non_stream",
+            Self::Stream => "stream",
+        }
+    }
+
+    /// Streaming requests are sent once. The client is waiting on an open
+    /// connection for its first event, so a failed stream is reported at
+    /// once instead of after backoff delays; the client can resend it.
+    pub fn retry_policy(self, config: &Config) -> RetryPolicy {
+        match self {
+            Self::NonStream => RetryPolicy::from_config(config),
+            Self::Stream => RetryPolicy::disabled(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    base_delay: Duration,
+    jitter_factor: f64,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            base_delay: Duration::from_millis(config.retry_base_delay_ms),
+            jitter_factor: config.retry_jitter_factor,
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::ZERO,
+            jitter_factor: 0.0,
+        }
+    }
+
+    /// Exponential backoff scaled by `1 ± jitter_factor` so clients that
+    /// failed together do not retry together. `Retry-After` stays a floor.
+    pub fn delay_for(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_RETRY_DELAY);
+        let delay = retry_after.map_or(backoff, |floor| backoff.max(floor));
+        let jittered = self.apply_jitter(delay);
+        retry_after.map_or(jittered, |floor| jittered.max(floor))
+    }
+
+    fn apply_jitter(&self, delay: Duration) -> Duration {
+        if self.jitter_factor <= 0.0 {
+            return delay;
+        }
+        let offset: f64 = rand::thread_rng().gen_range(-1.0..=1.0);
+        delay.mul_f64(1.0 + self.jitter_factor * offset)
+    }
+}
+
+pub fn is_retryable_send_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
+/// A 500 carried on an upstream HTTP response is treated as transient here,
+/// unlike the locally built 500s that `UpstreamError::is_retryable` rejects.
+pub fn is_retryable_http_error(error: &UpstreamError) -> bool {
+    error.status == StatusCode::INTERNAL_SERVER_ERROR || error.is_retryable()
+}
+
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RequestKind, RetryPolicy, is_retryable_http_error, parse_retry_after};
+    use crate::errors::{UpstreamError, UpstreamErrorKind};
+    use crate::upstream_metadata::UpstreamMetadata;
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use salvo::http::StatusCode;
+    use std::time::Duration;
+
+    fn policy(base_ms: u64) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(base_ms),
+            jitter_factor: 0.0,
+        }
+    }
+
+    #[test]
+    fn only_non_stream_requests_use_the_configured_retries() {
+        let mut config = crate::upstream::test_support::test_config();
+        config.max_retries = 3;
+        assert_eq!(RequestKind::NonStream.retry_policy(&config).max_retries, 3);
+        assert_eq!(RequestKind::Stream.retry_policy(&config).max_retries, 0);
+    }
+
+    #[test]
+    fn delay_doubles_per_attempt_and_is_capped() {
+        let policy = policy(500);
+        assert_eq!(policy.delay_for(0, None), Duration::from_millis(500));
+        assert_eq!(policy.delay_for(1, None), Duration::from_millis(1000));
+        assert_eq!(policy.delay_for(2, None), Duration::from_millis(2000));
+        assert_eq!(policy.delay_for(20, None), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn retry_after_acts_as_delay_floor() {
+        let policy = policy(500);
+        assert_eq!(
+            policy.delay_for(0, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            policy.delay_for(3, Some(Duration::from_secs(1))),
+            Duration::from_secs(4)
+        );
+    }
+
+    #[test]
+    fn jitter_spreads_delays_within_the_configured_window() {
+        let policy = RetryPolicy {
+            jitter_factor: 0.25,
+            ..policy(1000)
+        };
+        let delays: Vec<Duration> = (0..32).map(|_| policy.delay_for(0, None)).collect();
+
+        assert!(delays.iter().all(|delay| {
+            (Duration::from_millis(750)..=Duration::from_millis(1250)).contains(delay)
+        }));
+        assert!(delays.iter().any(|delay| *delay != delays[0]));
+        assert!(
+            (0..32).all(
+                |_| policy.delay_for(0, Some(Duration::from_secs(2))) >= Duration::from_secs(2)
+            )
+        );
+    }
+
+    #[test]
+    fn http_errors_retry_on_transient_statuses_only() {
+        for (status, expected) in [
+            (429, true),
+            (500, true),
+            (502, true),
+            (503, true),
+            (504, true),
+            (400, false),
+            (401, false),
+            (403, false),
+            (404, false),
+            (413, false),
+        ] {
+            let error = UpstreamError {
+                status: StatusCode::from_u16(status).expect("valid status"),
+                message: "boom".to_string(),
+                kind: UpstreamErrorKind::Other,
+                metadata: UpstreamMetadata::default(),
+            };
+            assert_eq!(is_retryable_http_error(&error), expected, "{status}");
+        }
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static(" 7 "));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+}
@@ -1,21 +1,32 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use opentelemetry_sdk::trace::SdkTracer;
 use salvo::http::StatusCode;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::config::LogFormat;
 
 pub fn to_salvo_status(status: reqwest::StatusCode) -> StatusCode {
     StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::BAD_GATEWAY)
 }
 
-pub fn now_timestamp_string() -> String {
+pub fn now_unix_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs()
-        .to_string()
 }
 
-pub fn init_tracing(log_level: &str) {
+pub fn now_timestamp_string() -> String {
+    now_unix_secs().to_string()
+}
+
+/// JSON mode flattens event fields (`phase`, `session_id`, ...) into
+/// top-level keys so log aggregators can index them directly. With a
+/// `tracer`, spans are additionally exported through OpenTelemetry.
+pub fn init_tracing(log_level: &str, log_format: LogFormat, tracer: Option<SdkTracer>) {
     let normalized = log_level
         .split_whitespace()
         .next()
@@ -23,5 +34,17 @@ pub fn init_tracing(log_level: &str) {
         .to_lowercase();
 
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(normalized));
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    let fmt_layer = match log_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .boxed(),
+    };
+    let otel_layer = tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer)
+        .with(otel_layer)
+        .init();
 }
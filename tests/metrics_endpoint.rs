@@ -0,0 +1,111 @@
+//! Runs the built binary so the process-global application state stays out of
+//! the unit-test process.
+
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::json;
+
+const COMPLETED: &str = r#"{"id":"chatcmpl-1","choices":[{"index":0,"message":{"role":"assistant","content":"ok"},"finish_reason":"stop"}],"usage":{"prompt_tokens":3,"completion_tokens":1}}"#;
+
+/// Answers every connection with one canned chat completion.
+async fn spawn_completion_upstream() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let address = listener.local_addr().expect("local addr");
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buffer = vec![0; 16_384];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buffer).await;
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{COMPLETED}",
+                COMPLETED.len()
+            );
+            let _ = tokio::io::AsyncWriteExt::write_all(&mut socket, reply.as_bytes()).await;
+        }
+    });
+    format!("http://{address}/v1")
+}
+
+fn free_port() -> u16 {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    listener.local_addr().expect("local addr").port()
+}
+
+struct Bridge(Child);
+
+impl Drop for Bridge {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Started from the temp dir with a cleared environment so a developer's
+/// `.env`, `config.toml` or exported settings are not picked up.
+fn spawn_bridge(base_url: &str, port: u16) -> Bridge {
+    let child = Command::new(env!("CARGO_BIN_EXE_claude-openai-bridge"))
+        .current_dir(std::env::temp_dir())
+        .env_clear()
+        .env("OPENAI_API_KEY", "sk-test")
+        .env("OPENAI_BASE_URL", base_url)
+        .env("HOST", "127.0.0.1")
+        .env("PORT", port.to_string())
+        .env("LOG_LEVEL", "WARN")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("bridge binary should start");
+    Bridge(child)
+}
+
+async fn wait_until_listening(client: &reqwest::Client, base: &str) {
+    for _ in 0..100 {
+        if client.get(base).send().await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("bridge did not start listening on {base}");
+}
+
+#[tokio::test]
+async fn counts_a_proxied_messages_call_in_requests_total() {
+    let port = free_port();
+    let _bridge = spawn_bridge(&spawn_completion_upstream().await, port);
+    let base = format!("http://127.0.0.1:{port}");
+    let client = reqwest::Client::new();
+    wait_until_listening(&client, &base).await;
+
+    let response = client
+        .post(format!("{base}/v1/messages"))
+        .json(&json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 16,
+            "messages": [{"role": "user", "content": "hi"}]
+        }))
+        .send()
+        .await
+        .expect("messages request");
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+    let output = client
+        .get(format!("{base}/metrics"))
+        .send()
+        .await
+        .expect("metrics request")
+        .text()
+        .await
+        .expect("metrics body");
+    assert!(
+        output.contains(r#"bridge_requests_total{endpoint="/v1/messages",status="200"} 1"#),
+        "missing request count in:\n{output}"
+    );
+    assert!(
+        output.contains(
+            r#"bridge_messages_requests_total{model_tier="middle",stream="false",wire_api="chat"} 1"#
+        ),
+        "missing messages count in:\n{output}"
+    );
+}